@@ -0,0 +1,382 @@
+use aes_gcm::Aes256Gcm;
+use chacha20poly1305::{
+    aead::{generic_array::GenericArray, rand_core::RngCore, Aead, KeyInit, OsRng, Payload},
+    ChaCha20Poly1305, XChaCha20Poly1305,
+};
+
+use crate::{Error, Result};
+
+/// Version of the ciphertext format
+const VERSION: u8 = 1;
+/// Length of the ciphertext header (version and algorithm)
+const HEADER_LEN: usize = 2;
+/// Length of the authentication tag in bytes
+const TAG_LEN: usize = 16;
+
+/// Authenticated encryption algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlgorithmAead {
+    /// Read about AES-GCM in [wikipedia](https://en.wikipedia.org/wiki/Galois/Counter_Mode)
+    Aes256Gcm,
+    /// Read about ChaCha20-Poly1305 in [wikipedia](https://en.wikipedia.org/wiki/ChaCha20-Poly1305)
+    ChaCha20Poly1305,
+    /// ChaCha20-Poly1305 with an extended 192-bit nonce, recommended for random nonces
+    XChaCha20Poly1305,
+}
+
+impl AlgorithmAead {
+    /// Length of the key in bytes
+    pub const KEY_LEN: usize = 32;
+
+    /// Length of the nonce in bytes
+    pub fn nonce_len(&self) -> usize {
+        match self {
+            Self::Aes256Gcm | Self::ChaCha20Poly1305 => 12,
+            Self::XChaCha20Poly1305 => 24,
+        }
+    }
+
+    /// Identifier of the algorithm in the ciphertext header
+    pub(super) fn id(&self) -> u8 {
+        match self {
+            Self::Aes256Gcm => 1,
+            Self::ChaCha20Poly1305 => 2,
+            Self::XChaCha20Poly1305 => 3,
+        }
+    }
+
+    pub(super) fn from_id(id: u8) -> Result<Self> {
+        match id {
+            1 => Ok(Self::Aes256Gcm),
+            2 => Ok(Self::ChaCha20Poly1305),
+            3 => Ok(Self::XChaCha20Poly1305),
+            _ => Err(Error::InvalidAlgorithm),
+        }
+    }
+}
+
+/// Encrypt and decrypt data (AES-256-GCM, ChaCha20-Poly1305, XChaCha20-Poly1305).
+///
+/// ```
+/// use crypto_utils::aead::{AlgorithmAead, CryptographicAead};
+///
+/// // create a new cipher
+/// let cipher = CryptographicAead::new(AlgorithmAead::ChaCha20Poly1305, &[0u8; 32]).unwrap();
+///
+/// // encrypt data
+/// let ciphertext: Vec<u8> = cipher.encrypt(b"input", b"").unwrap();
+///
+/// // decrypt data
+/// let plaintext: Vec<u8> = cipher.decrypt(&ciphertext, b"").unwrap();
+///
+/// assert_eq!(plaintext, b"input");
+/// ```
+pub enum CryptographicAead {
+    /// AES-256-GCM cipher (boxed because of the size of the expanded key)
+    Aes256Gcm(Box<Aes256Gcm>),
+    /// ChaCha20-Poly1305 cipher
+    ChaCha20Poly1305(ChaCha20Poly1305),
+    /// XChaCha20-Poly1305 cipher
+    XChaCha20Poly1305(XChaCha20Poly1305),
+}
+
+impl CryptographicAead {
+    /// Create a new cipher using a 256-bit key.
+    ///
+    /// ```no_run
+    /// use crypto_utils::aead::{AlgorithmAead, CryptographicAead};
+    ///
+    /// // AES-256-GCM
+    /// let cipher = CryptographicAead::new(AlgorithmAead::Aes256Gcm, &[0u8; 32]).unwrap();
+    ///
+    /// // ChaCha20-Poly1305
+    /// let cipher = CryptographicAead::new(AlgorithmAead::ChaCha20Poly1305, &[0u8; 32]).unwrap();
+    ///
+    /// // XChaCha20-Poly1305
+    /// let cipher = CryptographicAead::new(AlgorithmAead::XChaCha20Poly1305, &[0u8; 32]).unwrap();
+    /// ```
+    pub fn new(algo: AlgorithmAead, key: impl AsRef<[u8]>) -> Result<Self> {
+        let key = key.as_ref();
+
+        if key.len() != AlgorithmAead::KEY_LEN {
+            return Err(Error::InvalidKeyLength);
+        }
+
+        Ok(match algo {
+            AlgorithmAead::Aes256Gcm => Self::Aes256Gcm(Box::new(
+                Aes256Gcm::new_from_slice(key).map_err(|_| Error::InvalidKeyLength)?,
+            )),
+            AlgorithmAead::ChaCha20Poly1305 => Self::ChaCha20Poly1305(
+                ChaCha20Poly1305::new_from_slice(key).map_err(|_| Error::InvalidKeyLength)?,
+            ),
+            AlgorithmAead::XChaCha20Poly1305 => Self::XChaCha20Poly1305(
+                XChaCha20Poly1305::new_from_slice(key).map_err(|_| Error::InvalidKeyLength)?,
+            ),
+        })
+    }
+
+    /// Algorithm of the cipher
+    pub fn algorithm(&self) -> AlgorithmAead {
+        match self {
+            Self::Aes256Gcm(_) => AlgorithmAead::Aes256Gcm,
+            Self::ChaCha20Poly1305(_) => AlgorithmAead::ChaCha20Poly1305,
+            Self::XChaCha20Poly1305(_) => AlgorithmAead::XChaCha20Poly1305,
+        }
+    }
+
+    /// Encrypt data with a random nonce, `aad` is authenticated but not encrypted.
+    ///
+    /// ```no_run
+    /// # use crypto_utils::aead::{AlgorithmAead, CryptographicAead};
+    /// #
+    /// # let cipher = CryptographicAead::new(AlgorithmAead::Aes256Gcm, &[0u8; 32]).unwrap();
+    /// #
+    /// let ciphertext: Vec<u8> = cipher.encrypt(b"input", b"associated data").unwrap();
+    /// ```
+    pub fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+        let header = [VERSION, self.algorithm().id()];
+
+        let mut nonce = vec![0u8; self.algorithm().nonce_len()];
+        OsRng.fill_bytes(&mut nonce);
+
+        let ciphertext = self.encrypt_with_nonce(&nonce, plaintext, &[&header, aad].concat())?;
+
+        Ok([&header, nonce.as_slice(), &ciphertext].concat())
+    }
+
+    /// Decrypt data encrypted with the same algorithm and key.
+    ///
+    /// ```no_run
+    /// # use crypto_utils::aead::{AlgorithmAead, CryptographicAead};
+    /// #
+    /// # let cipher = CryptographicAead::new(AlgorithmAead::Aes256Gcm, &[0u8; 32]).unwrap();
+    /// # let ciphertext: Vec<u8> = cipher.encrypt(b"input", b"associated data").unwrap();
+    /// #
+    /// let plaintext: Vec<u8> = cipher.decrypt(&ciphertext, b"associated data").unwrap();
+    /// ```
+    pub fn decrypt(&self, ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+        let algo = parse_header(ciphertext)?;
+
+        // the algorithm of the ciphertext must match the cipher
+        if algo != self.algorithm() {
+            return Err(Error::InvalidAlgorithm);
+        }
+
+        let (header, rest) = ciphertext.split_at(HEADER_LEN);
+
+        if rest.len() < algo.nonce_len() + TAG_LEN {
+            return Err(Error::InvalidCiphertext);
+        }
+
+        let (nonce, ciphertext) = rest.split_at(algo.nonce_len());
+
+        self.decrypt_with_nonce(nonce, ciphertext, &[header, aad].concat())
+    }
+
+    /// Encrypt data using the given nonce, returns the ciphertext with the appended tag.
+    pub(super) fn encrypt_with_nonce(
+        &self,
+        nonce: &[u8],
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>> {
+        match self {
+            Self::Aes256Gcm(cipher) => seal(cipher.as_ref(), nonce, plaintext, aad),
+            Self::ChaCha20Poly1305(cipher) => seal(cipher, nonce, plaintext, aad),
+            Self::XChaCha20Poly1305(cipher) => seal(cipher, nonce, plaintext, aad),
+        }
+    }
+
+    /// Decrypt data (the ciphertext with the appended tag) using the given nonce.
+    pub(super) fn decrypt_with_nonce(
+        &self,
+        nonce: &[u8],
+        ciphertext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>> {
+        match self {
+            Self::Aes256Gcm(cipher) => open(cipher.as_ref(), nonce, ciphertext, aad),
+            Self::ChaCha20Poly1305(cipher) => open(cipher, nonce, ciphertext, aad),
+            Self::XChaCha20Poly1305(cipher) => open(cipher, nonce, ciphertext, aad),
+        }
+    }
+}
+
+/// Encrypt data using a single function
+///
+/// ```
+/// use crypto_utils::aead::{encrypt, AlgorithmAead};
+///
+/// let ciphertext: Vec<u8> = encrypt(AlgorithmAead::Aes256Gcm, &[0u8; 32], b"input").unwrap();
+/// ```
+pub fn encrypt(algo: AlgorithmAead, key: impl AsRef<[u8]>, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let key = key.as_ref();
+
+    CryptographicAead::new(algo, key)?.encrypt(plaintext, b"")
+}
+
+/// Decrypt data using a single function, the algorithm is read from the ciphertext
+///
+/// ```
+/// use crypto_utils::aead::{decrypt, encrypt, AlgorithmAead};
+///
+/// let ciphertext = encrypt(AlgorithmAead::Aes256Gcm, &[0u8; 32], b"input").unwrap();
+///
+/// let plaintext: Vec<u8> = decrypt(&[0u8; 32], &ciphertext).unwrap();
+/// ```
+pub fn decrypt(key: impl AsRef<[u8]>, ciphertext: &[u8]) -> Result<Vec<u8>> {
+    let key = key.as_ref();
+
+    let algo = parse_header(ciphertext)?;
+
+    CryptographicAead::new(algo, key)?.decrypt(ciphertext, b"")
+}
+
+/// Parse the version and algorithm of the ciphertext.
+fn parse_header(ciphertext: &[u8]) -> Result<AlgorithmAead> {
+    match ciphertext {
+        [VERSION, algo, ..] => AlgorithmAead::from_id(*algo),
+        _ => Err(Error::InvalidCiphertext),
+    }
+}
+
+fn seal<C: Aead>(cipher: &C, nonce: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    cipher
+        .encrypt(
+            GenericArray::from_slice(nonce),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| Error::InvalidCiphertext)
+}
+
+fn open<C: Aead>(cipher: &C, nonce: &[u8], ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    cipher
+        .decrypt(
+            GenericArray::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| Error::Decryption)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt, encrypt, AlgorithmAead, CryptographicAead, Error};
+
+    const KEY: &[u8] = &[7u8; 32];
+    const INPUT: &[u8] = b"input";
+
+    const ALGORITHMS: [AlgorithmAead; 3] = [
+        AlgorithmAead::Aes256Gcm,
+        AlgorithmAead::ChaCha20Poly1305,
+        AlgorithmAead::XChaCha20Poly1305,
+    ];
+
+    /// Encrypt and decrypt data using every algorithm
+    #[test]
+    fn encrypt_decrypt() {
+        for algo in ALGORITHMS {
+            let ciphertext = encrypt(algo, KEY, INPUT).unwrap();
+
+            // version || algorithm || nonce || ciphertext || tag
+            assert_eq!(ciphertext.len(), 2 + algo.nonce_len() + INPUT.len() + 16);
+            assert_eq!(ciphertext[0], 1);
+
+            let plaintext = decrypt(KEY, &ciphertext).unwrap();
+
+            assert_eq!(plaintext, INPUT);
+        }
+    }
+
+    /// Encrypting the same data twice uses different nonces
+    #[test]
+    fn encrypt_random_nonce() {
+        for algo in ALGORITHMS {
+            assert_ne!(
+                encrypt(algo, KEY, INPUT).unwrap(),
+                encrypt(algo, KEY, INPUT).unwrap()
+            );
+        }
+    }
+
+    /// Decrypt data with invalid key
+    #[test]
+    fn decrypt_key_invalid() {
+        for algo in ALGORITHMS {
+            let ciphertext = encrypt(algo, KEY, INPUT).unwrap();
+
+            let err = decrypt([8u8; 32], &ciphertext).unwrap_err();
+
+            assert!(matches!(err, Error::Decryption));
+        }
+    }
+
+    /// Decrypt modified data
+    #[test]
+    fn decrypt_modified() {
+        for algo in ALGORITHMS {
+            let mut ciphertext = encrypt(algo, KEY, INPUT).unwrap();
+
+            // flip a bit of the ciphertext
+            let last = ciphertext.len() - 1;
+            ciphertext[last] ^= 1;
+
+            let err = decrypt(KEY, &ciphertext).unwrap_err();
+
+            assert!(matches!(err, Error::Decryption));
+        }
+    }
+
+    /// Decrypt data with invalid associated data
+    #[test]
+    fn decrypt_aad_invalid() {
+        let cipher = CryptographicAead::new(AlgorithmAead::Aes256Gcm, KEY).unwrap();
+
+        let ciphertext = cipher.encrypt(INPUT, b"user:1").unwrap();
+
+        let err = cipher.decrypt(&ciphertext, b"user:2").unwrap_err();
+
+        assert!(matches!(err, Error::Decryption));
+    }
+
+    /// Decrypt data encrypted with other algorithm
+    #[test]
+    fn decrypt_algorithm_invalid() {
+        let cipher = CryptographicAead::new(AlgorithmAead::ChaCha20Poly1305, KEY).unwrap();
+
+        let ciphertext = encrypt(AlgorithmAead::Aes256Gcm, KEY, INPUT).unwrap();
+
+        let err = cipher.decrypt(&ciphertext, b"").unwrap_err();
+        assert!(matches!(err, Error::InvalidAlgorithm));
+
+        // changing the algorithm byte doesn't help, the header is authenticated
+        let mut ciphertext = ciphertext;
+        ciphertext[1] = 2;
+
+        let err = cipher.decrypt(&ciphertext, b"").unwrap_err();
+        assert!(matches!(err, Error::Decryption));
+    }
+
+    /// Decrypt data with unknown version or too short data
+    #[test]
+    fn decrypt_invalid() {
+        let err = decrypt(KEY, &[2, 1, 0, 0]).unwrap_err();
+        assert!(matches!(err, Error::InvalidCiphertext));
+
+        let err = decrypt(KEY, &[1, 1, 0, 0]).unwrap_err();
+        assert!(matches!(err, Error::InvalidCiphertext));
+    }
+
+    /// Create cipher with invalid key length
+    #[test]
+    fn key_length_invalid() {
+        let err = encrypt(AlgorithmAead::Aes256Gcm, [0u8; 16], INPUT).unwrap_err();
+
+        assert!(matches!(err, Error::InvalidKeyLength));
+    }
+}
@@ -0,0 +1,81 @@
+//! Module for authenticated symmetric encryption (AES-256-GCM, ChaCha20-Poly1305 and XChaCha20-Poly1305).
+//!
+//! Nonces are generated randomly for every message and the output is self-describing,
+//! so the ciphertext can be decrypted knowing only the key:
+//!
+//! ```text
+//! version (1 byte) || algorithm (1 byte) || nonce || ciphertext || tag (16 bytes)
+//! ```
+//!
+//! The version and algorithm bytes are authenticated together with the optional associated data.
+//!
+//! ## Encrypt and decrypt
+//!
+//! ```
+//! use crypto_utils::aead::{decrypt, encrypt, AlgorithmAead};
+//!
+//! // 256-bit key
+//! let key = [0u8; 32];
+//!
+//! // encrypt data
+//! let ciphertext: Vec<u8> = encrypt(AlgorithmAead::XChaCha20Poly1305, &key, b"input").unwrap();
+//!
+//! // decrypt data
+//! let plaintext: Vec<u8> = decrypt(&key, &ciphertext).unwrap();
+//!
+//! assert_eq!(plaintext, b"input");
+//! ```
+//!
+//! ## Associated data
+//!
+//! Associated data is authenticated but not encrypted, e.g. to bind an encrypted database
+//! column to the row id:
+//!
+//! ```
+//! use crypto_utils::aead::{AlgorithmAead, CryptographicAead};
+//!
+//! // create a new cipher
+//! let cipher = CryptographicAead::new(AlgorithmAead::Aes256Gcm, &[0u8; 32]).unwrap();
+//!
+//! // encrypt data
+//! let ciphertext = cipher.encrypt(b"user@example.com", b"user:1234").unwrap();
+//!
+//! // decrypt data
+//! let plaintext = cipher.decrypt(&ciphertext, b"user:1234").unwrap();
+//!
+//! assert_eq!(plaintext, b"user@example.com");
+//! ```
+//!
+//! ## Streaming
+//!
+//! Large files are encrypted in chunks with the STREAM construction, so they never have to fit
+//! in memory. Truncated, reordered or modified chunks are detected when decrypting.
+//!
+//! ```
+//! use std::io::Write;
+//!
+//! use crypto_utils::{
+//!     aead::{AlgorithmAead, DecryptReader, EncryptWriter},
+//!     sha::{Algorithm, CryptographicHash},
+//! };
+//!
+//! let key = [0u8; 32];
+//!
+//! // encrypt data written to the writer
+//! let mut writer = EncryptWriter::new(AlgorithmAead::Aes256Gcm, &key, Vec::new()).unwrap();
+//! writer.write_all(b"backup").unwrap();
+//! let encrypted = writer.finish().unwrap();
+//!
+//! // decrypt data and hash it on the fly
+//! let mut reader = DecryptReader::new(&key, encrypted.as_slice()).unwrap();
+//! let mut hasher = CryptographicHash::new(Algorithm::SHA256);
+//! std::io::copy(&mut reader, &mut hasher).unwrap();
+//!
+//! let hash: Vec<u8> = hasher.finalize();
+//! ```
+
+mod cipher;
+mod stream;
+
+pub use cipher::*;
+pub use stream::*;
@@ -0,0 +1,478 @@
+use std::io::{self, Read, Write};
+
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+
+use super::{AlgorithmAead, CryptographicAead};
+use crate::{
+    sha::{AlgorithmMac, CryptographicMac},
+    Error, Result,
+};
+
+/// Version of the stream format, the high bit distinguishes it from the single-shot ciphertext
+const STREAM_VERSION: u8 = 0x81;
+/// Length of the random salt used to derive the stream key
+const SALT_LEN: usize = 32;
+/// Length of the stream header (version, algorithm and salt)
+const HEADER_LEN: usize = 2 + SALT_LEN;
+/// Length of the authentication tag in bytes
+const TAG_LEN: usize = 16;
+
+/// Size of the plaintext chunk in bytes, every chunk except the last one has this size
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Encrypt data written to the writer using the STREAM construction.
+///
+/// The data is split into chunks of [CHUNK_SIZE] bytes, every chunk is encrypted with a nonce
+/// made of its index and a flag marking the last chunk. Every stream uses its own key derived
+/// from the key and a random salt, so the nonces never repeat. The stream must be completed with
+/// [finish](EncryptWriter::finish), otherwise it is treated as truncated.
+///
+/// ```
+/// use std::io::Write;
+///
+/// use crypto_utils::aead::{AlgorithmAead, DecryptReader, EncryptWriter};
+///
+/// let key = [0u8; 32];
+///
+/// // encrypt data
+/// let mut writer = EncryptWriter::new(AlgorithmAead::XChaCha20Poly1305, &key, Vec::new()).unwrap();
+/// writer.write_all(b"input").unwrap();
+/// let encrypted: Vec<u8> = writer.finish().unwrap();
+///
+/// // decrypt data
+/// let mut reader = DecryptReader::new(&key, encrypted.as_slice()).unwrap();
+/// let mut decrypted = Vec::new();
+/// std::io::copy(&mut reader, &mut decrypted).unwrap();
+///
+/// assert_eq!(decrypted, b"input");
+/// ```
+pub struct EncryptWriter<W: Write> {
+    inner: W,
+    cipher: CryptographicAead,
+    header: Vec<u8>,
+    buffer: Vec<u8>,
+    counter: u32,
+}
+
+impl<W: Write> EncryptWriter<W> {
+    /// Create a new encrypting writer and write the stream header to the inner writer.
+    pub fn new(algo: AlgorithmAead, key: impl AsRef<[u8]>, mut inner: W) -> Result<Self> {
+        let key = key.as_ref();
+
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+
+        let header = [&[STREAM_VERSION, algo.id()], salt.as_slice()].concat();
+        let cipher = CryptographicAead::new(algo, &stream_key(key, &salt)?)?;
+
+        inner.write_all(&header)?;
+
+        Ok(Self {
+            inner,
+            cipher,
+            header,
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+            counter: 0,
+        })
+    }
+
+    /// Encrypt the last chunk and return the inner writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.seal_chunk(true)?;
+        self.inner.flush()?;
+
+        Ok(self.inner)
+    }
+
+    /// Encrypt the next chunk from the buffer and write it to the inner writer.
+    fn seal_chunk(&mut self, last: bool) -> Result<()> {
+        let len = self.buffer.len().min(CHUNK_SIZE);
+
+        let nonce = chunk_nonce(self.cipher.algorithm(), self.counter, last);
+        let chunk = self
+            .cipher
+            .encrypt_with_nonce(&nonce, &self.buffer[..len], &self.header)?;
+
+        self.inner.write_all(&chunk)?;
+        self.buffer.drain(..len);
+
+        self.counter = self
+            .counter
+            .checked_add(1)
+            .ok_or(Error::InvalidCiphertext)?;
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for EncryptWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+
+        // keep at least one byte buffered, so the last chunk is never a full chunk of nothing
+        while self.buffer.len() > CHUNK_SIZE {
+            self.seal_chunk(false).map_err(into_io)?;
+        }
+
+        Ok(buf.len())
+    }
+
+    /// Flush the inner writer, the buffered part of the chunk is written by [finish](EncryptWriter::finish).
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decrypt data read from the reader, encrypted with [EncryptWriter].
+///
+/// Modified, reordered or missing chunks cause a read error, and so does a stream that ends
+/// before its last chunk.
+pub struct DecryptReader<R: Read> {
+    inner: R,
+    cipher: CryptographicAead,
+    header: Vec<u8>,
+    counter: u32,
+    encrypted: Vec<u8>,
+    plaintext: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+impl<R: Read> DecryptReader<R> {
+    /// Create a new decrypting reader, the stream header is read from the inner reader.
+    pub fn new(key: impl AsRef<[u8]>, mut inner: R) -> Result<Self> {
+        let key = key.as_ref();
+
+        let mut header = vec![0u8; HEADER_LEN];
+
+        inner
+            .read_exact(&mut header)
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::UnexpectedEof => Error::InvalidCiphertext,
+                _ => Error::Io(err),
+            })?;
+
+        if header[0] != STREAM_VERSION {
+            return Err(Error::InvalidCiphertext);
+        }
+
+        let algo = AlgorithmAead::from_id(header[1])?;
+        let cipher = CryptographicAead::new(algo, &stream_key(key, &header[2..])?)?;
+
+        Ok(Self {
+            inner,
+            cipher,
+            header,
+            counter: 0,
+            encrypted: Vec::with_capacity(CHUNK_SIZE + TAG_LEN + 1),
+            plaintext: Vec::new(),
+            position: 0,
+            finished: false,
+        })
+    }
+
+    /// Read and decrypt the next chunk.
+    fn open_chunk(&mut self) -> Result<()> {
+        let full = CHUNK_SIZE + TAG_LEN;
+
+        // read one byte more than a full chunk to know if this chunk is the last one
+        let needed = (full + 1).saturating_sub(self.encrypted.len());
+        (&mut self.inner)
+            .take(needed as u64)
+            .read_to_end(&mut self.encrypted)?;
+
+        let last = self.encrypted.len() <= full;
+        let chunk: Vec<u8> = self
+            .encrypted
+            .drain(..self.encrypted.len().min(full))
+            .collect();
+
+        if chunk.len() < TAG_LEN {
+            return Err(Error::Truncated);
+        }
+
+        let algo = self.cipher.algorithm();
+        let nonce = chunk_nonce(algo, self.counter, last);
+
+        self.plaintext = match self.cipher.decrypt_with_nonce(&nonce, &chunk, &self.header) {
+            Ok(plaintext) => plaintext,
+            // a full chunk that is not the last one means the stream was cut at a chunk boundary
+            Err(_) if last && chunk.len() == full => {
+                let nonce = chunk_nonce(algo, self.counter, false);
+
+                return Err(
+                    match self.cipher.decrypt_with_nonce(&nonce, &chunk, &self.header) {
+                        Ok(_) => Error::Truncated,
+                        Err(err) => err,
+                    },
+                );
+            }
+            Err(err) => return Err(err),
+        };
+        self.position = 0;
+
+        if last {
+            self.finished = true;
+        } else {
+            self.counter = self
+                .counter
+                .checked_add(1)
+                .ok_or(Error::InvalidCiphertext)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecryptReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.plaintext.len() {
+            if self.finished {
+                return Ok(0);
+            }
+
+            self.open_chunk().map_err(into_io)?;
+        }
+
+        let len = buf.len().min(self.plaintext.len() - self.position);
+        buf[..len].copy_from_slice(&self.plaintext[self.position..self.position + len]);
+        self.position += len;
+
+        Ok(len)
+    }
+}
+
+/// Encrypt everything from the reader to the writer, returns the writer.
+///
+/// ```
+/// use crypto_utils::aead::{decrypt_stream, encrypt_stream, AlgorithmAead};
+///
+/// let key = [0u8; 32];
+///
+/// let encrypted = encrypt_stream(AlgorithmAead::Aes256Gcm, &key, &mut &b"input"[..], Vec::new()).unwrap();
+///
+/// let mut decrypted = Vec::new();
+/// decrypt_stream(&key, encrypted.as_slice(), &mut decrypted).unwrap();
+///
+/// assert_eq!(decrypted, b"input");
+/// ```
+pub fn encrypt_stream<R: Read, W: Write>(
+    algo: AlgorithmAead,
+    key: impl AsRef<[u8]>,
+    reader: &mut R,
+    writer: W,
+) -> Result<W> {
+    let mut encryptor = EncryptWriter::new(algo, key, writer)?;
+
+    io::copy(reader, &mut encryptor).map_err(from_io)?;
+
+    encryptor.finish()
+}
+
+/// Decrypt everything from the reader to the writer, returns the number of decrypted bytes.
+///
+/// Data written before an error was detected must be discarded.
+pub fn decrypt_stream<R: Read, W: Write>(
+    key: impl AsRef<[u8]>,
+    reader: R,
+    writer: &mut W,
+) -> Result<u64> {
+    let mut decryptor = DecryptReader::new(key, reader)?;
+
+    io::copy(&mut decryptor, writer).map_err(from_io)
+}
+
+/// Derive the key of a single stream from the key and the salt.
+fn stream_key(key: &[u8], salt: &[u8]) -> Result<Vec<u8>> {
+    if key.len() != AlgorithmAead::KEY_LEN {
+        return Err(Error::InvalidKeyLength);
+    }
+
+    let mut mac = CryptographicMac::new(AlgorithmMac::HmacSHA256, key)?;
+    mac.update(b"crypto-utils stream key");
+    mac.update(salt);
+
+    Ok(mac.finalize())
+}
+
+/// Nonce of a chunk: `zeros || counter (32-bit big endian) || last chunk flag`
+fn chunk_nonce(algo: AlgorithmAead, counter: u32, last: bool) -> Vec<u8> {
+    let mut nonce = vec![0u8; algo.nonce_len() - 5];
+    nonce.extend_from_slice(&counter.to_be_bytes());
+    nonce.push(last as u8);
+
+    nonce
+}
+
+fn into_io(err: Error) -> io::Error {
+    match err {
+        Error::Io(err) => err,
+        err => io::Error::new(io::ErrorKind::InvalidData, err),
+    }
+}
+
+fn from_io(err: io::Error) -> Error {
+    match err.get_ref() {
+        Some(inner) if inner.is::<Error>() => *err.into_inner().unwrap().downcast().unwrap(),
+        _ => Error::Io(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::{
+        decrypt_stream, encrypt_stream, AlgorithmAead, EncryptWriter, Error, CHUNK_SIZE,
+        HEADER_LEN, TAG_LEN,
+    };
+    use crate::sha::{Algorithm, CryptographicHash};
+
+    const KEY: &[u8] = &[7u8; 32];
+
+    const ALGORITHMS: [AlgorithmAead; 3] = [
+        AlgorithmAead::Aes256Gcm,
+        AlgorithmAead::ChaCha20Poly1305,
+        AlgorithmAead::XChaCha20Poly1305,
+    ];
+
+    fn input(len: usize) -> Vec<u8> {
+        (0..len).map(|i| i as u8).collect()
+    }
+
+    fn decrypt(encrypted: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut decrypted = Vec::new();
+        decrypt_stream(KEY, encrypted, &mut decrypted)?;
+
+        Ok(decrypted)
+    }
+
+    /// Encrypt and decrypt streams of different sizes using every algorithm
+    #[test]
+    fn encrypt_decrypt() {
+        for algo in ALGORITHMS {
+            for len in [
+                0,
+                1,
+                CHUNK_SIZE - 1,
+                CHUNK_SIZE,
+                CHUNK_SIZE + 1,
+                3 * CHUNK_SIZE + 5,
+            ] {
+                let input = input(len);
+
+                let encrypted =
+                    encrypt_stream(algo, KEY, &mut input.as_slice(), Vec::new()).unwrap();
+
+                // every chunk has its own tag
+                let chunks = len.max(1).div_ceil(CHUNK_SIZE);
+                assert_eq!(encrypted.len(), HEADER_LEN + len + chunks * TAG_LEN);
+
+                assert_eq!(decrypt(&encrypted).unwrap(), input, "{algo:?} {len}");
+            }
+        }
+    }
+
+    /// Write data in small pieces
+    #[test]
+    fn encrypt_small_writes() {
+        let input = input(2 * CHUNK_SIZE + 100);
+
+        let mut writer = EncryptWriter::new(AlgorithmAead::Aes256Gcm, KEY, Vec::new()).unwrap();
+        for piece in input.chunks(1000) {
+            writer.write_all(piece).unwrap();
+        }
+        let encrypted = writer.finish().unwrap();
+
+        assert_eq!(decrypt(&encrypted).unwrap(), input);
+    }
+
+    /// Decrypt a stream directly into a hasher
+    #[test]
+    fn decrypt_to_hasher() {
+        let input = input(CHUNK_SIZE + 10);
+
+        let encrypted = encrypt_stream(
+            AlgorithmAead::XChaCha20Poly1305,
+            KEY,
+            &mut input.as_slice(),
+            Vec::new(),
+        )
+        .unwrap();
+
+        let mut hasher = CryptographicHash::new(Algorithm::SHA256);
+        decrypt_stream(KEY, encrypted.as_slice(), &mut hasher).unwrap();
+
+        assert_eq!(
+            hasher.finalize(),
+            CryptographicHash::hash(Algorithm::SHA256, &input)
+        );
+    }
+
+    /// Drop the last chunks of the stream
+    #[test]
+    fn decrypt_truncated() {
+        for algo in ALGORITHMS {
+            let input = input(3 * CHUNK_SIZE + 5);
+
+            let encrypted = encrypt_stream(algo, KEY, &mut input.as_slice(), Vec::new()).unwrap();
+
+            // cut at a chunk boundary
+            for chunks in 0..3 {
+                let len = HEADER_LEN + chunks * (CHUNK_SIZE + TAG_LEN);
+
+                let err = decrypt(&encrypted[..len]).unwrap_err();
+                assert!(matches!(err, Error::Truncated), "{algo:?} {chunks}: {err}");
+            }
+
+            // cut in the middle of a chunk
+            let err = decrypt(&encrypted[..HEADER_LEN + CHUNK_SIZE / 2]).unwrap_err();
+            assert!(matches!(err, Error::Decryption), "{algo:?}: {err}");
+
+            // the header only
+            let err = decrypt(&encrypted[..HEADER_LEN - 1]).unwrap_err();
+            assert!(matches!(err, Error::InvalidCiphertext), "{algo:?}: {err}");
+        }
+    }
+
+    /// Swap chunks of the stream
+    #[test]
+    fn decrypt_reordered() {
+        for algo in ALGORITHMS {
+            let input = input(3 * CHUNK_SIZE + 5);
+
+            let encrypted = encrypt_stream(algo, KEY, &mut input.as_slice(), Vec::new()).unwrap();
+
+            let (header, chunks) = encrypted.split_at(HEADER_LEN);
+            let chunks: Vec<&[u8]> = chunks.chunks(CHUNK_SIZE + TAG_LEN).collect();
+
+            // swap the first and the second chunk
+            let reordered = [header, chunks[1], chunks[0], chunks[2], chunks[3]].concat();
+
+            let err = decrypt(&reordered).unwrap_err();
+            assert!(matches!(err, Error::Decryption), "{algo:?}: {err}");
+
+            // drop a chunk in the middle of the stream
+            let dropped = [header, chunks[0], chunks[2], chunks[3]].concat();
+
+            let err = decrypt(&dropped).unwrap_err();
+            assert!(matches!(err, Error::Decryption), "{algo:?}: {err}");
+        }
+    }
+
+    /// Decrypt a stream with invalid key
+    #[test]
+    fn decrypt_key_invalid() {
+        let encrypted = encrypt_stream(
+            AlgorithmAead::Aes256Gcm,
+            KEY,
+            &mut &b"input"[..],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let err = decrypt_stream([8u8; 32], encrypted.as_slice(), &mut Vec::new()).unwrap_err();
+
+        assert!(matches!(err, Error::Decryption));
+    }
+}
@@ -0,0 +1,56 @@
+//! Signed checkpoints of the audit log.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    sign::{SigningKey, VerifyingKey},
+    Error, Result,
+};
+
+/// Domain of the signed checkpoint message
+const CHECKPOINT_V1: &[u8] = b"crypto-utils audit checkpoint v1\0";
+
+/// Signature of the head of the log, it can be published outside of the log so the
+/// removal of the last entries is detected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Number of the entries covered by the checkpoint
+    pub size: u64,
+    /// Unix timestamp (in seconds) of the checkpoint
+    pub timestamp: u64,
+    /// Hex encoded hash of the last covered entry
+    pub hash: String,
+    /// Hex encoded signature of the checkpoint
+    pub signature: String,
+}
+
+impl Checkpoint {
+    pub(super) fn sign(key: &SigningKey, size: u64, timestamp: u64, hash: &str) -> Self {
+        let signature = key.sign(&message(size, timestamp, hash));
+
+        Self {
+            size,
+            timestamp,
+            hash: hash.to_string(),
+            signature: hex::encode(signature),
+        }
+    }
+
+    /// Verify the signature of the checkpoint, it doesn't check the entries of the log
+    /// (see [AuditLog::verify_checkpoint](super::AuditLog::verify_checkpoint)).
+    pub fn verify(&self, key: &VerifyingKey) -> Result<()> {
+        let signature = hex::decode(&self.signature).map_err(|_| Error::InvalidSignature)?;
+
+        key.verify(&message(self.size, self.timestamp, &self.hash), &signature)
+    }
+}
+
+/// Message signed by the checkpoint.
+fn message(size: u64, timestamp: u64, hash: &str) -> Vec<u8> {
+    let mut message = CHECKPOINT_V1.to_vec();
+    message.extend_from_slice(&size.to_be_bytes());
+    message.extend_from_slice(&timestamp.to_be_bytes());
+    message.extend_from_slice(hash.as_bytes());
+
+    message
+}
@@ -0,0 +1,593 @@
+//! Module for a tamper-evident, append-only audit log.
+//!
+//! Each entry stores the hash of the previous entry, so an entry can't be modified, removed
+//! or inserted without breaking the chain. The hashes can additionally be authenticated with
+//! a HMAC key, then the whole chain can't be recomputed without the key. The removal of the
+//! last entries is detected by the [Checkpoint]s signed with a [SigningKey].
+//!
+//! The log is stored as JSON Lines, one entry or checkpoint per line.
+//!
+//! ```no_run
+//! use crypto_utils::{
+//!     audit::AuditLog,
+//!     sha::{Algorithm, AlgorithmMac},
+//!     sign::{AlgorithmSign, SigningKey},
+//! };
+//!
+//! let signing_key = SigningKey::generate(AlgorithmSign::Ed25519);
+//!
+//! let mut log = AuditLog::open("audit.log", Algorithm::SHA256)
+//!     .unwrap()
+//!     .mac(AlgorithmMac::HmacSHA256, b"secret key")
+//!     .unwrap()
+//!     .checkpoint_every(100, signing_key.clone());
+//!
+//! log.append(r#"{"user":"admin","action":"delete_user","target":42}"#).unwrap();
+//!
+//! // verify the chain and the signatures of the checkpoints
+//! log.verify().unwrap();
+//! log.verify_checkpoints(&signing_key.verifying_key()).unwrap();
+//! ```
+
+mod checkpoint;
+
+pub use checkpoint::*;
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    sha::{Algorithm, AlgorithmMac, CryptographicHash, CryptographicMac},
+    sign::{SigningKey, VerifyingKey},
+    time::unix_timestamp,
+    Error, Result,
+};
+
+/// Domain of the entry hash
+const ENTRY_V1: &[u8] = b"crypto-utils audit v1\0";
+
+/// Entry of the audit log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Position of the entry in the log, starting at zero
+    pub index: u64,
+    /// Unix timestamp (in seconds) of the entry
+    pub timestamp: u64,
+    /// Logged data, e.g. a JSON describing the action
+    pub data: String,
+    /// Hex encoded hash of the previous entry, zeros for the first entry
+    pub prev_hash: String,
+    /// Hex encoded hash of the entry
+    pub hash: String,
+    /// Hex encoded MAC of the hash if the log uses a MAC key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac: Option<String>,
+}
+
+/// Line of the log file
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Record {
+    Entry(AuditEntry),
+    Checkpoint(Checkpoint),
+}
+
+/// Hash-chained audit log, kept in memory and optionally appended to a file.
+pub struct AuditLog {
+    algorithm: Algorithm,
+    mac: Option<CryptographicMac>,
+    checkpoint_every: Option<(u64, SigningKey)>,
+    entries: Vec<AuditEntry>,
+    checkpoints: Vec<Checkpoint>,
+    file: Option<File>,
+}
+
+impl AuditLog {
+    /// Create an empty log kept only in memory.
+    pub fn new(algo: Algorithm) -> Self {
+        Self {
+            algorithm: algo,
+            mac: None,
+            checkpoint_every: None,
+            entries: Vec::new(),
+            checkpoints: Vec::new(),
+            file: None,
+        }
+    }
+
+    /// Open the log file or create it if it doesn't exist, the new entries are appended
+    /// to the file.
+    ///
+    /// The file isn't verified, [Error::InvalidLogLine] is returned if a line can't be
+    /// parsed.
+    pub fn open(path: impl AsRef<Path>, algo: Algorithm) -> Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut log = Self::new(algo);
+
+        for (number, line) in BufReader::new(&file).lines().enumerate() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let record = serde_json::from_str(&line)
+                .map_err(|_| Error::InvalidLogLine(number as u64 + 1))?;
+
+            match record {
+                Record::Entry(entry) => log.entries.push(entry),
+                Record::Checkpoint(checkpoint) => log.checkpoints.push(checkpoint),
+            }
+        }
+
+        log.file = Some(file);
+
+        Ok(log)
+    }
+
+    /// Authenticate the entries with the HMAC key, the key is also required to verify them.
+    pub fn mac(mut self, algo: AlgorithmMac, key: impl AsRef<[u8]>) -> Result<Self> {
+        self.mac = Some(CryptographicMac::new(algo, key)?);
+        Ok(self)
+    }
+
+    /// Sign a checkpoint after every `interval` entries.
+    pub fn checkpoint_every(mut self, interval: u64, key: SigningKey) -> Self {
+        self.checkpoint_every = Some((interval.max(1), key));
+        self
+    }
+
+    /// Algorithm used by the log
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Entries of the log
+    pub fn entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+
+    /// Checkpoints stored in the log
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        &self.checkpoints
+    }
+
+    /// Number of the entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check that the log doesn't have any entry
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Append the data to the log and return the new entry.
+    ///
+    /// ```
+    /// use crypto_utils::{audit::AuditLog, sha::Algorithm};
+    ///
+    /// let mut log = AuditLog::new(Algorithm::SHA256);
+    ///
+    /// let entry = log.append("admin logged in").unwrap();
+    ///
+    /// assert_eq!(entry.index, 0);
+    /// assert_eq!(entry.prev_hash, "0".repeat(64));
+    /// ```
+    pub fn append(&mut self, data: impl Into<String>) -> Result<&AuditEntry> {
+        self.append_at(data.into(), unix_timestamp())
+    }
+
+    /// Serialize the data to JSON and append it to the log.
+    pub fn append_json<T: Serialize>(&mut self, data: &T) -> Result<&AuditEntry> {
+        self.append(serde_json::to_string(data)?)
+    }
+
+    fn append_at(&mut self, data: String, timestamp: u64) -> Result<&AuditEntry> {
+        let index = self.entries.len() as u64;
+        let prev_hash = self.head();
+
+        let hash = hex::encode(entry_hash(
+            self.algorithm,
+            index,
+            timestamp,
+            &prev_hash,
+            &data,
+        ));
+
+        let mac = self.mac.clone().map(|mut mac| {
+            mac.update(hash.as_bytes());
+            hex::encode(mac.finalize())
+        });
+
+        let entry = AuditEntry {
+            index,
+            timestamp,
+            data,
+            prev_hash,
+            hash,
+            mac,
+        };
+
+        // the entry and its checkpoint are written together, so an error leaves the log
+        // unchanged in memory
+        let checkpoint = match &self.checkpoint_every {
+            Some((interval, key)) if (index + 1).is_multiple_of(*interval) => {
+                Some(Checkpoint::sign(key, index + 1, timestamp, &entry.hash))
+            }
+            _ => None,
+        };
+
+        let mut records = vec![Record::Entry(entry.clone())];
+        records.extend(checkpoint.clone().map(Record::Checkpoint));
+        self.write(&records)?;
+
+        self.entries.push(entry);
+        self.checkpoints.extend(checkpoint);
+
+        Ok(&self.entries[index as usize])
+    }
+
+    /// Sign a checkpoint of the current head and store it in the log.
+    pub fn checkpoint(&mut self, key: &SigningKey) -> Result<Checkpoint> {
+        self.checkpoint_at(key, unix_timestamp())
+    }
+
+    fn checkpoint_at(&mut self, key: &SigningKey, timestamp: u64) -> Result<Checkpoint> {
+        let checkpoint = Checkpoint::sign(key, self.len() as u64, timestamp, &self.head());
+
+        self.write(&[Record::Checkpoint(checkpoint.clone())])?;
+        self.checkpoints.push(checkpoint.clone());
+
+        Ok(checkpoint)
+    }
+
+    /// Verify the whole chain and the MACs if the log uses a MAC key.
+    ///
+    /// [Error::BrokenChain] with the index of the first broken entry is returned
+    /// if an entry was modified, removed or inserted.
+    ///
+    /// ```
+    /// use crypto_utils::{audit::AuditLog, sha::Algorithm, Error};
+    ///
+    /// let mut log = AuditLog::new(Algorithm::SHA256);
+    /// log.append("first").unwrap();
+    /// log.append("second").unwrap();
+    ///
+    /// log.verify().unwrap();
+    /// ```
+    pub fn verify(&self) -> Result<()> {
+        let mut prev_hash = genesis_hash(self.algorithm);
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let broken = Error::BrokenChain(index as u64);
+
+            if entry.index != index as u64 || entry.prev_hash != prev_hash {
+                return Err(broken);
+            }
+
+            let hash = entry_hash(
+                self.algorithm,
+                entry.index,
+                entry.timestamp,
+                &entry.prev_hash,
+                &entry.data,
+            );
+
+            if hex::encode(hash) != entry.hash {
+                return Err(broken);
+            }
+
+            if let Some(mac) = &self.mac {
+                let expected = entry
+                    .mac
+                    .as_ref()
+                    .and_then(|expected| hex::decode(expected).ok())
+                    .ok_or(Error::BrokenChain(index as u64))?;
+
+                let mut mac = mac.clone();
+                mac.update(entry.hash.as_bytes());
+                mac.verify(&expected).map_err(|_| broken)?;
+            }
+
+            prev_hash = entry.hash.clone();
+        }
+
+        Ok(())
+    }
+
+    /// Verify the signatures of all stored checkpoints and that the entries match them.
+    pub fn verify_checkpoints(&self, key: &VerifyingKey) -> Result<()> {
+        self.checkpoints
+            .iter()
+            .try_for_each(|checkpoint| self.verify_checkpoint(checkpoint, key))
+    }
+
+    /// Verify the signature of the checkpoint (e.g. published outside of the log) and that
+    /// the log contains the entries covered by it.
+    ///
+    /// [Error::BrokenChain] is returned if the entries were removed or the covered entry
+    /// doesn't match. The chain itself is checked by [AuditLog::verify].
+    pub fn verify_checkpoint(&self, checkpoint: &Checkpoint, key: &VerifyingKey) -> Result<()> {
+        checkpoint.verify(key)?;
+
+        let size = checkpoint.size as usize;
+
+        if size > self.len() {
+            return Err(Error::BrokenChain(self.len() as u64));
+        }
+
+        let hash = match size {
+            0 => genesis_hash(self.algorithm),
+            _ => self.entries[size - 1].hash.clone(),
+        };
+
+        if hash != checkpoint.hash {
+            return Err(Error::BrokenChain(size.saturating_sub(1) as u64));
+        }
+
+        Ok(())
+    }
+
+    /// Hash of the last entry
+    fn head(&self) -> String {
+        self.entries
+            .last()
+            .map(|entry| entry.hash.clone())
+            .unwrap_or_else(|| genesis_hash(self.algorithm))
+    }
+
+    /// Append the records to the file with a single write, if the log is stored in a file.
+    fn write(&mut self, records: &[Record]) -> Result<()> {
+        if let Some(file) = &mut self.file {
+            let mut lines = Vec::new();
+            for record in records {
+                serde_json::to_writer(&mut lines, record)?;
+                lines.push(b'\n');
+            }
+
+            file.write_all(&lines)?;
+            file.sync_data()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Previous hash of the first entry
+fn genesis_hash(algo: Algorithm) -> String {
+    "0".repeat(algo.output_len() * 2)
+}
+
+/// Compute the hash of the entry.
+fn entry_hash(algo: Algorithm, index: u64, timestamp: u64, prev_hash: &str, data: &str) -> Vec<u8> {
+    let mut hasher = CryptographicHash::new(algo);
+    hasher.update(ENTRY_V1);
+    hasher.update(&index.to_be_bytes());
+    hasher.update(&timestamp.to_be_bytes());
+    hasher.update(prev_hash.as_bytes());
+    hasher.update(&(data.len() as u64).to_be_bytes());
+    hasher.update(data.as_bytes());
+
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{AuditEntry, AuditLog, Checkpoint};
+    use crate::{
+        sha::{Algorithm, AlgorithmMac},
+        sign::{AlgorithmSign, SigningKey},
+        Error,
+    };
+
+    /// Create a log with the entries `entry 0`, `entry 1`, ...
+    fn create_log(entries: usize) -> AuditLog {
+        let mut log = AuditLog::new(Algorithm::SHA256);
+
+        for i in 0..entries {
+            log.append_at(format!("entry {i}"), 1_700_000_000 + i as u64)
+                .unwrap();
+        }
+
+        log
+    }
+
+    /// Chain the entries and pinpoint the modified entry
+    #[test]
+    fn chain() {
+        let mut log = create_log(5);
+        log.verify().unwrap();
+
+        assert_eq!(log.entries()[3].prev_hash, log.entries()[2].hash);
+
+        // modify the data
+        log.entries[2].data = "entry X".to_string();
+        assert!(matches!(log.verify().unwrap_err(), Error::BrokenChain(2)));
+
+        // recompute the hash of the modified entry, the next entry breaks
+        let mut forged = create_log(2);
+        forged
+            .append_at("entry X".to_string(), 1_700_000_002)
+            .unwrap();
+        log.entries[2] = forged.entries[2].clone();
+        assert!(matches!(log.verify().unwrap_err(), Error::BrokenChain(3)));
+
+        // remove an entry
+        let mut log = create_log(5);
+        log.entries.remove(1);
+        assert!(matches!(log.verify().unwrap_err(), Error::BrokenChain(1)));
+    }
+
+    /// The chain can't be recomputed without the MAC key
+    #[test]
+    fn mac() {
+        let key = b"mac key";
+
+        // create the log
+        let mut log = AuditLog::new(Algorithm::SHA512)
+            .mac(AlgorithmMac::HmacSHA256, key)
+            .unwrap();
+
+        for i in 0..3 {
+            log.append_at(format!("entry {i}"), i).unwrap();
+        }
+
+        log.verify().unwrap();
+
+        // recompute the chain from the modified entry, without the key
+        let mut forged = AuditLog::new(Algorithm::SHA512);
+
+        for entry in &log.entries {
+            let data = match entry.index {
+                1 => "forged".to_string(),
+                _ => entry.data.clone(),
+            };
+
+            forged.append_at(data, entry.timestamp).unwrap();
+        }
+
+        let entries = forged
+            .entries
+            .into_iter()
+            .zip(&log.entries)
+            .map(|(forged, entry)| AuditEntry {
+                mac: entry.mac.clone(),
+                ..forged
+            })
+            .collect::<Vec<_>>();
+
+        let mut forged_log = AuditLog::new(Algorithm::SHA512)
+            .mac(AlgorithmMac::HmacSHA256, key)
+            .unwrap();
+        forged_log.entries = entries;
+
+        assert!(matches!(
+            forged_log.verify().unwrap_err(),
+            Error::BrokenChain(1)
+        ));
+
+        // the log can't be verified with another key
+        let mut other = AuditLog::new(Algorithm::SHA512)
+            .mac(AlgorithmMac::HmacSHA256, b"other key")
+            .unwrap();
+        other.entries = log.entries.clone();
+
+        assert!(matches!(other.verify().unwrap_err(), Error::BrokenChain(0)));
+    }
+
+    /// Persist the log, continue it and detect modifications of the file
+    #[test]
+    fn file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+
+        // write the first entries
+        let mut log = AuditLog::open(&path, Algorithm::SHA256).unwrap();
+        log.append("first").unwrap();
+        log.append_json(&serde_json::json!({"user": "admin", "action": "login"}))
+            .unwrap();
+        drop(log);
+
+        // reopen and continue the chain
+        let mut log = AuditLog::open(&path, Algorithm::SHA256).unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(
+            log.entries()[1].data,
+            r#"{"action":"login","user":"admin"}"#
+        );
+
+        log.append("third").unwrap();
+        log.verify().unwrap();
+        drop(log);
+
+        // modify the file
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(&path, content.replace("login", "logout")).unwrap();
+
+        let log = AuditLog::open(&path, Algorithm::SHA256).unwrap();
+        assert!(matches!(log.verify().unwrap_err(), Error::BrokenChain(1)));
+
+        // corrupt a line
+        fs::write(&path, content.replace("third", "third\"")).unwrap();
+
+        let err = AuditLog::open(&path, Algorithm::SHA256).err().unwrap();
+        assert!(matches!(err, Error::InvalidLogLine(3)));
+    }
+
+    /// Sign checkpoints and detect removed entries
+    #[test]
+    fn checkpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+
+        let key = SigningKey::generate(AlgorithmSign::Ed25519);
+        let verifying_key = key.verifying_key();
+
+        // sign a checkpoint after every two entries
+        let mut log = AuditLog::open(&path, Algorithm::SHA256)
+            .unwrap()
+            .checkpoint_every(2, key.clone());
+
+        for i in 0..5 {
+            log.append(format!("entry {i}")).unwrap();
+        }
+
+        assert_eq!(log.checkpoints().len(), 2);
+        assert_eq!(log.checkpoints()[1].size, 4);
+
+        let published = log.checkpoint(&key).unwrap();
+        assert_eq!(published.size, 5);
+        drop(log);
+
+        // verify the checkpoints stored in the file
+        let log = AuditLog::open(&path, Algorithm::SHA256).unwrap();
+        log.verify().unwrap();
+        log.verify_checkpoints(&verifying_key).unwrap();
+
+        // another key
+        let other = SigningKey::generate(AlgorithmSign::Ed25519).verifying_key();
+        assert!(matches!(
+            log.verify_checkpoints(&other).unwrap_err(),
+            Error::InvalidSignature
+        ));
+
+        // remove the last entry, the chain is valid but the checkpoint isn't
+        let mut truncated = AuditLog::new(Algorithm::SHA256);
+        truncated.entries = log.entries()[..4].to_vec();
+
+        truncated.verify().unwrap();
+        assert!(matches!(
+            truncated
+                .verify_checkpoint(&published, &verifying_key)
+                .unwrap_err(),
+            Error::BrokenChain(4)
+        ));
+
+        // modified checkpoint
+        let forged = Checkpoint {
+            size: 3,
+            ..published
+        };
+        assert!(forged.verify(&verifying_key).is_err());
+
+        // corrupt an entry after the checkpoint lines, its line number is reported
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(&path, content.replace("\"entry 4\"", "\"entry 4")).unwrap();
+
+        let err = AuditLog::open(&path, Algorithm::SHA256).err().unwrap();
+        assert!(matches!(err, Error::InvalidLogLine(7)));
+    }
+}
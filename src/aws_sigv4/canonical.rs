@@ -0,0 +1,231 @@
+//! Canonical request of the Signature Version 4.
+
+use std::collections::BTreeMap;
+
+use http::{header::HOST, HeaderMap, Uri};
+
+use crate::{Error, Result};
+
+/// Headers which are never signed, they can be changed by proxies.
+const UNSIGNED_HEADERS: [&str; 5] = [
+    "authorization",
+    "user-agent",
+    "x-amzn-trace-id",
+    "expect",
+    "transfer-encoding",
+];
+
+/// URI encode the bytes, only the unreserved characters (and optionally `/`) are kept.
+pub(super) fn uri_encode(input: &[u8], keep_slash: bool) -> String {
+    let mut out = String::with_capacity(input.len());
+
+    for &byte in input {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    out
+}
+
+/// Decode the percent-encoded string, invalid escapes are kept as they are.
+fn percent_decode(input: &str) -> Vec<u8> {
+    let input = input.as_bytes();
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < input.len() {
+        let escaped = input
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (input[i], escaped) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
+
+/// Canonical URI of the path.
+///
+/// S3 uses the path as it is, other services remove the dot segments and empty segments
+/// and encode the (already encoded) path a second time.
+pub(super) fn canonical_uri(path: &str, s3: bool) -> String {
+    if s3 {
+        // normalize the encoding, escaped slashes stay escaped
+        let path = if path.is_empty() { "/" } else { path };
+
+        return path
+            .split('/')
+            .map(|segment| uri_encode(&percent_decode(segment), false))
+            .collect::<Vec<_>>()
+            .join("/");
+    }
+
+    let mut segments = Vec::new();
+
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    let mut normalized = format!("/{}", segments.join("/"));
+
+    if !segments.is_empty() && (path.ends_with('/') || path.ends_with("/.")) {
+        normalized.push('/');
+    }
+
+    uri_encode(normalized.as_bytes(), true)
+}
+
+/// Canonical query string, the parameters are encoded and sorted.
+pub(super) fn canonical_query(query: &str) -> String {
+    let mut params = query
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+
+            (
+                uri_encode(&percent_decode(key), false),
+                uri_encode(&percent_decode(value), false),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    params.sort();
+
+    params
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Host of the URI, lowercased and without the default port.
+pub(super) fn host(uri: &Uri) -> Result<String> {
+    let authority = uri
+        .authority()
+        .ok_or_else(|| Error::MissingComponent(HOST.to_string()))?;
+
+    let host = authority.host().to_ascii_lowercase();
+
+    let default_port = match uri.scheme_str() {
+        Some("http") => Some(80),
+        Some("https") | None => Some(443),
+        _ => None,
+    };
+
+    Ok(match authority.port_u16() {
+        Some(port) if Some(port) != default_port => format!("{host}:{port}"),
+        _ => host,
+    })
+}
+
+/// Canonical headers and the signed headers list, the `Host` header is taken from the URI
+/// if it isn't set.
+pub(super) fn canonical_headers(headers: &HeaderMap, uri: &Uri) -> Result<(String, String)> {
+    let mut values: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+
+    for (name, value) in headers {
+        if UNSIGNED_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+
+        // trim the value and collapse sequential spaces
+        let value = String::from_utf8_lossy(value.as_bytes())
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        values.entry(name.as_str()).or_default().push(value);
+    }
+
+    if !values.contains_key(HOST.as_str()) {
+        values.insert(HOST.as_str(), vec![host(uri)?]);
+    }
+
+    let canonical = values
+        .iter()
+        .map(|(name, values)| format!("{name}:{}\n", values.join(",")))
+        .collect();
+
+    let signed = values.keys().copied().collect::<Vec<_>>().join(";");
+
+    Ok((canonical, signed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonical_query, canonical_uri};
+
+    /// Normalize paths of the AWS test suite
+    #[test]
+    fn normalize_path() {
+        for (path, expected) in [
+            ("", "/"),
+            ("/", "/"),
+            ("/./", "/"),
+            ("//", "/"),
+            ("//example//", "/example/"),
+            ("/example/..", "/"),
+            ("/example1/example2/../..", "/"),
+            ("/example/./", "/example/"),
+            ("/example/.", "/example/"),
+            ("/example space/", "/example%20space/"),
+            ("/%E1%88%B4", "/%25E1%2588%25B4"),
+        ] {
+            assert_eq!(canonical_uri(path, false), expected, "{path}");
+        }
+    }
+
+    /// S3 paths are encoded only once
+    #[test]
+    fn s3_path() {
+        for (path, expected) in [
+            ("/test.txt", "/test.txt"),
+            ("/photos/2024//a b.jpg", "/photos/2024//a%20b.jpg"),
+            ("/my%20file/./x", "/my%20file/./x"),
+            ("/key%2fwith", "/key%2Fwith"),
+            ("/a*b", "/a%2Ab"),
+        ] {
+            assert_eq!(canonical_uri(path, true), expected, "{path}");
+        }
+    }
+
+    /// Sort and encode query parameters
+    #[test]
+    fn query() {
+        for (query, expected) in [
+            ("", ""),
+            ("Param2=value2&Param1=value1", "Param1=value1&Param2=value2"),
+            ("Param1=value2&Param1=Value1", "Param1=Value1&Param1=value2"),
+            (
+                "Param-3=Value3&Param=Value2&-Param=Value1",
+                "-Param=Value1&Param=Value2&Param-3=Value3",
+            ),
+            ("acl", "acl="),
+            ("prefix=a b&x=%7e%2F+", "prefix=a%20b&x=~%2F%2B"),
+        ] {
+            assert_eq!(canonical_query(query), expected, "{query}");
+        }
+    }
+}
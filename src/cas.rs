@@ -0,0 +1,600 @@
+//! Module for a content-addressable store of blobs on the filesystem.
+//!
+//! Every blob is stored under the hex encoded digest of its content, in directories named
+//! after the first bytes of the digest (`ab/cd/abcd…`), so a single directory doesn't get
+//! too many entries. The blobs are written to a temporary file and renamed when complete,
+//! so a crash never leaves a partial blob, and they are verified again when read.
+//!
+//! ```no_run
+//! use crypto_utils::{cas::BlobStore, sha::Algorithm};
+//!
+//! let store = BlobStore::open("blobs", Algorithm::SHA256).unwrap();
+//!
+//! let digest = store.put(&b"hello world"[..]).unwrap();
+//! assert_eq!(store.read(&digest).unwrap(), b"hello world");
+//!
+//! // remove all blobs except the live ones
+//! let report = store.gc([&digest]).unwrap();
+//! println!("removed {} blobs, freed {} bytes", report.removed.len(), report.freed);
+//! ```
+
+use std::{
+    borrow::Borrow,
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    sha::{Algorithm, CryptographicHash, Digest},
+    Error, Result,
+};
+
+/// Directory of the temporary files, inside the root of the store
+const TEMP_DIR: &str = "tmp";
+
+/// Temporary files older than this are left by a crash and removed by the garbage collection
+const TEMP_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Maximum number of the directory levels
+const MAX_FAN_OUT: usize = 4;
+
+/// Counter of the temporary files created by this process
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Result of the garbage collection.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcReport {
+    /// Digests of the removed blobs
+    pub removed: Vec<Digest>,
+    /// Number of the freed bytes
+    pub freed: u64,
+}
+
+/// Content-addressable store of blobs in a directory.
+///
+/// Blobs can be added and read concurrently, also by many processes. The garbage collection
+/// must not run together with [BlobStore::put], because a blob added during it may be removed.
+#[derive(Debug, Clone)]
+pub struct BlobStore {
+    root: PathBuf,
+    algorithm: Algorithm,
+    fan_out: usize,
+}
+
+impl BlobStore {
+    /// Open the store in the directory or create it if it doesn't exist.
+    pub fn open(root: impl AsRef<Path>, algo: Algorithm) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(root.join(TEMP_DIR))?;
+
+        Ok(Self {
+            root,
+            algorithm: algo,
+            fan_out: 2,
+        })
+    }
+
+    /// Set the number of the directory levels (default `2`, at most `4`), each named after
+    /// the next byte of the digest.
+    ///
+    /// The blobs already in the store aren't moved, so it must be the same every time the
+    /// store is opened.
+    pub fn fan_out(mut self, levels: usize) -> Self {
+        self.fan_out = levels.min(MAX_FAN_OUT);
+        self
+    }
+
+    /// Algorithm of the digests
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Path of the blob, it may not exist.
+    ///
+    /// Returns [Error::InvalidAlgorithm] or [Error::InvalidDigest] if the digest doesn't match
+    /// the algorithm of the store.
+    ///
+    /// ```
+    /// use crypto_utils::{cas::BlobStore, sha::{Algorithm, CryptographicHash}};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let store = BlobStore::open(dir.path(), Algorithm::SHA256).unwrap();
+    ///
+    /// let mut hasher = CryptographicHash::new(Algorithm::SHA256);
+    /// hasher.update(b"hello world");
+    ///
+    /// let path = store.path(&hasher.digest()).unwrap();
+    ///
+    /// assert!(path.ends_with("b9/4d/b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"));
+    /// ```
+    pub fn path(&self, digest: &Digest) -> Result<PathBuf> {
+        self.check_algorithm(digest)?;
+
+        let hex = digest.to_string();
+
+        let mut path = self.root.clone();
+        for level in 0..self.fan_out {
+            path.push(&hex[level * 2..level * 2 + 2]);
+        }
+        path.push(hex);
+
+        Ok(path)
+    }
+
+    /// Stream the content to the store and return its digest.
+    ///
+    /// The content is hashed while it's written, an already stored blob isn't written again.
+    pub fn put(&self, mut reader: impl Read) -> Result<Digest> {
+        let mut temp = TempFile::create(&self.root.join(TEMP_DIR))?;
+        let mut hasher = CryptographicHash::new(self.algorithm);
+
+        // write the content to the temporary file
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            let len = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            hasher.update(&buffer[..len]);
+            temp.file.write_all(&buffer[..len])?;
+        }
+
+        temp.file.sync_all()?;
+
+        // move the file to its path, unless the blob is already stored
+        let digest = hasher.digest();
+        let path = self.path(&digest)?;
+
+        if !path.exists() {
+            let dir = path.parent().expect("blob path has a parent");
+            fs::create_dir_all(dir)?;
+
+            temp.persist(&path)?;
+            sync_dir(dir)?;
+        }
+
+        Ok(digest)
+    }
+
+    /// Check whether the blob is stored, the content isn't verified.
+    pub fn contains(&self, digest: &Digest) -> bool {
+        self.path(digest).is_ok_and(|path| path.is_file())
+    }
+
+    /// Open the blob for reading.
+    ///
+    /// The content is verified while it's read: the end of the blob returns an
+    /// [io::ErrorKind::InvalidData] error wrapping [Error::DigestMismatch] if it was modified.
+    /// A missing blob returns an [Error::Io] with [io::ErrorKind::NotFound].
+    pub fn get(&self, digest: &Digest) -> Result<BlobReader> {
+        Ok(BlobReader {
+            file: File::open(self.path(digest)?)?,
+            hasher: CryptographicHash::new(self.algorithm),
+            expected: digest.clone(),
+            verified: false,
+        })
+    }
+
+    /// Read and verify the whole blob.
+    ///
+    /// Returns [Error::DigestMismatch] if the blob was modified.
+    pub fn read(&self, digest: &Digest) -> Result<Vec<u8>> {
+        let data = fs::read(self.path(digest)?)?;
+
+        match CryptographicHash::hash(self.algorithm, &data) == digest.as_bytes() {
+            true => Ok(data),
+            false => Err(Error::DigestMismatch),
+        }
+    }
+
+    /// Remove the blob, returns `false` if it wasn't stored.
+    pub fn remove(&self, digest: &Digest) -> Result<bool> {
+        match fs::remove_file(self.path(digest)?) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Digests of all stored blobs, sorted.
+    pub fn list(&self) -> Result<Vec<Digest>> {
+        let mut digests = Vec::new();
+        self.list_dir(&self.root, 0, &mut digests)?;
+        digests.sort();
+
+        Ok(digests)
+    }
+
+    /// Remove all blobs which aren't live, and the temporary files left by a crash.
+    pub fn gc<I>(&self, live: I) -> Result<GcReport>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Digest>,
+    {
+        self.gc_with(live, |_, _| Ok(Vec::new()))
+    }
+
+    /// Remove all blobs which aren't reachable from the roots.
+    ///
+    /// The `references` function returns the digests referenced by the verified content of a
+    /// live blob, e.g. the files of a directory listing, they are also kept.
+    ///
+    /// ```
+    /// use crypto_utils::{cas::BlobStore, sha::{Algorithm, Digest}};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let store = BlobStore::open(dir.path(), Algorithm::SHA256).unwrap();
+    ///
+    /// let file = store.put(&b"file content"[..]).unwrap();
+    /// let garbage = store.put(&b"garbage"[..]).unwrap();
+    ///
+    /// // the root lists the digests of its files, one per line
+    /// let root = store.put(format!("{file}\n").as_bytes()).unwrap();
+    ///
+    /// let report = store
+    ///     .gc_with([&root], |digest, content| {
+    ///         // the files don't reference other blobs
+    ///         if *digest != root {
+    ///             return Ok(Vec::new());
+    ///         }
+    ///
+    ///         String::from_utf8_lossy(content)
+    ///             .lines()
+    ///             .map(|line| Ok(Digest::new(Algorithm::SHA256, hex::decode(line).unwrap())))
+    ///             .collect()
+    ///     })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(report.removed, [garbage]);
+    /// assert!(store.contains(&file));
+    /// ```
+    pub fn gc_with<I, F>(&self, roots: I, mut references: F) -> Result<GcReport>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Digest>,
+        F: FnMut(&Digest, &[u8]) -> Result<Vec<Digest>>,
+    {
+        // mark the blobs reachable from the roots
+        let mut live = HashSet::new();
+        let mut stack = roots
+            .into_iter()
+            .map(|digest| digest.borrow().clone())
+            .collect::<Vec<_>>();
+
+        while let Some(digest) = stack.pop() {
+            if live.contains(&digest) || !self.contains(&digest) {
+                continue;
+            }
+
+            let content = self.read(&digest)?;
+            stack.extend(references(&digest, &content)?);
+
+            live.insert(digest);
+        }
+
+        // remove the other blobs
+        let mut report = GcReport::default();
+
+        for digest in self.list()? {
+            if live.contains(&digest) {
+                continue;
+            }
+
+            let path = self.path(&digest)?;
+            let len = fs::metadata(&path)?.len();
+            fs::remove_file(path)?;
+
+            report.freed += len;
+            report.removed.push(digest);
+        }
+
+        // remove the stale temporary files
+        for entry in fs::read_dir(self.root.join(TEMP_DIR))? {
+            let entry = entry?;
+            let age = entry.metadata()?.modified()?.elapsed().unwrap_or_default();
+
+            if age > TEMP_MAX_AGE {
+                fs::remove_file(entry.path())?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn check_algorithm(&self, digest: &Digest) -> Result<()> {
+        if digest.algorithm() != self.algorithm {
+            return Err(Error::InvalidAlgorithm);
+        }
+
+        match digest.as_bytes().len() == self.algorithm.output_len() {
+            true => Ok(()),
+            false => Err(Error::InvalidDigest),
+        }
+    }
+
+    fn list_dir(&self, dir: &Path, level: usize, digests: &mut Vec<Digest>) -> Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+
+            // directories of the next level, files of the blobs in the last one
+            if level < self.fan_out {
+                let is_shard = name.len() == 2 && name.bytes().all(|c| c.is_ascii_hexdigit());
+
+                if is_shard && entry.file_type()?.is_dir() {
+                    self.list_dir(&entry.path(), level + 1, digests)?;
+                }
+            } else if let Ok(bytes) = hex::decode(name) {
+                // skip unrelated files
+                if bytes.len() != self.algorithm.output_len() {
+                    continue;
+                }
+
+                let digest = Digest::new(self.algorithm, bytes);
+
+                if self.path(&digest)? == entry.path() && entry.file_type()?.is_file() {
+                    digests.push(digest);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reader of a blob, which verifies its content at the end.
+pub struct BlobReader {
+    file: File,
+    hasher: CryptographicHash,
+    expected: Digest,
+    verified: bool,
+}
+
+impl Read for BlobReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.file.read(buf)?;
+        self.hasher.update(&buf[..len]);
+
+        if len == 0 && !buf.is_empty() && !self.verified {
+            if self.hasher.finalize() != self.expected.as_bytes() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    Error::DigestMismatch,
+                ));
+            }
+
+            self.verified = true;
+        }
+
+        Ok(len)
+    }
+}
+
+/// Temporary file, which is removed unless it's persisted
+struct TempFile {
+    path: Option<PathBuf>,
+    file: File,
+}
+
+impl TempFile {
+    fn create(dir: &Path) -> Result<Self> {
+        loop {
+            let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+            let nanos = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .subsec_nanos();
+            let path = dir.join(format!("{}-{counter}-{nanos}.tmp", std::process::id()));
+
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => {
+                    return Ok(Self {
+                        path: Some(path),
+                        file,
+                    })
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    fn persist(&mut self, to: &Path) -> Result<()> {
+        if let Some(path) = &self.path {
+            fs::rename(path, to)?;
+            self.path = None;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Make the rename durable, directories can't be synced on Windows.
+fn sync_dir(dir: &Path) -> Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+
+    #[cfg(not(unix))]
+    let _ = dir;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io::Read};
+
+    use super::{Algorithm, BlobStore, Error};
+    use crate::sha::{CryptographicHash, Digest};
+
+    /// Put a blob and read it back
+    #[test]
+    fn put_get() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::open(dir.path(), Algorithm::SHA256).unwrap();
+
+        // put the blob twice
+        let data = vec![42u8; 200_000];
+        let digest = store.put(&data[..]).unwrap();
+        assert_eq!(store.put(&data[..]).unwrap(), digest);
+
+        assert_eq!(
+            digest.as_bytes(),
+            CryptographicHash::hash(Algorithm::SHA256, &data)
+        );
+        assert!(store.contains(&digest));
+
+        // read the blob
+        assert_eq!(store.read(&digest).unwrap(), data);
+
+        let mut content = Vec::new();
+        store
+            .get(&digest)
+            .unwrap()
+            .read_to_end(&mut content)
+            .unwrap();
+        assert_eq!(content, data);
+
+        // no temporary files are left
+        assert_eq!(fs::read_dir(dir.path().join("tmp")).unwrap().count(), 0);
+        assert_eq!(store.list().unwrap(), [digest]);
+    }
+
+    /// Store the blobs in the sharded directories
+    #[test]
+    fn fan_out() {
+        let dir = tempfile::tempdir().unwrap();
+
+        for levels in [0, 1, 3] {
+            let store = BlobStore::open(dir.path().join(levels.to_string()), Algorithm::SHA1)
+                .unwrap()
+                .fan_out(levels);
+
+            let digest = store.put(&b"blob"[..]).unwrap();
+            let hex = digest.to_string();
+
+            let relative = store
+                .path(&digest)
+                .unwrap()
+                .strip_prefix(dir.path().join(levels.to_string()))
+                .unwrap()
+                .to_path_buf();
+            let components = relative
+                .iter()
+                .map(|c| c.to_str().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(components.len(), levels + 1);
+
+            for (level, component) in components[..levels].iter().enumerate() {
+                assert_eq!(*component, &hex[level * 2..level * 2 + 2]);
+            }
+
+            assert_eq!(store.list().unwrap(), [digest]);
+        }
+    }
+
+    /// Detect a modified blob when it's read
+    #[test]
+    fn modified_blob() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::open(dir.path(), Algorithm::SHA256).unwrap();
+
+        let digest = store.put(&b"original"[..]).unwrap();
+        fs::write(store.path(&digest).unwrap(), b"modified").unwrap();
+
+        // read the whole blob
+        assert!(matches!(store.read(&digest), Err(Error::DigestMismatch)));
+
+        // stream the blob
+        let err = store
+            .get(&digest)
+            .unwrap()
+            .read_to_end(&mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    /// Remove the blobs which aren't live
+    #[test]
+    fn gc() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::open(dir.path(), Algorithm::SHA256).unwrap();
+
+        let live = store.put(&b"live"[..]).unwrap();
+        let garbage = store.put(&b"garbage"[..]).unwrap();
+
+        // unrelated files are kept
+        fs::write(dir.path().join("README"), b"blobs").unwrap();
+
+        let report = store.gc([&live]).unwrap();
+
+        assert_eq!(report.removed, std::slice::from_ref(&garbage));
+        assert_eq!(report.freed, 7);
+        assert!(store.contains(&live));
+        assert!(!store.contains(&garbage));
+        assert!(dir.path().join("README").exists());
+    }
+
+    /// Ignore the files of the last level which aren't named by a full digest
+    #[test]
+    fn gc_short_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::open(dir.path(), Algorithm::SHA256).unwrap();
+
+        let digest = store.put(&b"blob"[..]).unwrap();
+        let stray = store.path(&digest).unwrap().with_file_name("ab");
+        fs::write(&stray, b"stray").unwrap();
+
+        assert_eq!(store.list().unwrap(), std::slice::from_ref(&digest));
+
+        let report = store.gc([&digest]).unwrap();
+        assert!(report.removed.is_empty());
+        assert!(stray.exists());
+    }
+
+    /// Reject digests of another algorithm
+    #[test]
+    fn other_algorithm() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::open(dir.path(), Algorithm::SHA256).unwrap();
+
+        let mut hasher = CryptographicHash::new(Algorithm::SHA512);
+        hasher.update(b"blob");
+        let digest = hasher.digest();
+
+        assert!(!store.contains(&digest));
+        assert!(matches!(store.read(&digest), Err(Error::InvalidAlgorithm)));
+    }
+
+    /// Reject digests of the wrong length
+    #[test]
+    fn short_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::open(dir.path(), Algorithm::SHA256).unwrap();
+
+        let digest = Digest::new(Algorithm::SHA256, vec![1]);
+
+        assert!(!store.contains(&digest));
+        assert!(matches!(store.path(&digest), Err(Error::InvalidDigest)));
+        assert!(matches!(store.get(&digest), Err(Error::InvalidDigest)));
+        assert!(matches!(store.remove(&digest), Err(Error::InvalidDigest)));
+    }
+}
@@ -0,0 +1,366 @@
+//! Parser and writer of the checksum manifests.
+
+use std::path::Path;
+
+use super::{hash_file, list_files};
+use crate::{
+    sha::{Algorithm, Digest},
+    Error, Result,
+};
+
+/// Output format of a manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// Format of the coreutils, `<hex>  <file>` in text mode (the default of `sha256sum`)
+    /// and `<hex> *<file>` in binary mode (`--binary`)
+    #[default]
+    Gnu,
+    /// BSD tag format (`--tag`), `SHA256 (<file>) = <hex>`
+    Bsd,
+}
+
+/// Mode in which the file was read, it only makes a difference on Windows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Text mode, marked with a space
+    Text,
+    /// Binary mode, marked with `*`
+    #[default]
+    Binary,
+}
+
+/// Checksum of a single file in the manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Path of the file relative to the verified directory, separated by `/`
+    pub path: String,
+    /// Hash of the file content
+    pub digest: Digest,
+    /// Mode in which the file was read
+    pub mode: Mode,
+}
+
+/// List of file checksums, the content of a `SHA256SUMS` file.
+///
+/// ```
+/// use crypto_utils::{
+///     checksum::{Format, Manifest},
+///     sha::Algorithm,
+/// };
+///
+/// let manifest = Manifest::parse("\
+/// 2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae *foo.txt
+/// SHA256 (bar/baz.txt) = fcde2b2edba56bf408601fb721fe9b5c338d10ee429ea04fae5511b68fbf8fb9
+/// ").unwrap();
+///
+/// assert_eq!(manifest.entries[1].path, "bar/baz.txt");
+/// assert_eq!(manifest.entries[1].digest.algorithm(), Algorithm::SHA256);
+///
+/// assert_eq!(manifest.format(Format::Bsd), "\
+/// SHA256 (foo.txt) = 2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae
+/// SHA256 (bar/baz.txt) = fcde2b2edba56bf408601fb721fe9b5c338d10ee429ea04fae5511b68fbf8fb9
+/// ");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Manifest {
+    /// Checksums in the order of the manifest
+    pub entries: Vec<Entry>,
+}
+
+impl Manifest {
+    /// Compute the checksums of all files in the directory and its subdirectories.
+    ///
+    /// The entries are sorted by their paths and use the binary mode. A file name which is
+    /// not UTF-8 returns an [Error::Io] with [std::io::ErrorKind::InvalidData].
+    ///
+    /// ```no_run
+    /// use crypto_utils::{checksum::Manifest, sha::Algorithm};
+    ///
+    /// let manifest = Manifest::generate(Algorithm::SHA512, "release").unwrap();
+    /// ```
+    pub fn generate(algo: Algorithm, dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+
+        let entries = list_files(dir)?
+            .into_iter()
+            .map(|path| {
+                Ok(Entry {
+                    digest: hash_file(algo, dir.join(&path))?,
+                    path,
+                    mode: Mode::Binary,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { entries })
+    }
+
+    /// Parse a manifest in the GNU or BSD format, both formats can be mixed.
+    ///
+    /// The algorithm of a GNU line is detected from the hash length. Empty lines are
+    /// skipped, other malformed lines return [Error::InvalidDigest].
+    pub fn parse(input: &str) -> Result<Self> {
+        let entries = input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(parse_line)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { entries })
+    }
+
+    /// Write the manifest in the format, each line ends with a newline.
+    pub fn format(&self, format: Format) -> String {
+        let mut out = String::new();
+
+        for entry in &self.entries {
+            let (path, escaped) = escape(&entry.path);
+
+            if escaped {
+                out.push('\\');
+            }
+
+            match format {
+                Format::Gnu => {
+                    let marker = match entry.mode {
+                        Mode::Text => ' ',
+                        Mode::Binary => '*',
+                    };
+
+                    out.push_str(&format!("{} {marker}{path}\n", entry.digest));
+                }
+                Format::Bsd => {
+                    let tag = tag(entry.digest.algorithm());
+
+                    out.push_str(&format!("{tag} ({path}) = {}\n", entry.digest));
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Algorithms which can be used in a manifest
+const ALGORITHMS: [Algorithm; 4] = [
+    Algorithm::SHA1,
+    Algorithm::SHA256,
+    Algorithm::SHA384,
+    Algorithm::SHA512,
+];
+
+/// Name of the algorithm in the BSD format.
+fn tag(algo: Algorithm) -> &'static str {
+    match algo {
+        Algorithm::SHA1 => "SHA1",
+        Algorithm::SHA256 => "SHA256",
+        Algorithm::SHA384 => "SHA384",
+        Algorithm::SHA512 => "SHA512",
+    }
+}
+
+/// Parse a line of the manifest.
+fn parse_line(line: &str) -> Result<Entry> {
+    let (line, escaped) = match line.strip_prefix('\\') {
+        Some(line) => (line, true),
+        None => (line, false),
+    };
+
+    // BSD format, the line starts with a known algorithm
+    let bsd = line.split_once(" (").and_then(|(name, rest)| {
+        let algo = ALGORITHMS.into_iter().find(|&algo| tag(algo) == name)?;
+
+        Some((algo, rest.rsplit_once(") = ")?))
+    });
+
+    if let Some((algo, (path, hex))) = bsd {
+        return Ok(Entry {
+            path: unescape(path, escaped)?,
+            digest: parse_hex(hex, Some(algo))?,
+            mode: Mode::Binary,
+        });
+    }
+
+    // GNU format
+    let (hex, rest) = line.split_once(' ').ok_or(Error::InvalidDigest)?;
+
+    let mode = match rest.as_bytes().first() {
+        Some(b' ') => Mode::Text,
+        Some(b'*') => Mode::Binary,
+        _ => return Err(Error::InvalidDigest),
+    };
+
+    let path = &rest[1..];
+
+    if path.is_empty() {
+        return Err(Error::InvalidDigest);
+    }
+
+    Ok(Entry {
+        path: unescape(path, escaped)?,
+        digest: parse_hex(hex, None)?,
+        mode,
+    })
+}
+
+/// Decode the hex hash, the algorithm is detected from its length if it isn't given.
+fn parse_hex(hex: &str, algo: Option<Algorithm>) -> Result<Digest> {
+    let bytes = hex::decode(hex).map_err(|_| Error::InvalidDigest)?;
+
+    let detected = ALGORITHMS
+        .into_iter()
+        .find(|algo| algo.output_len() == bytes.len())
+        .ok_or(Error::InvalidDigest)?;
+
+    if algo.is_some_and(|algo| algo != detected) {
+        return Err(Error::InvalidDigest);
+    }
+
+    Ok(Digest::new(detected, bytes))
+}
+
+/// Escape backslashes and newlines of the path, returns whether anything was escaped.
+fn escape(path: &str) -> (String, bool) {
+    if !path.contains(['\\', '\n', '\r']) {
+        return (path.to_string(), false);
+    }
+
+    let escaped = path
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+
+    (escaped, true)
+}
+
+/// Reverse [escape] if the line started with a backslash.
+fn unescape(path: &str, escaped: bool) -> Result<String> {
+    if !escaped {
+        return Ok(path.to_string());
+    }
+
+    let mut out = String::with_capacity(path.len());
+    let mut chars = path.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('\\') => out.push('\\'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            _ => return Err(Error::InvalidDigest),
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, Format, Manifest, Mode};
+    use crate::{checksum::hash_file, sha::Algorithm, Error};
+
+    // outputs of `sha*sum -b a.txt` for a file with `hello\n`
+    const SHA1: &str = "f572d396fae9206628714fb2ce00f72e94f2258f *a.txt\n";
+    const SHA256: &str =
+        "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03 *a.txt\n";
+    const SHA384: &str = "1d0f284efe3edea4b9ca3bd514fa134b17eae361ccc7a1eefeff801b9bd6604e01f21f6bf249ef030599f0c218f2ba8c *a.txt\n";
+    const SHA512: &str = "e7c22b994c59d9cf2b48e549b1e24666636045930d3da7c1acb299d1c3b7f931f94aae41edda2c2b207a36e10f8bcb8d45223e54878f5b316e7ce3b6bc019629 *a.txt\n";
+
+    /// Generate the same manifests as the coreutils for every algorithm
+    #[test]
+    fn coreutils_compatible() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), b"hello\n").unwrap();
+
+        for (algo, expected) in [
+            (Algorithm::SHA1, SHA1),
+            (Algorithm::SHA256, SHA256),
+            (Algorithm::SHA384, SHA384),
+            (Algorithm::SHA512, SHA512),
+        ] {
+            // generate the manifest
+            let manifest = Manifest::generate(algo, dir.path()).unwrap();
+            assert_eq!(manifest.format(Format::Gnu), expected);
+
+            // parse the manifest and detect the algorithm
+            let parsed = Manifest::parse(expected).unwrap();
+            assert_eq!(parsed, manifest);
+            assert_eq!(parsed.entries[0].digest.algorithm(), algo);
+
+            // parse the BSD format
+            let bsd = manifest.format(Format::Bsd);
+            assert_eq!(Manifest::parse(&bsd).unwrap(), manifest);
+        }
+
+        let digest = hash_file(Algorithm::SHA256, dir.path().join("a.txt")).unwrap();
+        assert_eq!(digest.to_string(), &SHA256[..64]);
+    }
+
+    /// Escape file names with a backslash or a newline
+    #[test]
+    fn escaped_names() {
+        // outputs of `sha256sum --tag` and `sha256sum -t`
+        let input = "\
+\\SHA256 (back\\\\slash) = 2d711642b726b04401627ca9fbac32f5c8530fb1903cc4db02258717921a4881
+\\a1fce4363854ff888cff4b8e7875d600c2682390412a8cf79b37d0b11148b0fa  new\\nline
+";
+
+        let manifest = Manifest::parse(input).unwrap();
+
+        assert_eq!(manifest.entries[0].path, "back\\slash");
+        assert_eq!(manifest.entries[0].mode, Mode::Binary);
+        assert_eq!(manifest.entries[1].path, "new\nline");
+        assert_eq!(manifest.entries[1].mode, Mode::Text);
+
+        let entries = manifest.entries.clone();
+        let bsd = Manifest {
+            entries: vec![entries[0].clone()],
+        };
+        let gnu = Manifest {
+            entries: vec![entries[1].clone()],
+        };
+
+        assert_eq!(bsd.format(Format::Bsd) + &gnu.format(Format::Gnu), input);
+    }
+
+    /// Parse file names which look like the BSD format
+    #[test]
+    fn gnu_name_with_parentheses() {
+        let manifest =
+            Manifest::parse("f572d396fae9206628714fb2ce00f72e94f2258f *SHA1 (a.txt) = x\n\n")
+                .unwrap();
+
+        assert_eq!(
+            manifest.entries,
+            [Entry {
+                path: "SHA1 (a.txt) = x".to_string(),
+                digest: manifest.entries[0].digest.clone(),
+                mode: Mode::Binary,
+            }]
+        );
+    }
+
+    /// Reject malformed lines
+    #[test]
+    fn parse_invalid() {
+        for input in [
+            "f572d396fae9206628714fb2ce00f72e94f2258f",
+            "f572d396fae9206628714fb2ce00f72e94f2258f a.txt",
+            "f572d396fae9206628714fb2ce00f72e94f2258f *",
+            "f572d396 *a.txt",
+            "zz72d396fae9206628714fb2ce00f72e94f2258f *a.txt",
+            "SHA256 (a.txt) = f572d396fae9206628714fb2ce00f72e94f2258f",
+            "MD5 (a.txt) = 5891b5b522d5df086d0ff0b110fbd9d2",
+            "\\f572d396fae9206628714fb2ce00f72e94f2258f *a\\t",
+        ] {
+            let err = Manifest::parse(input).unwrap_err();
+
+            assert!(matches!(err, Error::InvalidDigest), "{input}");
+        }
+    }
+}
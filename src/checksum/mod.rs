@@ -0,0 +1,121 @@
+//! Module for checksum manifests compatible with the GNU coreutils `*sum` tools
+//! (`sha1sum`, `sha256sum`, `sha384sum` and `sha512sum`).
+//!
+//! Both output formats are supported:
+//!
+//! | Format       | Line                                |
+//! |:-------------|:------------------------------------|
+//! | [Format::Gnu] | `<hex>  <file>` (`<hex> *<file>` in binary mode) |
+//! | [Format::Bsd] | `SHA256 (<file>) = <hex>` (`--tag`) |
+//!
+//! File names with a backslash or a newline are escaped like the coreutils do.
+//!
+//! ## Create a manifest
+//!
+//! ```no_run
+//! use crypto_utils::{
+//!     checksum::{Format, Manifest},
+//!     sha::Algorithm,
+//! };
+//!
+//! let manifest = Manifest::generate(Algorithm::SHA256, "release").unwrap();
+//!
+//! std::fs::write("SHA256SUMS", manifest.format(Format::Gnu)).unwrap();
+//! ```
+//!
+//! ## Hash a whole directory
+//!
+//! [hash_tree] computes a single digest of a directory, e.g. for a build cache key:
+//!
+//! ```no_run
+//! use crypto_utils::{
+//!     checksum::{hash_tree, TreeOptions},
+//!     sha::Algorithm,
+//! };
+//!
+//! let options = TreeOptions::new().file_mode(true).ignore("target");
+//!
+//! let digest = hash_tree(Algorithm::SHA256, "config", &options).unwrap();
+//! ```
+//!
+//! ## Verify a directory
+//!
+//! ```no_run
+//! use crypto_utils::checksum::Manifest;
+//!
+//! let manifest = Manifest::parse(&std::fs::read_to_string("SHA256SUMS").unwrap()).unwrap();
+//!
+//! let report = manifest.verify_dir("release").unwrap();
+//!
+//! for path in &report.modified {
+//!     eprintln!("{path}: FAILED");
+//! }
+//!
+//! assert!(report.is_ok());
+//! ```
+
+mod manifest;
+mod tree;
+mod verify;
+
+pub use manifest::*;
+pub use tree::*;
+pub use verify::*;
+
+use std::{
+    fs::{self, File},
+    io,
+    path::Path,
+};
+
+use crate::{
+    sha::{Algorithm, CryptographicHash, Digest},
+    Result,
+};
+
+/// Compute the hash of the file content, the file is streamed to the hasher.
+///
+/// ```no_run
+/// use crypto_utils::{checksum::hash_file, sha::Algorithm};
+///
+/// let digest = hash_file(Algorithm::SHA256, "release/app.tar.gz").unwrap();
+///
+/// println!("{digest}  release/app.tar.gz");
+/// ```
+pub fn hash_file(algo: Algorithm, path: impl AsRef<Path>) -> Result<Digest> {
+    let mut file = File::open(path)?;
+    let mut hasher = CryptographicHash::new(algo);
+
+    io::copy(&mut file, &mut hasher)?;
+
+    Ok(hasher.digest())
+}
+
+/// Relative paths (separated by `/`) of all files in the directory and its subdirectories,
+/// sorted by their bytes. Symlinks to files are included, symlinks to directories
+/// aren't followed and broken symlinks are skipped. A name which is not UTF-8 returns
+/// an [io::ErrorKind::InvalidData] error.
+fn list_files(dir: &Path) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![(dir.to_path_buf(), String::new())];
+
+    while let Some((dir, prefix)) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name().into_string().map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "file name is not UTF-8")
+            })?;
+            let name = format!("{prefix}{name}");
+
+            if entry.file_type()?.is_dir() {
+                dirs.push((entry.path(), format!("{name}/")));
+            } else if fs::metadata(entry.path()).is_ok_and(|metadata| metadata.is_file()) {
+                files.push(name);
+            }
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
@@ -0,0 +1,442 @@
+//! Deterministic hash of a directory tree.
+
+use std::{fs, io, path::Path};
+
+use crate::{
+    sha::{Algorithm, CryptographicHash, Digest},
+    Result,
+};
+
+/// Version of the encoding of the tree, hashed before the entries
+const TREE_V1: &[u8] = b"crypto-utils tree v1\0";
+
+/// Options of [hash_tree], by default only the regular files and their content are hashed.
+///
+/// ```
+/// use crypto_utils::checksum::TreeOptions;
+///
+/// let options = TreeOptions::new()
+///     .file_mode(true)
+///     .empty_dirs(true)
+///     .ignore("target")
+///     .ignore("*.log");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TreeOptions {
+    file_mode: bool,
+    symlinks: bool,
+    empty_dirs: bool,
+    ignore: Vec<String>,
+}
+
+impl TreeOptions {
+    /// Create the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Include whether the files are executable, the other permission bits depend on
+    /// the umask of the machine and are never hashed. It has no effect outside of Unix.
+    pub fn file_mode(mut self, file_mode: bool) -> Self {
+        self.file_mode = file_mode;
+        self
+    }
+
+    /// Include the symlinks with their targets, they are never followed. The symlinks
+    /// are skipped by default.
+    pub fn symlinks(mut self, symlinks: bool) -> Self {
+        self.symlinks = symlinks;
+        self
+    }
+
+    /// Include the directories which don't contain any hashed entry.
+    pub fn empty_dirs(mut self, empty_dirs: bool) -> Self {
+        self.empty_dirs = empty_dirs;
+        self
+    }
+
+    /// Skip the entries matching the glob pattern, a skipped directory is skipped with all
+    /// its content.
+    ///
+    /// `*` matches any characters except `/`, `**` also matches `/` and `?` matches a single
+    /// character. A pattern without `/` is matched against the name of the entry, other
+    /// patterns against the whole path relative to the hashed directory.
+    pub fn ignore(mut self, pattern: impl Into<String>) -> Self {
+        self.ignore.push(pattern.into());
+        self
+    }
+
+    fn is_ignored(&self, path: &str) -> bool {
+        let name = path.rsplit('/').next().unwrap_or(path);
+
+        self.ignore.iter().any(|pattern| {
+            let pattern = pattern.trim_start_matches('/');
+
+            if pattern.contains('/') {
+                glob_match(pattern.as_bytes(), path.as_bytes())
+            } else {
+                glob_match(pattern.as_bytes(), name.as_bytes())
+            }
+        })
+    }
+}
+
+/// Entry of the tree
+enum Node {
+    File { executable: bool, digest: Digest },
+    Symlink(String),
+    Dir,
+}
+
+/// Compute a single digest of the directory, stable across machines and platforms.
+///
+/// The entries are sorted by their paths, which are relative to the directory and
+/// separated by `/`. The digest covers the paths and content of the files, the other
+/// entries are included according to the [TreeOptions].
+///
+/// A name or symlink target which is not UTF-8 returns an [Error::Io](crate::Error::Io) with
+/// [io::ErrorKind::InvalidData], since it has no stable representation across platforms.
+///
+/// ```no_run
+/// use crypto_utils::{
+///     checksum::{hash_tree, TreeOptions},
+///     sha::Algorithm,
+/// };
+///
+/// let options = TreeOptions::new().ignore("target").ignore(".git");
+///
+/// let cache_key = hash_tree(Algorithm::SHA256, ".", &options).unwrap();
+///
+/// println!("{cache_key}");
+/// ```
+pub fn hash_tree(algo: Algorithm, path: impl AsRef<Path>, options: &TreeOptions) -> Result<Digest> {
+    let mut nodes = Vec::new();
+    walk(algo, path.as_ref(), "", options, &mut nodes)?;
+
+    nodes.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut hasher = CryptographicHash::new(algo);
+    hasher.update(TREE_V1);
+
+    for (path, node) in &nodes {
+        match node {
+            Node::File { executable, digest } => {
+                hasher.update(b"f");
+                update_bytes(&mut hasher, path.as_bytes());
+
+                if options.file_mode {
+                    hasher.update(if *executable { b"x" } else { b"-" });
+                }
+
+                hasher.update(digest.as_bytes());
+            }
+            Node::Symlink(target) => {
+                hasher.update(b"l");
+                update_bytes(&mut hasher, path.as_bytes());
+                update_bytes(&mut hasher, target.as_bytes());
+            }
+            Node::Dir => {
+                hasher.update(b"d");
+                update_bytes(&mut hasher, path.as_bytes());
+            }
+        }
+    }
+
+    Ok(hasher.digest())
+}
+
+/// Hash the length of the bytes followed by the bytes.
+fn update_bytes(hasher: &mut CryptographicHash, bytes: &[u8]) {
+    hasher.update(&(bytes.len() as u64).to_be_bytes());
+    hasher.update(bytes);
+}
+
+/// Collect the entries of the directory, returns the number of the collected entries.
+fn walk(
+    algo: Algorithm,
+    dir: &Path,
+    prefix: &str,
+    options: &TreeOptions,
+    nodes: &mut Vec<(String, Node)>,
+) -> Result<usize> {
+    let mut count = 0;
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "file name is not UTF-8"))?;
+        let path = format!("{prefix}{name}");
+
+        if options.is_ignored(&path) {
+            continue;
+        }
+
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            let children = walk(algo, &entry.path(), &format!("{path}/"), options, nodes)?;
+
+            if children == 0 && options.empty_dirs {
+                nodes.push((path, Node::Dir));
+                count += 1;
+            }
+
+            count += children;
+        } else if file_type.is_symlink() {
+            if options.symlinks {
+                let target = fs::read_link(entry.path())?;
+                let target = target
+                    .to_str()
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "symlink target is not UTF-8")
+                    })?
+                    .replace('\\', "/");
+
+                nodes.push((path, Node::Symlink(target)));
+                count += 1;
+            }
+        } else if file_type.is_file() {
+            let executable = is_executable(&entry.metadata()?);
+            let digest = super::hash_file(algo, entry.path())?;
+
+            nodes.push((path, Node::File { executable, digest }));
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// Match the text against the glob pattern.
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        // `**/` matches any directories, including none
+        [b'*', b'*', b'/', rest @ ..] => (0..=text.len())
+            .filter(|&i| i == 0 || text[i - 1] == b'/')
+            .any(|i| glob_match(rest, &text[i..])),
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob_match(rest, &text[i..])),
+        [b'?', rest @ ..] => matches!(text, [c, ..] if *c != b'/') && glob_match(rest, &text[1..]),
+        [c, rest @ ..] => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io, path::Path};
+
+    use super::{glob_match, hash_tree, Algorithm, TreeOptions};
+    use crate::Error;
+
+    /// Create a small project in the directory, the files are written in the given order.
+    fn create_tree(dir: &Path, reversed: bool) {
+        let mut files = vec![
+            ("README.md", "# readme\n"),
+            ("src/main.rs", "fn main() {}\n"),
+            ("src/lib/mod.rs", "pub mod a;\n"),
+            ("target/debug/app", "binary"),
+            ("build.log", "log"),
+        ];
+
+        if reversed {
+            files.reverse();
+        }
+
+        for (path, content) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+    }
+
+    /// The digest doesn't depend on the location or the creation order
+    #[test]
+    fn deterministic() {
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+
+        create_tree(a.path(), false);
+        create_tree(b.path(), true);
+
+        let options = TreeOptions::new();
+
+        let digest = hash_tree(Algorithm::SHA256, a.path(), &options).unwrap();
+        assert_eq!(
+            digest,
+            hash_tree(Algorithm::SHA256, b.path(), &options).unwrap()
+        );
+
+        // pinned so a change of the encoding is noticed
+        assert_eq!(
+            digest.to_string(),
+            "12a0f812ec72e7516f8a1a4859bd23b9d275e45eebefa1dec5a6dd56270913a1"
+        );
+
+        // the content and the paths are hashed
+        fs::write(b.path().join("src/main.rs"), "fn main() { }\n").unwrap();
+        assert_ne!(
+            digest,
+            hash_tree(Algorithm::SHA256, b.path(), &options).unwrap()
+        );
+
+        fs::write(b.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::rename(b.path().join("README.md"), b.path().join("README")).unwrap();
+        assert_ne!(
+            digest,
+            hash_tree(Algorithm::SHA256, b.path(), &options).unwrap()
+        );
+    }
+
+    /// Ignored entries don't change the digest
+    #[test]
+    fn ignore() {
+        let dir = tempfile::tempdir().unwrap();
+        create_tree(dir.path(), false);
+
+        let options = TreeOptions::new().ignore("target").ignore("*.log");
+        let digest = hash_tree(Algorithm::SHA512, dir.path(), &options).unwrap();
+
+        // change the ignored files
+        fs::write(dir.path().join("target/debug/app"), "rebuilt").unwrap();
+        fs::write(dir.path().join("other.log"), "log").unwrap();
+        assert_eq!(
+            digest,
+            hash_tree(Algorithm::SHA512, dir.path(), &options).unwrap()
+        );
+
+        // change a hashed file
+        fs::write(dir.path().join("src/lib/b.rs"), "").unwrap();
+        assert_ne!(
+            digest,
+            hash_tree(Algorithm::SHA512, dir.path(), &options).unwrap()
+        );
+    }
+
+    /// Include empty directories only if enabled
+    #[test]
+    fn empty_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        create_tree(dir.path(), false);
+
+        let with = TreeOptions::new().empty_dirs(true);
+        let without = TreeOptions::new();
+
+        let digest_with = hash_tree(Algorithm::SHA256, dir.path(), &with).unwrap();
+        let digest_without = hash_tree(Algorithm::SHA256, dir.path(), &without).unwrap();
+
+        // the tree doesn't have any empty directory
+        assert_eq!(digest_with, digest_without);
+
+        fs::create_dir_all(dir.path().join("assets/images")).unwrap();
+
+        assert_ne!(
+            digest_with,
+            hash_tree(Algorithm::SHA256, dir.path(), &with).unwrap()
+        );
+        assert_eq!(
+            digest_without,
+            hash_tree(Algorithm::SHA256, dir.path(), &without).unwrap()
+        );
+    }
+
+    /// Include the executable bit and the symlinks only if enabled
+    #[cfg(unix)]
+    #[test]
+    fn mode_and_symlinks() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        create_tree(dir.path(), false);
+
+        let defaults = TreeOptions::new();
+        let all = TreeOptions::new().file_mode(true).symlinks(true);
+
+        let digest = hash_tree(Algorithm::SHA256, dir.path(), &defaults).unwrap();
+        let digest_all = hash_tree(Algorithm::SHA256, dir.path(), &all).unwrap();
+
+        // make a file executable
+        let app = dir.path().join("target/debug/app");
+        fs::set_permissions(&app, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(
+            digest,
+            hash_tree(Algorithm::SHA256, dir.path(), &defaults).unwrap()
+        );
+        let digest_exec = hash_tree(Algorithm::SHA256, dir.path(), &all).unwrap();
+        assert_ne!(digest_all, digest_exec);
+
+        // other permission bits are ignored
+        fs::set_permissions(&app, fs::Permissions::from_mode(0o700)).unwrap();
+        assert_eq!(
+            digest_exec,
+            hash_tree(Algorithm::SHA256, dir.path(), &all).unwrap()
+        );
+
+        // add a symlink
+        symlink("src/main.rs", dir.path().join("main.rs")).unwrap();
+
+        assert_eq!(
+            digest,
+            hash_tree(Algorithm::SHA256, dir.path(), &defaults).unwrap()
+        );
+        assert_ne!(
+            digest_exec,
+            hash_tree(Algorithm::SHA256, dir.path(), &all).unwrap()
+        );
+    }
+
+    /// Reject the names which are not UTF-8 instead of replacing their invalid bytes
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_name() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(OsStr::from_bytes(b"a\xff")), b"a").unwrap();
+
+        let err = hash_tree(Algorithm::SHA256, dir.path(), &TreeOptions::new()).unwrap_err();
+        assert!(matches!(err, Error::Io(err) if err.kind() == io::ErrorKind::InvalidData));
+    }
+
+    /// Match glob patterns
+    #[test]
+    fn glob() {
+        for (pattern, text, expected) in [
+            ("*.log", "build.log", true),
+            ("*.log", "build.txt", false),
+            ("*", "a/b", false),
+            ("src/*.rs", "src/main.rs", true),
+            ("src/*.rs", "src/lib/mod.rs", false),
+            ("src/**/*.rs", "src/main.rs", true),
+            ("src/**/*.rs", "src/lib/mod.rs", true),
+            ("**/mod.rs", "mod.rs", true),
+            ("**", "a/b/c", true),
+            ("file?.txt", "file1.txt", true),
+            ("file?.txt", "file10.txt", false),
+            ("a?b", "a/b", false),
+        ] {
+            assert_eq!(
+                glob_match(pattern.as_bytes(), text.as_bytes()),
+                expected,
+                "{pattern} {text}"
+            );
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Verification of a directory against a manifest.
+
+use std::{
+    collections::BTreeSet,
+    io::ErrorKind,
+    path::{Component, Path},
+};
+
+use super::{hash_file, list_files, Manifest};
+use crate::{Error, Result};
+
+/// Result of [Manifest::verify_dir], the paths are relative to the verified directory.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Report {
+    /// Files of the manifest which were verified
+    pub ok: Vec<String>,
+    /// Files of the manifest which don't exist
+    pub missing: Vec<String>,
+    /// Files whose content doesn't match the manifest
+    pub modified: Vec<String>,
+    /// Files in the directory which aren't in the manifest
+    pub extra: Vec<String>,
+}
+
+impl Report {
+    /// Check that no file is missing, modified or extra.
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.modified.is_empty() && self.extra.is_empty()
+    }
+}
+
+impl Manifest {
+    /// Verify the files of the directory against the manifest.
+    ///
+    /// Each file is hashed with the algorithm of its entry. The manifest file itself
+    /// is reported as extra if it is stored in the directory, an entry naming a directory
+    /// is reported as modified.
+    ///
+    /// Returns [Error::InvalidDigest] if a path is absolute or contains `..`, so
+    /// an untrusted manifest can't read files outside of the directory.
+    ///
+    /// ```no_run
+    /// use crypto_utils::checksum::Manifest;
+    ///
+    /// let manifest = Manifest::parse(&std::fs::read_to_string("SHA256SUMS").unwrap()).unwrap();
+    /// let report = manifest.verify_dir("release").unwrap();
+    ///
+    /// for path in report.missing.iter().chain(&report.modified) {
+    ///     eprintln!("{path}: FAILED");
+    /// }
+    /// ```
+    pub fn verify_dir(&self, dir: impl AsRef<Path>) -> Result<Report> {
+        let dir = dir.as_ref();
+        let mut report = Report::default();
+        let mut listed = BTreeSet::new();
+
+        for entry in &self.entries {
+            let path = normalize(&entry.path);
+            let file = dir.join(check_path(&path)?);
+            listed.insert(path.clone());
+
+            if file.is_dir() {
+                report.modified.push(path);
+                continue;
+            }
+
+            match hash_file(entry.digest.algorithm(), file) {
+                Ok(digest) if digest == entry.digest => report.ok.push(path),
+                Ok(_) => report.modified.push(path),
+                Err(Error::Io(err)) if err.kind() == ErrorKind::NotFound => {
+                    report.missing.push(path)
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        report.extra = list_files(dir)?
+            .into_iter()
+            .filter(|path| !listed.contains(path))
+            .collect();
+
+        Ok(report)
+    }
+}
+
+/// Remove the `.` components of the path, e.g. the prefix written by `sha256sum ./*`.
+fn normalize(path: &str) -> String {
+    path.split('/')
+        .filter(|component| *component != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Check that the path stays inside of the verified directory.
+fn check_path(path: &str) -> Result<&Path> {
+    let inside = Path::new(path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+
+    match inside {
+        true => Ok(Path::new(path)),
+        false => Err(Error::InvalidDigest),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{Manifest, Report};
+    use crate::{checksum::Format, sha::Algorithm, Error};
+
+    /// Verify an unchanged directory, then modify, remove and add files
+    #[test]
+    fn verify_dir() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // create the files
+        fs::create_dir(dir.path().join("docs")).unwrap();
+        fs::write(dir.path().join("app.bin"), b"binary").unwrap();
+        fs::write(dir.path().join("docs/README"), b"readme").unwrap();
+        fs::write(dir.path().join("docs/LICENSE"), b"license").unwrap();
+
+        // generate the manifest and read it back
+        let manifest = Manifest::generate(Algorithm::SHA256, dir.path()).unwrap();
+        let manifest = Manifest::parse(&manifest.format(Format::Gnu)).unwrap();
+
+        let report = manifest.verify_dir(dir.path()).unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.ok, ["app.bin", "docs/LICENSE", "docs/README"]);
+
+        // change the directory
+        fs::write(dir.path().join("app.bin"), b"modified").unwrap();
+        fs::remove_file(dir.path().join("docs/LICENSE")).unwrap();
+        fs::write(dir.path().join("docs/CHANGELOG"), b"changelog").unwrap();
+
+        let report = manifest.verify_dir(dir.path()).unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(
+            report,
+            Report {
+                ok: vec!["docs/README".to_string()],
+                missing: vec!["docs/LICENSE".to_string()],
+                modified: vec!["app.bin".to_string()],
+                extra: vec!["docs/CHANGELOG".to_string()],
+            }
+        );
+    }
+
+    /// Verify a manifest written by `sha1sum ./*`
+    #[test]
+    fn verify_coreutils_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("foo"), b"foo\n").unwrap();
+
+        let manifest =
+            Manifest::parse("f1d2d2f924e986ac86fdf7b36c94bcdf32beec15  ./foo\n").unwrap();
+
+        let report = manifest.verify_dir(dir.path()).unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.ok, ["foo"]);
+    }
+
+    /// Match the paths with inner `.` components to the listed files
+    #[test]
+    fn verify_current_dir_components() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("docs")).unwrap();
+        fs::write(dir.path().join("docs/foo"), b"foo\n").unwrap();
+
+        let manifest =
+            Manifest::parse("f1d2d2f924e986ac86fdf7b36c94bcdf32beec15  ./docs/./foo\n").unwrap();
+
+        let report = manifest.verify_dir(dir.path()).unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.ok, ["docs/foo"]);
+    }
+
+    /// Reject file names which aren't UTF-8
+    #[cfg(unix)]
+    #[test]
+    fn verify_non_utf8_name() {
+        use std::{ffi::OsStr, io, os::unix::ffi::OsStrExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(OsStr::from_bytes(b"a\xff")), b"a").unwrap();
+
+        let err = Manifest::generate(Algorithm::SHA256, dir.path()).unwrap_err();
+        assert!(matches!(err, Error::Io(err) if err.kind() == io::ErrorKind::InvalidData));
+
+        let err = Manifest::default().verify_dir(dir.path()).unwrap_err();
+        assert!(matches!(err, Error::Io(err) if err.kind() == io::ErrorKind::InvalidData));
+    }
+
+    /// Reject paths outside of the directory
+    #[test]
+    fn verify_path_outside() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("release")).unwrap();
+        fs::write(dir.path().join("secret"), b"secret").unwrap();
+
+        let hex = "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b";
+
+        for path in [
+            "../secret",
+            "./../secret",
+            "docs/../../secret",
+            "/etc/passwd",
+        ] {
+            let manifest = Manifest::parse(&format!("{hex}  {path}\n")).unwrap();
+
+            let err = manifest.verify_dir(dir.path().join("release")).unwrap_err();
+            assert!(matches!(err, Error::InvalidDigest), "{path}");
+        }
+    }
+
+    /// Report an entry naming a directory as modified
+    #[test]
+    fn verify_directory_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("docs")).unwrap();
+
+        let manifest = Manifest::parse(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  docs\n",
+        )
+        .unwrap();
+
+        let report = manifest.verify_dir(dir.path()).unwrap();
+        assert_eq!(report.modified, ["docs"]);
+    }
+}
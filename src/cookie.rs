@@ -0,0 +1,467 @@
+//! Module for signed and encrypted cookies (stateless sessions).
+//!
+//! A value is serialized to JSON together with the creation time, then it is either signed
+//! with HMAC-SHA256 (readable by the client, but can't be changed) or encrypted with AEAD.
+//! The result is URL-safe base64, so it can be used as a cookie value:
+//!
+//! ```text
+//! signed:    base64(json) "." base64(timestamp) "." base64(mac)
+//! encrypted: base64(aead(timestamp || json))
+//! ```
+//!
+//! Keys are derived from the secret and the namespace (e.g. `session` or `csrf`) with HKDF,
+//! so a cookie can't be reused in another context. Old secrets can be added to the codec
+//! to decode cookies issued before the secret was rotated.
+//!
+//! ## Signed cookie
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use crypto_utils::cookie::CookieCodec;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Session {
+//!     user_id: u64,
+//! }
+//!
+//! let codec = CookieCodec::signed(b"secret", "session")
+//!     .unwrap()
+//!     .max_age(Duration::from_secs(3600));
+//!
+//! let cookie: String = codec.encode(&Session { user_id: 42 }).unwrap();
+//!
+//! let session: Session = codec.decode(&cookie).unwrap();
+//! assert_eq!(session.user_id, 42);
+//! ```
+//!
+//! ## Encrypted cookie and key rotation
+//!
+//! ```
+//! use crypto_utils::{aead::AlgorithmAead, cookie::CookieCodec};
+//!
+//! // cookie issued with the old secret
+//! let old_codec = CookieCodec::encrypted(AlgorithmAead::XChaCha20Poly1305, b"old secret", "session").unwrap();
+//! let cookie = old_codec.encode(&"user@example.com").unwrap();
+//!
+//! // new cookies use the new secret, the old one is still accepted
+//! let codec = CookieCodec::encrypted(AlgorithmAead::XChaCha20Poly1305, b"new secret", "session")
+//!     .unwrap()
+//!     .old_secret(b"old secret")
+//!     .unwrap();
+//!
+//! let email: String = codec.decode(&cookie).unwrap();
+//! assert_eq!(email, "user@example.com");
+//! ```
+
+use std::time::Duration;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    aead::{AlgorithmAead, CryptographicAead},
+    sha::{hkdf, AlgorithmMac, CryptographicMac},
+    time::unix_timestamp,
+    Error, Result,
+};
+
+/// HKDF info of the signing keys
+const SIGNING_INFO: &[u8] = b"crypto-utils cookie signing";
+/// HKDF info of the encryption keys
+const ENCRYPTION_INFO: &[u8] = b"crypto-utils cookie encryption";
+/// Seconds a cookie can be timestamped ahead of the current time, for the clock skew
+const MAX_CLOCK_SKEW: u64 = 60;
+
+/// Keys of the codec, the first one encodes the cookies
+enum Keys {
+    Signed(Vec<CryptographicMac>),
+    Encrypted(AlgorithmAead, Vec<CryptographicAead>),
+}
+
+/// Encoder and decoder of signed or encrypted cookies.
+pub struct CookieCodec {
+    namespace: String,
+    keys: Keys,
+    max_age: Option<Duration>,
+}
+
+impl CookieCodec {
+    /// Create a codec of signed cookies, the value is readable by the client.
+    ///
+    /// ```no_run
+    /// use crypto_utils::cookie::CookieCodec;
+    ///
+    /// let codec = CookieCodec::signed(b"secret", "session").unwrap();
+    /// ```
+    pub fn signed(secret: impl AsRef<[u8]>, namespace: &str) -> Result<Self> {
+        let mut codec = Self {
+            namespace: namespace.to_string(),
+            keys: Keys::Signed(Vec::new()),
+            max_age: None,
+        };
+        codec.add_key(secret.as_ref())?;
+
+        Ok(codec)
+    }
+
+    /// Create a codec of encrypted cookies.
+    ///
+    /// ```no_run
+    /// use crypto_utils::{aead::AlgorithmAead, cookie::CookieCodec};
+    ///
+    /// let codec = CookieCodec::encrypted(AlgorithmAead::Aes256Gcm, b"secret", "session").unwrap();
+    /// ```
+    pub fn encrypted(
+        algo: AlgorithmAead,
+        secret: impl AsRef<[u8]>,
+        namespace: &str,
+    ) -> Result<Self> {
+        let mut codec = Self {
+            namespace: namespace.to_string(),
+            keys: Keys::Encrypted(algo, Vec::new()),
+            max_age: None,
+        };
+        codec.add_key(secret.as_ref())?;
+
+        Ok(codec)
+    }
+
+    /// Accept cookies encoded with the old secret, new cookies are encoded with the first one.
+    pub fn old_secret(mut self, secret: impl AsRef<[u8]>) -> Result<Self> {
+        self.add_key(secret.as_ref())?;
+        Ok(self)
+    }
+
+    /// Reject cookies older than the duration, or timestamped more than a minute in the future.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    fn add_key(&mut self, secret: &[u8]) -> Result<()> {
+        let salt = self.namespace.as_bytes();
+
+        match &mut self.keys {
+            Keys::Signed(keys) => {
+                let key = hkdf(AlgorithmMac::HmacSHA256, salt, secret, SIGNING_INFO, 32)?;
+                keys.push(CryptographicMac::new(AlgorithmMac::HmacSHA256, key)?);
+            }
+            Keys::Encrypted(algo, keys) => {
+                let key = hkdf(AlgorithmMac::HmacSHA256, salt, secret, ENCRYPTION_INFO, 32)?;
+                keys.push(CryptographicAead::new(*algo, key)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Encode the value to a cookie.
+    ///
+    /// ```
+    /// use crypto_utils::cookie::CookieCodec;
+    ///
+    /// let codec = CookieCodec::signed(b"secret", "session").unwrap();
+    ///
+    /// let cookie = codec.encode(&vec!["admin", "editor"]).unwrap();
+    /// ```
+    pub fn encode<T: Serialize>(&self, value: &T) -> Result<String> {
+        self.encode_at(value, unix_timestamp())
+    }
+
+    fn encode_at<T: Serialize>(&self, value: &T, timestamp: u64) -> Result<String> {
+        let payload = serde_json::to_vec(value)?;
+
+        match &self.keys {
+            Keys::Signed(keys) => {
+                let signed = format!(
+                    "{}.{}",
+                    URL_SAFE_NO_PAD.encode(payload),
+                    URL_SAFE_NO_PAD.encode(timestamp.to_be_bytes())
+                );
+
+                let mut mac = keys[0].clone();
+                mac.update(signed.as_bytes());
+
+                Ok(format!(
+                    "{signed}.{}",
+                    URL_SAFE_NO_PAD.encode(mac.finalize())
+                ))
+            }
+            Keys::Encrypted(_, keys) => {
+                let mut plaintext = timestamp.to_be_bytes().to_vec();
+                plaintext.extend_from_slice(&payload);
+
+                let ciphertext = keys[0].encrypt(&plaintext, self.namespace.as_bytes())?;
+
+                Ok(URL_SAFE_NO_PAD.encode(ciphertext))
+            }
+        }
+    }
+
+    /// Decode the cookie, with any of the secrets.
+    ///
+    /// Returns [Error::MacMismatch] (or [Error::Decryption]) if the cookie was changed or
+    /// encoded in other namespace, [Error::Expired] if it is older than the max age,
+    /// [Error::InvalidTimestamp] if it is timestamped in the future and [Error::InvalidToken]
+    /// if it is malformed.
+    ///
+    /// ```
+    /// use crypto_utils::cookie::CookieCodec;
+    ///
+    /// let codec = CookieCodec::signed(b"secret", "session").unwrap();
+    /// let cookie = codec.encode(&vec!["admin", "editor"]).unwrap();
+    ///
+    /// let roles: Vec<String> = codec.decode(&cookie).unwrap();
+    /// ```
+    pub fn decode<T: DeserializeOwned>(&self, cookie: &str) -> Result<T> {
+        self.decode_at(cookie, unix_timestamp())
+    }
+
+    fn decode_at<T: DeserializeOwned>(&self, cookie: &str, now: u64) -> Result<T> {
+        let (timestamp, payload) = match &self.keys {
+            Keys::Signed(keys) => {
+                let (signed, signature) = cookie.rsplit_once('.').ok_or(Error::InvalidToken)?;
+                let (payload, timestamp) = signed.split_once('.').ok_or(Error::InvalidToken)?;
+
+                let signature = decode_base64(signature)?;
+
+                let valid = keys.iter().any(|key| {
+                    let mut mac = key.clone();
+                    mac.update(signed.as_bytes());
+                    mac.verify(&signature).is_ok()
+                });
+
+                if !valid {
+                    return Err(Error::MacMismatch);
+                }
+
+                let timestamp: [u8; 8] = decode_base64(timestamp)?
+                    .try_into()
+                    .map_err(|_| Error::InvalidToken)?;
+
+                (u64::from_be_bytes(timestamp), decode_base64(payload)?)
+            }
+            Keys::Encrypted(_, keys) => {
+                let ciphertext = decode_base64(cookie)?;
+
+                let mut plaintext = keys
+                    .iter()
+                    .find_map(|key| key.decrypt(&ciphertext, self.namespace.as_bytes()).ok())
+                    .ok_or(Error::Decryption)?;
+
+                if plaintext.len() < 8 {
+                    return Err(Error::InvalidToken);
+                }
+
+                let payload = plaintext.split_off(8);
+                let timestamp: [u8; 8] = plaintext.try_into().map_err(|_| Error::InvalidToken)?;
+
+                (u64::from_be_bytes(timestamp), payload)
+            }
+        };
+
+        if let Some(max_age) = self.max_age {
+            if timestamp > now.saturating_add(MAX_CLOCK_SKEW) {
+                return Err(Error::InvalidTimestamp);
+            }
+
+            if now.saturating_sub(timestamp) > max_age.as_secs() {
+                return Err(Error::Expired);
+            }
+        }
+
+        Ok(serde_json::from_slice(&payload)?)
+    }
+}
+
+fn decode_base64(input: &str) -> Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(input)
+        .map_err(|_| Error::InvalidToken)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::{CookieCodec, Error};
+    use crate::aead::AlgorithmAead;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Session {
+        user_id: u64,
+        roles: Vec<String>,
+    }
+
+    fn session() -> Session {
+        Session {
+            user_id: 42,
+            roles: vec!["admin".to_string()],
+        }
+    }
+
+    fn codecs(secret: &[u8], namespace: &str) -> Vec<CookieCodec> {
+        vec![
+            CookieCodec::signed(secret, namespace).unwrap(),
+            CookieCodec::encrypted(AlgorithmAead::Aes256Gcm, secret, namespace).unwrap(),
+            CookieCodec::encrypted(AlgorithmAead::XChaCha20Poly1305, secret, namespace).unwrap(),
+        ]
+    }
+
+    /// Encode and decode cookies
+    #[test]
+    fn encode_decode() {
+        for codec in codecs(b"secret", "session") {
+            let cookie = codec.encode(&session()).unwrap();
+
+            // URL-safe base64 without padding
+            assert!(cookie
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c)));
+
+            let decoded: Session = codec.decode(&cookie).unwrap();
+            assert_eq!(decoded, session());
+        }
+    }
+
+    /// Signed cookies are readable, encrypted ones are not
+    #[test]
+    fn signed_readable() {
+        let [signed, encrypted, _] = <[CookieCodec; 3]>::try_from(codecs(b"secret", "session"))
+            .ok()
+            .unwrap();
+
+        let cookie = signed.encode(&"user@example.com").unwrap();
+        let payload = cookie.split('.').next().unwrap();
+        assert_eq!(
+            base64::Engine::decode(&base64::engine::general_purpose::URL_SAFE_NO_PAD, payload)
+                .unwrap(),
+            b"\"user@example.com\""
+        );
+
+        let cookie = encrypted.encode(&"user@example.com").unwrap();
+        assert!(!cookie.contains('.'));
+    }
+
+    /// Cookies of other namespace or secret are rejected
+    #[test]
+    fn decode_other_context() {
+        let cookies = codecs(b"secret", "session")
+            .iter()
+            .map(|codec| codec.encode(&session()).unwrap())
+            .collect::<Vec<_>>();
+
+        for other in [
+            codecs(b"secret", "csrf"),
+            codecs(b"other secret", "session"),
+        ] {
+            for (codec, cookie) in other.iter().zip(&cookies) {
+                let err = codec.decode::<Session>(cookie).unwrap_err();
+
+                assert!(
+                    matches!(err, Error::MacMismatch | Error::Decryption),
+                    "{err:?}"
+                );
+            }
+        }
+    }
+
+    /// Decode cookies encoded before the secret was rotated
+    #[test]
+    fn key_rotation() {
+        let old = codecs(b"old secret", "session");
+        let new = codecs(b"new secret", "session")
+            .into_iter()
+            .map(|codec| codec.old_secret(b"old secret").unwrap());
+
+        for (old, new) in old.iter().zip(new) {
+            let cookie = old.encode(&session()).unwrap();
+
+            let decoded: Session = new.decode(&cookie).unwrap();
+            assert_eq!(decoded, session());
+
+            // new cookies are encoded with the new secret
+            let cookie = new.encode(&session()).unwrap();
+            assert!(old.decode::<Session>(&cookie).is_err());
+        }
+    }
+
+    /// Expired cookies are rejected
+    #[test]
+    fn max_age() {
+        for codec in codecs(b"secret", "session") {
+            let codec = codec.max_age(Duration::from_secs(60));
+
+            let cookie = codec.encode_at(&session(), 1_700_000_000).unwrap();
+
+            codec.decode_at::<Session>(&cookie, 1_700_000_060).unwrap();
+
+            let err = codec
+                .decode_at::<Session>(&cookie, 1_700_000_061)
+                .unwrap_err();
+            assert!(matches!(err, Error::Expired));
+        }
+    }
+
+    /// Cookies timestamped in the future are rejected
+    #[test]
+    fn future_timestamp() {
+        for codec in codecs(b"secret", "session") {
+            let codec = codec.max_age(Duration::from_secs(60));
+
+            let cookie = codec.encode_at(&session(), 1_700_000_000).unwrap();
+
+            // within the clock skew
+            codec.decode_at::<Session>(&cookie, 1_699_999_940).unwrap();
+
+            let err = codec
+                .decode_at::<Session>(&cookie, 1_699_999_939)
+                .unwrap_err();
+            assert!(matches!(err, Error::InvalidTimestamp));
+        }
+    }
+
+    /// Modified and malformed cookies are rejected
+    #[test]
+    fn decode_invalid() {
+        let [signed, encrypted, _] = <[CookieCodec; 3]>::try_from(codecs(b"secret", "session"))
+            .ok()
+            .unwrap();
+
+        // change the payload of the signed cookie
+        let cookie = signed.encode(&session()).unwrap();
+        let (_, rest) = cookie.split_once('.').unwrap();
+        let forged = format!(
+            "{}.{rest}",
+            base64::Engine::encode(
+                &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+                br#"{"user_id":1,"roles":["admin"]}"#
+            )
+        );
+
+        let err = signed.decode::<Session>(&forged).unwrap_err();
+        assert!(matches!(err, Error::MacMismatch));
+
+        for cookie in ["", "abc", "a.b", "a.b.***"] {
+            let err = signed.decode::<Session>(cookie).unwrap_err();
+            assert!(matches!(err, Error::InvalidToken), "{cookie}");
+        }
+
+        for cookie in ["***", "AAAA"] {
+            let err = encrypted.decode::<Session>(cookie).unwrap_err();
+            assert!(
+                matches!(err, Error::InvalidToken | Error::Decryption),
+                "{cookie}"
+            );
+        }
+
+        // valid cookie of other type
+        let cookie = signed.encode(&"text").unwrap();
+        let err = signed.decode::<Session>(&cookie).unwrap_err();
+        assert!(matches!(err, Error::Json(_)));
+    }
+}
@@ -0,0 +1,126 @@
+use thiserror::Error;
+
+/// Error type returned by all modules of the crate
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// Invalid key format
+    #[error("invalid key")]
+    InvalidKey,
+    /// Key is not set, e.g. the environment variable is missing
+    #[error("missing key `{0}`")]
+    MissingKey(String),
+    /// Invalid key length for the algorithm
+    #[error("invalid key length")]
+    InvalidKeyLength,
+    /// Token doesn't have a valid shape
+    #[error("invalid token")]
+    InvalidToken,
+    /// Signature or authentication tag doesn't match
+    #[error("invalid signature")]
+    InvalidSignature,
+    /// Algorithm of the token doesn't match the expected one
+    #[error("invalid algorithm")]
+    InvalidAlgorithm,
+    /// The signed value has expired, e.g. the `exp` claim of a token
+    #[error("expired")]
+    Expired,
+    /// The `nbf` claim indicates that the token is not valid yet
+    #[error("token is not valid yet")]
+    NotYetValid,
+    /// The `iss` claim doesn't match the expected issuer
+    #[error("invalid issuer")]
+    InvalidIssuer,
+    /// The `aud` claim doesn't match the expected audience
+    #[error("invalid audience")]
+    InvalidAudience,
+    /// The `sub` claim doesn't match the expected subject
+    #[error("invalid subject")]
+    InvalidSubject,
+    /// A required claim is missing
+    #[error("missing required claim `{0}`")]
+    MissingClaim(String),
+    /// A component covered by the signature is missing, e.g. a header of the HTTP request
+    #[error("missing component `{0}`")]
+    MissingComponent(String),
+    /// Computed MAC doesn't match the expected one
+    #[error("mac mismatch")]
+    MacMismatch,
+    /// Integrity metadata (e.g. a `Content-Digest` header) is malformed or has no supported algorithm
+    #[error("invalid digest")]
+    InvalidDigest,
+    /// Computed digest doesn't match the expected one
+    #[error("digest mismatch")]
+    DigestMismatch,
+    /// Merkle proof is malformed or doesn't match the tree
+    #[error("invalid proof")]
+    InvalidProof,
+    /// Entry of the hash-chained log doesn't match the previous entries, the index of the first
+    /// broken entry is included
+    #[error("log is broken at entry {0}")]
+    BrokenChain(u64),
+    /// Line of the log file can't be parsed, the line number (starting at 1) is included
+    #[error("invalid log line {0}")]
+    InvalidLogLine(u64),
+    /// Exported state of a hasher is malformed or from an unsupported version
+    #[error("invalid hasher state")]
+    InvalidState,
+    /// String or bytes don't match the expected encoding, e.g. an unknown multibase prefix
+    #[error("invalid encoding")]
+    InvalidEncoding,
+    /// Input contains the blocks of a known SHA-1 collision attack
+    #[error("sha1 collision attack detected")]
+    CollisionDetected,
+    /// Timestamp of the signed message is outside of the tolerance
+    #[error("timestamp is outside of the tolerance")]
+    InvalidTimestamp,
+    /// Ciphertext doesn't have a valid shape
+    #[error("invalid ciphertext")]
+    InvalidCiphertext,
+    /// Decryption failed, the key is invalid or the ciphertext was modified
+    #[error("decryption failed")]
+    Decryption,
+    /// Encrypted stream ended before its final chunk
+    #[error("stream is truncated")]
+    Truncated,
+    /// I/O error
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    /// Failed to serialize or deserialize json
+    #[cfg(feature = "serde_json")]
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Alias to a `Result<T, Error>` with the custom [enum@Error].
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(feature = "jwt")]
+impl From<jsonwebtoken::errors::Error> for Error {
+    fn from(err: jsonwebtoken::errors::Error) -> Self {
+        use jsonwebtoken::errors::ErrorKind;
+
+        match err.into_kind() {
+            ErrorKind::InvalidSignature => Self::InvalidSignature,
+            ErrorKind::InvalidAlgorithm
+            | ErrorKind::InvalidAlgorithmName
+            | ErrorKind::MissingAlgorithm => Self::InvalidAlgorithm,
+            ErrorKind::InvalidEcdsaKey
+            | ErrorKind::InvalidRsaKey(_)
+            | ErrorKind::RsaFailedSigning
+            | ErrorKind::InvalidKeyFormat
+            | ErrorKind::Crypto(_) => Self::InvalidKey,
+            ErrorKind::ExpiredSignature => Self::Expired,
+            ErrorKind::ImmatureSignature => Self::NotYetValid,
+            ErrorKind::InvalidIssuer => Self::InvalidIssuer,
+            ErrorKind::InvalidAudience => Self::InvalidAudience,
+            ErrorKind::InvalidSubject => Self::InvalidSubject,
+            ErrorKind::MissingRequiredClaim(claim) => Self::MissingClaim(claim),
+            ErrorKind::Json(err) => match std::sync::Arc::try_unwrap(err) {
+                Ok(err) => Self::Json(err),
+                Err(_) => Self::InvalidToken,
+            },
+            _ => Self::InvalidToken,
+        }
+    }
+}
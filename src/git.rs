@@ -0,0 +1,582 @@
+//! Module for computing git object ids without the git executable.
+//!
+//! Objects are hashed as `<kind> <length>\0<content>`, with SHA-1 in the default object
+//! format of git and with SHA-256 in the `--object-format=sha256` repositories.
+//!
+//! ```
+//! use crypto_utils::{git, sha::Algorithm};
+//!
+//! // git hash-object hello.txt
+//! let id = git::blob_id(Algorithm::SHA1, b"hello world\n").unwrap();
+//! assert_eq!(id.to_string(), "3b18e512dba79e4c8300dd08aeb37f8e728b8dad");
+//! ```
+//!
+//! The id of a directory is the same as the one of `git add -A && git write-tree` in a
+//! repository without ignored files:
+//!
+//! ```no_run
+//! use crypto_utils::{git, sha::Algorithm};
+//!
+//! let id = git::dir_id(Algorithm::SHA1, "vendor/serde").unwrap();
+//!
+//! // compare with the tree id of the upstream commit (`git rev-parse v1.0.152^{tree}`)
+//! println!("{id}");
+//! ```
+
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::Path,
+};
+
+use crate::{
+    sha::{Algorithm, CryptographicHash, Digest},
+    Error, Result,
+};
+
+/// Kind of a git object
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    /// Content of a file or the target of a symlink
+    Blob,
+    /// Directory listing
+    Tree,
+    /// Commit
+    Commit,
+    /// Annotated tag
+    Tag,
+}
+
+impl ObjectKind {
+    /// Name of the kind in the object header
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Blob => "blob",
+            Self::Tree => "tree",
+            Self::Commit => "commit",
+            Self::Tag => "tag",
+        }
+    }
+}
+
+/// Mode of a tree entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileMode {
+    /// Regular file (`100644`)
+    Regular,
+    /// Executable file (`100755`)
+    Executable,
+    /// Symbolic link (`120000`)
+    Symlink,
+    /// Subdirectory (`40000`)
+    Tree,
+    /// Submodule commit (`160000`)
+    Submodule,
+}
+
+impl FileMode {
+    /// Octal mode as written in a tree object
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Regular => "100644",
+            Self::Executable => "100755",
+            Self::Symlink => "120000",
+            Self::Tree => "40000",
+            Self::Submodule => "160000",
+        }
+    }
+}
+
+/// Entry of a tree object
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    /// Mode of the entry
+    pub mode: FileMode,
+    /// File name, without the path of the directory
+    pub name: String,
+    /// Id of the blob, tree or submodule commit
+    pub id: Digest,
+}
+
+/// Identity and time of the author or committer of a commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    /// Name of the person
+    pub name: String,
+    /// Email of the person
+    pub email: String,
+    /// Unix timestamp (in seconds)
+    pub timestamp: i64,
+    /// Offset of the timezone from UTC in minutes, e.g. `-330` for `-0530`
+    pub offset: i32,
+}
+
+impl Signature {
+    /// Format the signature as in a commit object: `Name <email> 1700000000 +0100`.
+    fn encode(&self) -> String {
+        let sign = if self.offset < 0 { '-' } else { '+' };
+        let offset = self.offset.unsigned_abs();
+
+        format!(
+            "{} <{}> {} {sign}{:02}{:02}",
+            self.name,
+            self.email,
+            self.timestamp,
+            offset / 60,
+            offset % 60
+        )
+    }
+}
+
+/// Commit object, without the optional headers like `gpgsig` or `encoding`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    /// Id of the root tree
+    pub tree: Digest,
+    /// Ids of the parent commits
+    pub parents: Vec<Digest>,
+    /// Author of the changes
+    pub author: Signature,
+    /// Creator of the commit
+    pub committer: Signature,
+    /// Message of the commit, git ends it with a newline
+    pub message: String,
+}
+
+impl Commit {
+    /// Content of the commit object.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut content = format!("tree {}\n", self.tree);
+
+        for parent in &self.parents {
+            content.push_str(&format!("parent {parent}\n"));
+        }
+
+        content.push_str(&format!("author {}\n", self.author.encode()));
+        content.push_str(&format!("committer {}\n", self.committer.encode()));
+        content.push('\n');
+        content.push_str(&self.message);
+
+        content.into_bytes()
+    }
+
+    /// Compute the id of the commit.
+    ///
+    /// Returns [Error::InvalidAlgorithm] if the tree or a parent id uses another object format
+    /// and [Error::InvalidDigest] if it doesn't have the length of the algorithm.
+    ///
+    /// ```
+    /// use crypto_utils::{
+    ///     git::{self, Commit, Signature},
+    ///     sha::Algorithm,
+    /// };
+    ///
+    /// let author = Signature {
+    ///     name: "Jane Doe".to_string(),
+    ///     email: "jane@example.com".to_string(),
+    ///     timestamp: 1700000000,
+    ///     offset: 60,
+    /// };
+    ///
+    /// let commit = Commit {
+    ///     tree: git::tree_id(Algorithm::SHA1, &[]).unwrap(),
+    ///     parents: Vec::new(),
+    ///     author: author.clone(),
+    ///     committer: author,
+    ///     message: "Initial commit\n".to_string(),
+    /// };
+    ///
+    /// let id = commit.id(Algorithm::SHA1).unwrap();
+    /// ```
+    pub fn id(&self, algo: Algorithm) -> Result<Digest> {
+        for id in std::iter::once(&self.tree).chain(&self.parents) {
+            check_id(algo, id)?;
+        }
+
+        object_id(algo, ObjectKind::Commit, &self.encode())
+    }
+}
+
+/// Compute the id of an object from its content.
+///
+/// Only `SHA1` and `SHA256` are allowed, other algorithms return [Error::InvalidAlgorithm].
+pub fn object_id(algo: Algorithm, kind: ObjectKind, content: &[u8]) -> Result<Digest> {
+    let mut hasher = object_hasher(algo, kind, content.len() as u64)?;
+    hasher.update(content);
+
+    Ok(hasher.digest())
+}
+
+/// Compute the id of a blob, the same as `git hash-object`.
+///
+/// ```
+/// use crypto_utils::{git, sha::Algorithm};
+///
+/// let id = git::blob_id(Algorithm::SHA256, b"").unwrap();
+///
+/// assert_eq!(id.to_string(), "473a0f4c3be8a93681a267e3b1e9a7dcda1185436fe141f7749120a303721813");
+/// ```
+pub fn blob_id(algo: Algorithm, content: impl AsRef<[u8]>) -> Result<Digest> {
+    object_id(algo, ObjectKind::Blob, content.as_ref())
+}
+
+/// Compute the id of a blob streamed from the reader, its length must be known upfront.
+///
+/// A reader with another length returns an [Error::Io] with [io::ErrorKind::InvalidData].
+pub fn blob_id_reader(algo: Algorithm, reader: impl Read, len: u64) -> Result<Digest> {
+    let mut hasher = object_hasher(algo, ObjectKind::Blob, len)?;
+
+    // read one more byte to detect a longer reader
+    let copied = io::copy(&mut reader.take(len + 1), &mut hasher)?;
+    if copied != len {
+        return Err(
+            io::Error::new(io::ErrorKind::InvalidData, "unexpected length of the blob").into(),
+        );
+    }
+
+    Ok(hasher.digest())
+}
+
+/// Compute the id of the file content.
+pub fn file_id(algo: Algorithm, path: impl AsRef<Path>) -> Result<Digest> {
+    let file = File::open(path)?;
+    let len = file.metadata()?.len();
+
+    blob_id_reader(algo, file, len)
+}
+
+/// Compute the id of a tree from its entries, they are sorted the way git sorts them.
+///
+/// Returns [Error::InvalidAlgorithm] if an entry id uses another object format and
+/// [Error::InvalidDigest] if it doesn't have the length of the algorithm.
+///
+/// ```
+/// use crypto_utils::{git, sha::Algorithm};
+///
+/// let id = git::tree_id(Algorithm::SHA1, &[]).unwrap();
+///
+/// assert_eq!(id.to_string(), "4b825dc642cb6eb9a060e54bf8d69288fbee4904");
+/// ```
+pub fn tree_id(algo: Algorithm, entries: &[TreeEntry]) -> Result<Digest> {
+    let mut sorted = entries.iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|entry| sort_key(entry));
+
+    let mut content = Vec::new();
+    for entry in sorted {
+        check_id(algo, &entry.id)?;
+
+        content.extend_from_slice(entry.mode.as_str().as_bytes());
+        content.push(b' ');
+        content.extend_from_slice(entry.name.as_bytes());
+        content.push(0);
+        content.extend_from_slice(entry.id.as_bytes());
+    }
+
+    object_id(algo, ObjectKind::Tree, &content)
+}
+
+/// Compute the id of the directory tree, the same as `git add -A && git write-tree`.
+///
+/// Empty directories, `.git` and special files like sockets or FIFOs are skipped, the ignore
+/// rules aren't applied. Only the
+/// executable bit of the owner is kept from the permissions, it's always unset on Windows.
+pub fn dir_id(algo: Algorithm, path: impl AsRef<Path>) -> Result<Digest> {
+    match dir_entries(algo, path.as_ref())? {
+        Some(id) => Ok(id),
+        None => tree_id(algo, &[]),
+    }
+}
+
+/// Hash the directory, `None` if it doesn't contain any files
+fn dir_entries(algo: Algorithm, dir: &Path) -> Result<Option<Digest>> {
+    let mut entries = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "file name is not UTF-8"))?;
+
+        if name == ".git" {
+            continue;
+        }
+
+        let file_type = entry.file_type()?;
+
+        let (mode, id) = if file_type.is_symlink() {
+            let target = fs::read_link(&path)?;
+            let target = target.to_str().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "symlink target is not UTF-8")
+            })?;
+
+            (FileMode::Symlink, blob_id(algo, target)?)
+        } else if file_type.is_dir() {
+            match dir_entries(algo, &path)? {
+                Some(id) => (FileMode::Tree, id),
+                None => continue,
+            }
+        } else if file_type.is_file() {
+            let mode = match is_executable(&entry.metadata()?) {
+                true => FileMode::Executable,
+                false => FileMode::Regular,
+            };
+
+            (mode, file_id(algo, &path)?)
+        } else {
+            // git doesn't track special files
+            continue;
+        };
+
+        entries.push(TreeEntry { mode, name, id });
+    }
+
+    match entries.is_empty() {
+        true => Ok(None),
+        false => tree_id(algo, &entries).map(Some),
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o100 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// Git compares the names of the directories as if they ended with a slash
+fn sort_key(entry: &TreeEntry) -> Vec<u8> {
+    let mut key = entry.name.as_bytes().to_vec();
+
+    if entry.mode == FileMode::Tree {
+        key.push(b'/');
+    }
+
+    key
+}
+
+/// Create a hasher with the object header written.
+fn object_hasher(algo: Algorithm, kind: ObjectKind, len: u64) -> Result<CryptographicHash> {
+    if !matches!(algo, Algorithm::SHA1 | Algorithm::SHA256) {
+        return Err(Error::InvalidAlgorithm);
+    }
+
+    let mut hasher = CryptographicHash::new(algo);
+    hasher.update(format!("{} {len}\0", kind.as_str()).as_bytes());
+
+    Ok(hasher)
+}
+
+/// Ids of the other objects must use the same object format.
+fn check_id(algo: Algorithm, id: &Digest) -> Result<()> {
+    if id.algorithm() != algo {
+        return Err(Error::InvalidAlgorithm);
+    }
+
+    match id.as_bytes().len() == algo.output_len() {
+        true => Ok(()),
+        false => Err(Error::InvalidDigest),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        blob_id, blob_id_reader, dir_id, tree_id, Algorithm, Commit, Error, FileMode, Signature,
+        TreeEntry,
+    };
+    use crate::sha::Digest;
+
+    /// Parse an expected id
+    fn id(algo: Algorithm, hex: &str) -> Digest {
+        Digest::new(algo, hex::decode(hex).unwrap())
+    }
+
+    fn signature(name: &str, email: &str, timestamp: i64, offset: i32) -> Signature {
+        Signature {
+            name: name.to_string(),
+            email: email.to_string(),
+            timestamp,
+            offset,
+        }
+    }
+
+    /// Compare the blob ids with `git hash-object`
+    #[test]
+    fn blob() {
+        let vectors = [
+            (
+                Algorithm::SHA1,
+                "",
+                "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391",
+            ),
+            (
+                Algorithm::SHA1,
+                "hello world\n",
+                "3b18e512dba79e4c8300dd08aeb37f8e728b8dad",
+            ),
+            (
+                Algorithm::SHA256,
+                "",
+                "473a0f4c3be8a93681a267e3b1e9a7dcda1185436fe141f7749120a303721813",
+            ),
+            (
+                Algorithm::SHA256,
+                "hello world\n",
+                "0bd69098bd9b9cc5934a610ab65da429b525361147faa7b5b922919e9a23143d",
+            ),
+        ];
+
+        for (algo, content, expected) in vectors {
+            assert_eq!(blob_id(algo, content).unwrap().to_string(), expected);
+
+            let streamed = blob_id_reader(algo, content.as_bytes(), content.len() as u64);
+            assert_eq!(streamed.unwrap().to_string(), expected);
+        }
+    }
+
+    /// Reject other algorithms and readers with another length
+    #[test]
+    fn invalid() {
+        let err = blob_id(Algorithm::SHA512, b"").unwrap_err();
+        assert!(matches!(err, Error::InvalidAlgorithm));
+
+        let err = blob_id_reader(Algorithm::SHA1, &b"hello"[..], 4).unwrap_err();
+        assert!(matches!(err, Error::Io(_)));
+
+        let err = blob_id_reader(Algorithm::SHA1, &b"hello"[..], 6).unwrap_err();
+        assert!(matches!(err, Error::Io(_)));
+
+        // tree entry in the SHA-1 format
+        let entry = TreeEntry {
+            mode: FileMode::Regular,
+            name: "file".to_string(),
+            id: blob_id(Algorithm::SHA1, b"").unwrap(),
+        };
+        let err = tree_id(Algorithm::SHA256, &[entry]).unwrap_err();
+        assert!(matches!(err, Error::InvalidAlgorithm));
+
+        // truncated tree id
+        let commit = Commit {
+            tree: Digest::new(Algorithm::SHA1, vec![0; 19]),
+            parents: Vec::new(),
+            author: signature("A", "a@example.com", 0, 0),
+            committer: signature("A", "a@example.com", 0, 0),
+            message: String::new(),
+        };
+        let err = commit.id(Algorithm::SHA1).unwrap_err();
+        assert!(matches!(err, Error::InvalidDigest));
+    }
+
+    /// Compare the tree and commit ids with `git write-tree` and `git commit-tree`
+    #[test]
+    fn tree_and_commit() {
+        let vectors = [
+            (
+                Algorithm::SHA1,
+                [
+                    "3b18e512dba79e4c8300dd08aeb37f8e728b8dad",
+                    "a5162f80d4a6782b7cb2a0a197f834e683cb9eb1",
+                    "1a2485251c33a70432394c93fb89330ef214bfc9",
+                    "5d90422423db5ef6b431e8b9e60e0baf04b8742a",
+                ],
+                "b6297ae5dfd25074a545220931303d8e7c9454ec",
+                "d18217c6d3be6bbcf4059cfff513a33efb5a8a69",
+                "a4f77e54e30b79421718ff87ee4a186089f50110",
+            ),
+            (
+                Algorithm::SHA256,
+                [
+                    "0bd69098bd9b9cc5934a610ab65da429b525361147faa7b5b922919e9a23143d",
+                    "6cafa536fe7763ce8320204b29269847816b8a13216afd94b09c8aae7cf829a8",
+                    "1249034e3cf9007362d695b09b1fbdb4c578903bf10b665749b94743f8177ce1",
+                    "2899d38c316d6ba16a3a41ed1ab010319cd089021a104b2ca1d738a0f390f128",
+                ],
+                "17a0d8c6572315ada088d9219b33f65db6e1fc72fd6227c52cabd89a693d2734",
+                "ec2ef616d63523d59c73e664cce9da75f46b4dd84f1ebab237e2632b7c25cfd8",
+                "0353a0b3d91e0457902181aa8517f391827fa6cea5d75386ec3fac0480986322",
+            ),
+        ];
+
+        for (algo, [hello, link, run, src], tree, first, second) in vectors {
+            // tree with the entries in another order than git sorts them
+            let entries = [
+                (FileMode::Tree, "src", src),
+                (FileMode::Executable, "run.sh", run),
+                (FileMode::Symlink, "link", link),
+                (FileMode::Regular, "hello.txt", hello),
+            ]
+            .map(|(mode, name, hex)| TreeEntry {
+                mode,
+                name: name.to_string(),
+                id: id(algo, hex),
+            });
+
+            assert_eq!(tree_id(algo, &entries).unwrap(), id(algo, tree));
+
+            // root commit
+            let jane = signature("Jane Doe", "jane@example.com", 1700000000, 60);
+            let commit = Commit {
+                tree: id(algo, tree),
+                parents: Vec::new(),
+                author: jane.clone(),
+                committer: jane,
+                message: "Initial commit\n".to_string(),
+            };
+
+            assert_eq!(commit.id(algo).unwrap(), id(algo, first));
+
+            // commit with a parent and a negative timezone
+            let commit = Commit {
+                tree: id(algo, tree),
+                parents: vec![id(algo, first)],
+                author: signature("Jane Doe", "jane@example.com", 1700000100, -330),
+                committer: signature("John Roe", "john@example.com", 1700000200, -330),
+                message: "Second\n".to_string(),
+            };
+
+            assert_eq!(commit.id(algo).unwrap(), id(algo, second));
+        }
+    }
+
+    /// Hash a directory like `git add -A && git write-tree`
+    #[cfg(unix)]
+    #[test]
+    fn dir() {
+        use std::{fs, os::unix::fs::PermissionsExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+
+        // create the same files as in the git repository
+        fs::write(root.join("hello.txt"), "hello world\n").unwrap();
+        fs::write(root.join("run.sh"), "#!/bin/sh\n").unwrap();
+        fs::set_permissions(root.join("run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+        fs::create_dir_all(root.join("src/empty")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::os::unix::fs::symlink("hello.txt", root.join("link")).unwrap();
+
+        // `.git` is skipped
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(root.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+
+        // and so are special files
+        let _socket = std::os::unix::net::UnixListener::bind(root.join("src/socket")).unwrap();
+
+        // compare with the tree ids
+        assert_eq!(
+            dir_id(Algorithm::SHA1, root).unwrap().to_string(),
+            "b6297ae5dfd25074a545220931303d8e7c9454ec"
+        );
+        assert_eq!(
+            dir_id(Algorithm::SHA256, root).unwrap().to_string(),
+            "17a0d8c6572315ada088d9219b33f65db6e1fc72fd6227c52cabd89a693d2734"
+        );
+    }
+}
@@ -0,0 +1,535 @@
+use aes_gcm::{Aes128Gcm, Aes256Gcm};
+use chacha20poly1305::{
+    aead::{generic_array::GenericArray, Aead, KeyInit, Payload},
+    ChaCha20Poly1305,
+};
+
+use super::{
+    kem::{decap, encap, ENC_LEN, KEM_ID},
+    labeled::{labeled_expand, labeled_extract, KDF_ID},
+    AlgorithmHpke, PrivateKey, PublicKey,
+};
+use crate::{Error, Result};
+
+/// Length of the nonce in bytes
+const NONCE_LEN: usize = 12;
+/// Length of the exporter secret in bytes
+const EXPORTER_SECRET_LEN: usize = 32;
+/// Identifier of the base mode (no PSK and no sender authentication)
+const MODE_BASE: u8 = 0x00;
+
+/// Encrypt a single message to the recipient's public key.
+///
+/// Returns the encapsulated key (32 bytes) followed by the ciphertext,
+/// `info` and `aad` must be the same when opening.
+///
+/// ```
+/// use crypto_utils::hpke::{open, seal, AlgorithmHpke, PrivateKey};
+///
+/// // recipient keys
+/// let private_key = PrivateKey::generate();
+/// let public_key = private_key.public_key();
+///
+/// // encrypt to the public key
+/// let sealed = seal(AlgorithmHpke::ChaCha20Poly1305, &public_key, b"app", b"", b"input").unwrap();
+///
+/// // decrypt with the private key
+/// let plaintext = open(AlgorithmHpke::ChaCha20Poly1305, &private_key, b"app", b"", &sealed).unwrap();
+///
+/// assert_eq!(plaintext, b"input");
+/// ```
+pub fn seal(
+    algo: AlgorithmHpke,
+    public_key: &PublicKey,
+    info: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>> {
+    let (mut context, enc) = SenderContext::new(algo, public_key, info)?;
+
+    let ciphertext = context.seal(aad, plaintext)?;
+
+    Ok([enc, ciphertext].concat())
+}
+
+/// Decrypt a single message sealed with [seal].
+///
+/// ```no_run
+/// # use crypto_utils::hpke::{open, seal, AlgorithmHpke, PrivateKey};
+/// #
+/// # let private_key = PrivateKey::generate();
+/// # let sealed = seal(AlgorithmHpke::Aes128Gcm, &private_key.public_key(), b"", b"", b"input").unwrap();
+/// #
+/// let plaintext: Vec<u8> = open(AlgorithmHpke::Aes128Gcm, &private_key, b"", b"", &sealed).unwrap();
+/// ```
+pub fn open(
+    algo: AlgorithmHpke,
+    private_key: &PrivateKey,
+    info: &[u8],
+    aad: &[u8],
+    sealed: &[u8],
+) -> Result<Vec<u8>> {
+    if sealed.len() < ENC_LEN {
+        return Err(Error::InvalidCiphertext);
+    }
+
+    let (enc, ciphertext) = sealed.split_at(ENC_LEN);
+
+    RecipientContext::new(algo, private_key, enc, info)?.open(aad, ciphertext)
+}
+
+/// Sender context used to encrypt a sequence of messages to the same recipient.
+///
+/// ```
+/// use crypto_utils::hpke::{AlgorithmHpke, PrivateKey, RecipientContext, SenderContext};
+///
+/// let private_key = PrivateKey::generate();
+///
+/// // set up the sender, `enc` has to be sent to the recipient
+/// let (mut sender, enc) = SenderContext::new(AlgorithmHpke::Aes128Gcm, &private_key.public_key(), b"app").unwrap();
+///
+/// let first = sender.seal(b"", b"first").unwrap();
+/// let second = sender.seal(b"", b"second").unwrap();
+///
+/// // set up the recipient, messages are opened in the same order
+/// let mut recipient = RecipientContext::new(AlgorithmHpke::Aes128Gcm, &private_key, &enc, b"app").unwrap();
+///
+/// assert_eq!(recipient.open(b"", &first).unwrap(), b"first");
+/// assert_eq!(recipient.open(b"", &second).unwrap(), b"second");
+/// ```
+pub struct SenderContext(Context);
+
+impl SenderContext {
+    /// Set up a context for the recipient's public key, returns the context
+    /// and the encapsulated key for the recipient.
+    pub fn new(
+        algo: AlgorithmHpke,
+        public_key: &PublicKey,
+        info: &[u8],
+    ) -> Result<(Self, Vec<u8>)> {
+        Self::new_with_ephemeral(algo, public_key, info, &PrivateKey::generate())
+    }
+
+    fn new_with_ephemeral(
+        algo: AlgorithmHpke,
+        public_key: &PublicKey,
+        info: &[u8],
+        ephemeral: &PrivateKey,
+    ) -> Result<(Self, Vec<u8>)> {
+        let (shared_secret, enc) = encap(ephemeral, public_key)?;
+
+        Ok((Self(Context::new(algo, &shared_secret, info)?), enc))
+    }
+
+    /// Encrypt the next message, `aad` is authenticated but not encrypted.
+    pub fn seal(&mut self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        let ciphertext = self.0.cipher.encrypt(&self.0.nonce(), plaintext, aad)?;
+        self.0.increment_seq()?;
+
+        Ok(ciphertext)
+    }
+
+    /// Derive a secret of `len` bytes from the context, the recipient derives the same secret.
+    pub fn export(&self, exporter_context: &[u8], len: usize) -> Result<Vec<u8>> {
+        self.0.export(exporter_context, len)
+    }
+}
+
+/// Recipient context used to decrypt a sequence of messages, see [SenderContext].
+pub struct RecipientContext(Context);
+
+impl RecipientContext {
+    /// Set up a context from the encapsulated key sent by the sender.
+    pub fn new(
+        algo: AlgorithmHpke,
+        private_key: &PrivateKey,
+        enc: &[u8],
+        info: &[u8],
+    ) -> Result<Self> {
+        let shared_secret = decap(enc, private_key)?;
+
+        Ok(Self(Context::new(algo, &shared_secret, info)?))
+    }
+
+    /// Decrypt the next message, returns [Error::Decryption] if it was modified.
+    ///
+    /// The sequence number only advances when the message is opened, so a forged message
+    /// doesn't prevent opening the next genuine one.
+    pub fn open(&mut self, aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let plaintext = self.0.cipher.decrypt(&self.0.nonce(), ciphertext, aad)?;
+        self.0.increment_seq()?;
+
+        Ok(plaintext)
+    }
+
+    /// Derive a secret of `len` bytes from the context, the sender derives the same secret.
+    pub fn export(&self, exporter_context: &[u8], len: usize) -> Result<Vec<u8>> {
+        self.0.export(exporter_context, len)
+    }
+}
+
+/// Encryption context shared by the sender and the recipient
+struct Context {
+    suite_id: Vec<u8>,
+    cipher: Cipher,
+    base_nonce: [u8; NONCE_LEN],
+    exporter_secret: Vec<u8>,
+    seq: u64,
+}
+
+impl Context {
+    /// Key schedule of the base mode
+    fn new(algo: AlgorithmHpke, shared_secret: &[u8], info: &[u8]) -> Result<Self> {
+        let suite_id = [
+            b"HPKE".as_slice(),
+            &KEM_ID.to_be_bytes(),
+            &KDF_ID.to_be_bytes(),
+            &algo.id().to_be_bytes(),
+        ]
+        .concat();
+
+        let psk_id_hash = labeled_extract(&suite_id, b"", b"psk_id_hash", b"")?;
+        let info_hash = labeled_extract(&suite_id, b"", b"info_hash", info)?;
+        let context = [&[MODE_BASE], psk_id_hash.as_slice(), &info_hash].concat();
+
+        let secret = labeled_extract(&suite_id, shared_secret, b"secret", b"")?;
+
+        let key = labeled_expand(&suite_id, &secret, b"key", &context, algo.key_len())?;
+        let base_nonce = labeled_expand(&suite_id, &secret, b"base_nonce", &context, NONCE_LEN)?;
+        let exporter_secret =
+            labeled_expand(&suite_id, &secret, b"exp", &context, EXPORTER_SECRET_LEN)?;
+
+        Ok(Self {
+            cipher: Cipher::new(algo, &key)?,
+            base_nonce: base_nonce.try_into().unwrap(),
+            exporter_secret,
+            suite_id,
+            seq: 0,
+        })
+    }
+
+    /// Compute the nonce of the current message
+    fn nonce(&self) -> [u8; NONCE_LEN] {
+        let mut nonce = self.base_nonce;
+
+        for (byte, seq) in nonce[NONCE_LEN - 8..]
+            .iter_mut()
+            .zip(self.seq.to_be_bytes())
+        {
+            *byte ^= seq;
+        }
+
+        nonce
+    }
+
+    /// Move to the next message (RFC 9180 §5.2)
+    fn increment_seq(&mut self) -> Result<()> {
+        // message limit of the key has been reached
+        self.seq = self.seq.checked_add(1).ok_or(Error::InvalidKey)?;
+
+        Ok(())
+    }
+
+    fn export(&self, exporter_context: &[u8], len: usize) -> Result<Vec<u8>> {
+        labeled_expand(
+            &self.suite_id,
+            &self.exporter_secret,
+            b"sec",
+            exporter_context,
+            len,
+        )
+    }
+}
+
+enum Cipher {
+    Aes128Gcm(Box<Aes128Gcm>),
+    Aes256Gcm(Box<Aes256Gcm>),
+    ChaCha20Poly1305(ChaCha20Poly1305),
+}
+
+impl Cipher {
+    fn new(algo: AlgorithmHpke, key: &[u8]) -> Result<Self> {
+        Ok(match algo {
+            AlgorithmHpke::Aes128Gcm => Self::Aes128Gcm(Box::new(
+                Aes128Gcm::new_from_slice(key).map_err(|_| Error::InvalidKeyLength)?,
+            )),
+            AlgorithmHpke::Aes256Gcm => Self::Aes256Gcm(Box::new(
+                Aes256Gcm::new_from_slice(key).map_err(|_| Error::InvalidKeyLength)?,
+            )),
+            AlgorithmHpke::ChaCha20Poly1305 => Self::ChaCha20Poly1305(
+                ChaCha20Poly1305::new_from_slice(key).map_err(|_| Error::InvalidKeyLength)?,
+            ),
+        })
+    }
+
+    fn encrypt(&self, nonce: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+        let nonce = GenericArray::from_slice(nonce);
+        let payload = Payload {
+            msg: plaintext,
+            aad,
+        };
+
+        match self {
+            Self::Aes128Gcm(cipher) => cipher.encrypt(nonce, payload),
+            Self::Aes256Gcm(cipher) => cipher.encrypt(nonce, payload),
+            Self::ChaCha20Poly1305(cipher) => cipher.encrypt(nonce, payload),
+        }
+        .map_err(|_| Error::InvalidCiphertext)
+    }
+
+    fn decrypt(&self, nonce: &[u8], ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+        let nonce = GenericArray::from_slice(nonce);
+        let payload = Payload {
+            msg: ciphertext,
+            aad,
+        };
+
+        match self {
+            Self::Aes128Gcm(cipher) => cipher.decrypt(nonce, payload),
+            Self::Aes256Gcm(cipher) => cipher.decrypt(nonce, payload),
+            Self::ChaCha20Poly1305(cipher) => cipher.decrypt(nonce, payload),
+        }
+        .map_err(|_| Error::Decryption)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{open, seal, AlgorithmHpke, PrivateKey, RecipientContext, SenderContext};
+    use crate::Error;
+
+    /// RFC 9180 test vector
+    struct Vector {
+        name: &'static str,
+        algo: AlgorithmHpke,
+        info: &'static str,
+        sk_em: &'static str,
+        sk_rm: &'static str,
+        enc: &'static str,
+        // (sequence number, aad, plaintext, ciphertext)
+        encryptions: &'static [(u64, &'static str, &'static str, &'static str)],
+        // (exporter context, exported value)
+        exports: &'static [(&'static str, &'static str)],
+    }
+
+    const VECTORS: &[Vector] = &[
+        Vector {
+            name: "A.1.1",
+            algo: AlgorithmHpke::Aes128Gcm,
+            info: "4f6465206f6e2061204772656369616e2055726e",
+            sk_em: "52c4a758a802cd8b936eceea314432798d5baf2d7e9235dc084ab1b9cfa2f736",
+            sk_rm: "4612c550263fc8ad58375df3f557aac531d26850903e55a9f23f21d8534e8ac8",
+            enc: "37fda3567bdbd628e88668c3c8d7e97d1d1253b6d4ea6d44c150f741f1bf4431",
+            encryptions: &[(
+                0,
+                "436f756e742d30",
+                "4265617574792069732074727574682c20747275746820626561757479",
+                "f938558b5d72f1a23810b4be2ab4f84331acc02fc97babc53a52ae8218a355a96d8770ac83d07bea87e13c512a",
+            )],
+            exports: &[
+                (
+                    "",
+                    "3853fe2b4035195a573ffc53856e77058e15d9ea064de3e59f4961d0095250ee",
+                ),
+                (
+                    "00",
+                    "2e8f0b54673c7029649d4eb9d5e33bf1872cf76d623ff164ac185da9e88c21a5",
+                ),
+                (
+                    "54657374436f6e74657874",
+                    "e9e43065102c3836401bed8c3c3c75ae46be1639869391d62c61f1ec7af54931",
+                ),
+            ],
+        },
+        Vector {
+            name: "A.2.1",
+            algo: AlgorithmHpke::ChaCha20Poly1305,
+            info: "4f6465206f6e2061204772656369616e2055726e",
+            sk_em: "f4ec9b33b792c372c1d2c2063507b684ef925b8c75a42dbcbf57d63ccd381600",
+            sk_rm: "8057991eef8f1f1af18f4a9491d16a1ce333f695d4db8e38da75975c4478e0fb",
+            enc: "1afa08d3dec047a643885163f1180476fa7ddb54c6a8029ea33f95796bf2ac4a",
+            encryptions: &[(
+                0,
+                "436f756e742d30",
+                "4265617574792069732074727574682c20747275746820626561757479",
+                "1c5250d8034ec2b784ba2cfd69dbdb8af406cfe3ff938e131f0def8c8b60b4db21993c62ce81883d2dd1b51a28",
+            )],
+            exports: &[],
+        },
+    ];
+
+    /// Test the RFC 9180 test vectors
+    #[test]
+    fn test_vectors() {
+        for vector in VECTORS {
+            let name = vector.name;
+            let info = hex::decode(vector.info).unwrap();
+            let ephemeral = PrivateKey::from_bytes(hex::decode(vector.sk_em).unwrap()).unwrap();
+            let private_key = PrivateKey::from_bytes(hex::decode(vector.sk_rm).unwrap()).unwrap();
+
+            // set up the sender
+            let (mut sender, enc) = SenderContext::new_with_ephemeral(
+                vector.algo,
+                &private_key.public_key(),
+                &info,
+                &ephemeral,
+            )
+            .unwrap();
+
+            assert_eq!(hex::encode(&enc), vector.enc, "{name}");
+
+            // set up the recipient
+            let mut recipient =
+                RecipientContext::new(vector.algo, &private_key, &enc, &info).unwrap();
+
+            for (seq, aad, plaintext, ciphertext) in vector.encryptions {
+                let aad = hex::decode(aad).unwrap();
+                let plaintext = hex::decode(plaintext).unwrap();
+
+                sender.0.seq = *seq;
+                recipient.0.seq = *seq;
+
+                assert_eq!(
+                    hex::encode(sender.seal(&aad, &plaintext).unwrap()),
+                    *ciphertext,
+                    "{name}"
+                );
+                assert_eq!(
+                    recipient
+                        .open(&aad, &hex::decode(ciphertext).unwrap())
+                        .unwrap(),
+                    plaintext,
+                    "{name}"
+                );
+            }
+
+            for (exporter_context, exported) in vector.exports {
+                let exporter_context = hex::decode(exporter_context).unwrap();
+
+                assert_eq!(
+                    hex::encode(sender.export(&exporter_context, 32).unwrap()),
+                    *exported,
+                    "{name}"
+                );
+                assert_eq!(
+                    hex::encode(recipient.export(&exporter_context, 32).unwrap()),
+                    *exported,
+                    "{name}"
+                );
+            }
+        }
+    }
+
+    /// Seal and open messages with every algorithm
+    #[test]
+    fn seal_open() {
+        for algo in [
+            AlgorithmHpke::Aes128Gcm,
+            AlgorithmHpke::Aes256Gcm,
+            AlgorithmHpke::ChaCha20Poly1305,
+        ] {
+            let private_key = PrivateKey::generate();
+
+            let sealed = seal(algo, &private_key.public_key(), b"info", b"aad", b"input").unwrap();
+
+            let plaintext = open(algo, &private_key, b"info", b"aad", &sealed).unwrap();
+
+            assert_eq!(plaintext, b"input", "{algo:?}");
+        }
+    }
+
+    /// Open a message with other private key
+    #[test]
+    fn open_key_invalid() {
+        let private_key = PrivateKey::generate();
+
+        let sealed = seal(
+            AlgorithmHpke::Aes128Gcm,
+            &private_key.public_key(),
+            b"",
+            b"",
+            b"input",
+        )
+        .unwrap();
+
+        let err = open(
+            AlgorithmHpke::Aes128Gcm,
+            &PrivateKey::generate(),
+            b"",
+            b"",
+            &sealed,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, Error::Decryption));
+    }
+
+    /// Open a message with other info
+    #[test]
+    fn open_info_invalid() {
+        let private_key = PrivateKey::generate();
+
+        let sealed = seal(
+            AlgorithmHpke::ChaCha20Poly1305,
+            &private_key.public_key(),
+            b"app v1",
+            b"",
+            b"input",
+        )
+        .unwrap();
+
+        let err = open(
+            AlgorithmHpke::ChaCha20Poly1305,
+            &private_key,
+            b"app v2",
+            b"",
+            &sealed,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, Error::Decryption));
+    }
+
+    /// Open messages out of order
+    #[test]
+    fn open_out_of_order() {
+        let private_key = PrivateKey::generate();
+
+        let (mut sender, enc) =
+            SenderContext::new(AlgorithmHpke::Aes128Gcm, &private_key.public_key(), b"").unwrap();
+
+        let _first = sender.seal(b"", b"first").unwrap();
+        let second = sender.seal(b"", b"second").unwrap();
+
+        let mut recipient =
+            RecipientContext::new(AlgorithmHpke::Aes128Gcm, &private_key, &enc, b"").unwrap();
+
+        let err = recipient.open(b"", &second).unwrap_err();
+
+        assert!(matches!(err, Error::Decryption));
+    }
+
+    /// Keep the sequence number after a forged message
+    #[test]
+    fn open_tampered() {
+        let private_key = PrivateKey::generate();
+
+        let (mut sender, enc) =
+            SenderContext::new(AlgorithmHpke::Aes256Gcm, &private_key.public_key(), b"").unwrap();
+
+        let first = sender.seal(b"", b"first").unwrap();
+        let second = sender.seal(b"", b"second").unwrap();
+
+        let mut recipient =
+            RecipientContext::new(AlgorithmHpke::Aes256Gcm, &private_key, &enc, b"").unwrap();
+
+        let mut tampered = first.clone();
+        tampered[0] ^= 1;
+
+        let err = recipient.open(b"", &tampered).unwrap_err();
+        assert!(matches!(err, Error::Decryption));
+
+        assert_eq!(recipient.open(b"", &first).unwrap(), b"first");
+        assert_eq!(recipient.open(b"", &second).unwrap(), b"second");
+    }
+}
@@ -0,0 +1,217 @@
+use rand::rngs::OsRng;
+use x25519_dalek::StaticSecret;
+
+use super::labeled::{labeled_expand, labeled_extract};
+use crate::{Error, Result};
+
+/// DHKEM(X25519, HKDF-SHA256) identifier
+pub(super) const KEM_ID: u16 = 0x0020;
+/// Length of the encapsulated key in bytes
+pub(super) const ENC_LEN: usize = 32;
+/// Length of the KEM shared secret in bytes
+const SECRET_LEN: usize = 32;
+
+/// X25519 private key.
+///
+/// ```
+/// use crypto_utils::hpke::PrivateKey;
+///
+/// // generate keys of both sides
+/// let alice = PrivateKey::generate();
+/// let bob = PrivateKey::generate();
+///
+/// // compute the shared secret
+/// let alice_secret = alice.diffie_hellman(&bob.public_key()).unwrap();
+/// let bob_secret = bob.diffie_hellman(&alice.public_key()).unwrap();
+///
+/// assert_eq!(alice_secret, bob_secret);
+/// ```
+#[derive(Clone)]
+pub struct PrivateKey(StaticSecret);
+
+impl PrivateKey {
+    /// Generate a new random private key.
+    ///
+    /// ```no_run
+    /// use crypto_utils::hpke::PrivateKey;
+    ///
+    /// let private_key = PrivateKey::generate();
+    /// ```
+    pub fn generate() -> Self {
+        Self(StaticSecret::random_from_rng(OsRng))
+    }
+
+    /// Import a raw 32-byte private key.
+    ///
+    /// ```no_run
+    /// use crypto_utils::hpke::PrivateKey;
+    ///
+    /// let private_key = PrivateKey::from_bytes(&[1u8; 32]).unwrap();
+    /// ```
+    pub fn from_bytes(bytes: impl AsRef<[u8]>) -> Result<Self> {
+        let bytes = bytes.as_ref();
+
+        let bytes: [u8; 32] = bytes.try_into().map_err(|_| Error::InvalidKeyLength)?;
+
+        Ok(Self(StaticSecret::from(bytes)))
+    }
+
+    /// Export the raw private key
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes().to_vec()
+    }
+
+    /// Public key of the private key
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(x25519_dalek::PublicKey::from(&self.0))
+    }
+
+    /// Compute the X25519 shared secret with the other side's public key.
+    ///
+    /// Returns [Error::InvalidKey] if the public key is a low order point,
+    /// which would result in an all-zero shared secret.
+    ///
+    /// ```no_run
+    /// # use crypto_utils::hpke::PrivateKey;
+    /// #
+    /// # let private_key = PrivateKey::generate();
+    /// # let their_public_key = PrivateKey::generate().public_key();
+    /// #
+    /// let shared_secret: Vec<u8> = private_key.diffie_hellman(&their_public_key).unwrap();
+    /// ```
+    pub fn diffie_hellman(&self, public_key: &PublicKey) -> Result<Vec<u8>> {
+        let shared_secret = self.0.diffie_hellman(&public_key.0);
+
+        if !shared_secret.was_contributory() {
+            return Err(Error::InvalidKey);
+        }
+
+        Ok(shared_secret.as_bytes().to_vec())
+    }
+}
+
+/// X25519 public key.
+///
+/// ```
+/// use crypto_utils::hpke::{PrivateKey, PublicKey};
+///
+/// // public key shared with the other side
+/// let bytes: Vec<u8> = PrivateKey::generate().public_key().to_bytes();
+///
+/// // import public key
+/// let public_key = PublicKey::from_bytes(&bytes).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey(x25519_dalek::PublicKey);
+
+impl PublicKey {
+    /// Import a raw 32-byte public key
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let bytes: [u8; 32] = bytes.try_into().map_err(|_| Error::InvalidKeyLength)?;
+
+        Ok(Self(x25519_dalek::PublicKey::from(bytes)))
+    }
+
+    /// Export the raw public key
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes().to_vec()
+    }
+}
+
+/// Generate a shared secret and its encapsulation for the recipient's public key.
+pub(super) fn encap(ephemeral: &PrivateKey, public_key: &PublicKey) -> Result<(Vec<u8>, Vec<u8>)> {
+    let dh = ephemeral.diffie_hellman(public_key)?;
+    let enc = ephemeral.public_key().to_bytes();
+
+    let kem_context = [enc.as_slice(), &public_key.to_bytes()].concat();
+    let shared_secret = extract_and_expand(&dh, &kem_context)?;
+
+    Ok((shared_secret, enc))
+}
+
+/// Recover the shared secret from the encapsulated key.
+pub(super) fn decap(enc: &[u8], private_key: &PrivateKey) -> Result<Vec<u8>> {
+    let ephemeral = PublicKey::from_bytes(enc).map_err(|_| Error::InvalidCiphertext)?;
+    let dh = private_key.diffie_hellman(&ephemeral)?;
+
+    let kem_context = [enc, &private_key.public_key().to_bytes()].concat();
+
+    extract_and_expand(&dh, &kem_context)
+}
+
+fn extract_and_expand(dh: &[u8], kem_context: &[u8]) -> Result<Vec<u8>> {
+    let suite_id = [b"KEM".as_slice(), &KEM_ID.to_be_bytes()].concat();
+
+    let eae_prk = labeled_extract(&suite_id, b"", b"eae_prk", dh)?;
+
+    labeled_expand(
+        &suite_id,
+        &eae_prk,
+        b"shared_secret",
+        kem_context,
+        SECRET_LEN,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decap, encap, Error, PrivateKey, PublicKey};
+
+    /// RFC 7748 X25519 test vector (section 6.1)
+    #[test]
+    fn x25519_rfc7748() {
+        let alice = PrivateKey::from_bytes(
+            hex::decode("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a")
+                .unwrap(),
+        )
+        .unwrap();
+        let bob = PrivateKey::from_bytes(
+            hex::decode("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb")
+                .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            hex::encode(alice.public_key().to_bytes()),
+            "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"
+        );
+        assert_eq!(
+            hex::encode(bob.public_key().to_bytes()),
+            "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"
+        );
+
+        let shared_secret = alice.diffie_hellman(&bob.public_key()).unwrap();
+
+        assert_eq!(
+            hex::encode(&shared_secret),
+            "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742"
+        );
+        assert_eq!(
+            bob.diffie_hellman(&alice.public_key()).unwrap(),
+            shared_secret
+        );
+    }
+
+    /// Compute the shared secret with a low order public key
+    #[test]
+    fn x25519_low_order_point() {
+        let public_key = PublicKey::from_bytes(&[0u8; 32]).unwrap();
+
+        let err = PrivateKey::generate()
+            .diffie_hellman(&public_key)
+            .unwrap_err();
+
+        assert!(matches!(err, Error::InvalidKey));
+    }
+
+    /// Encapsulate and decapsulate a shared secret
+    #[test]
+    fn encap_decap() {
+        let private_key = PrivateKey::generate();
+
+        let (shared_secret, enc) =
+            encap(&PrivateKey::generate(), &private_key.public_key()).unwrap();
+
+        assert_eq!(decap(&enc, &private_key).unwrap(), shared_secret);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{
+    sha::{hkdf_expand, hkdf_extract, AlgorithmMac},
+    Result,
+};
+
+/// HKDF-SHA256 identifier
+pub(super) const KDF_ID: u16 = 0x0001;
+
+/// Version label prepended to every labeled input
+const VERSION_LABEL: &[u8] = b"HPKE-v1";
+
+/// `LabeledExtract(salt, label, ikm)`
+pub(super) fn labeled_extract(
+    suite_id: &[u8],
+    salt: &[u8],
+    label: &[u8],
+    ikm: &[u8],
+) -> Result<Vec<u8>> {
+    let labeled_ikm = [VERSION_LABEL, suite_id, label, ikm].concat();
+
+    hkdf_extract(AlgorithmMac::HmacSHA256, salt, &labeled_ikm)
+}
+
+/// `LabeledExpand(prk, label, info, len)`
+pub(super) fn labeled_expand(
+    suite_id: &[u8],
+    prk: &[u8],
+    label: &[u8],
+    info: &[u8],
+    len: usize,
+) -> Result<Vec<u8>> {
+    let labeled_info = [
+        &(len as u16).to_be_bytes(),
+        VERSION_LABEL,
+        suite_id,
+        label,
+        info,
+    ]
+    .concat();
+
+    hkdf_expand(AlgorithmMac::HmacSHA256, prk, &labeled_info, len)
+}
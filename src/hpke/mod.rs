@@ -0,0 +1,73 @@
+//! Module for X25519 key agreement and public-key encryption with HPKE ([RFC 9180](https://www.rfc-editor.org/rfc/rfc9180)).
+//!
+//! Only the base mode is supported, with the DHKEM(X25519, HKDF-SHA256) KEM and the HKDF-SHA256 KDF
+//! (built on [CryptographicMac](crate::sha::CryptographicMac)). The AEAD is chosen with [AlgorithmHpke].
+//!
+//! ## Encrypt to a public key
+//!
+//! ```
+//! use crypto_utils::hpke::{open, seal, AlgorithmHpke, PrivateKey, PublicKey};
+//!
+//! // the service generates its keys and publishes the public key
+//! let private_key = PrivateKey::generate();
+//! let public_key: Vec<u8> = private_key.public_key().to_bytes();
+//!
+//! // the client encrypts a payload to the public key
+//! let public_key = PublicKey::from_bytes(&public_key).unwrap();
+//! let sealed = seal(AlgorithmHpke::Aes128Gcm, &public_key, b"payload v1", b"", b"input").unwrap();
+//!
+//! // the service decrypts the payload
+//! let plaintext = open(AlgorithmHpke::Aes128Gcm, &private_key, b"payload v1", b"", &sealed).unwrap();
+//!
+//! assert_eq!(plaintext, b"input");
+//! ```
+//!
+//! ## X25519 key agreement
+//!
+//! ```
+//! use crypto_utils::hpke::PrivateKey;
+//!
+//! let alice = PrivateKey::generate();
+//! let bob = PrivateKey::generate();
+//!
+//! let shared_secret = alice.diffie_hellman(&bob.public_key()).unwrap();
+//!
+//! assert_eq!(shared_secret, bob.diffie_hellman(&alice.public_key()).unwrap());
+//! ```
+
+mod context;
+mod kem;
+mod labeled;
+
+pub use context::*;
+pub use kem::{PrivateKey, PublicKey};
+
+/// HPKE AEAD algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlgorithmHpke {
+    /// AES-128-GCM
+    Aes128Gcm,
+    /// AES-256-GCM
+    Aes256Gcm,
+    /// ChaCha20-Poly1305
+    ChaCha20Poly1305,
+}
+
+impl AlgorithmHpke {
+    /// Length of the key in bytes
+    fn key_len(&self) -> usize {
+        match self {
+            Self::Aes128Gcm => 16,
+            Self::Aes256Gcm | Self::ChaCha20Poly1305 => 32,
+        }
+    }
+
+    /// Identifier of the algorithm in the HPKE registry
+    fn id(&self) -> u16 {
+        match self {
+            Self::Aes128Gcm => 0x0001,
+            Self::Aes256Gcm => 0x0002,
+            Self::ChaCha20Poly1305 => 0x0003,
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Signature base of the RFC 9421 (section 2.5).
+
+use http::{header::HOST, Request, Uri};
+
+use super::structured::{serialize_parameters, Item, Member, Parameters};
+use crate::{Error, Result};
+
+/// Covered components and parameters of a signature, the value of `Signature-Input`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct SignatureParams {
+    pub(super) components: Vec<String>,
+    pub(super) params: Parameters,
+}
+
+impl SignatureParams {
+    /// Read the params from a `Signature-Input` member, parameters of the components
+    /// (e.g. `;req` or `;sf`) are not supported.
+    pub(super) fn from_member(member: Member) -> Result<Self> {
+        let Member::InnerList(items, params) = member else {
+            return Err(Error::InvalidSignature);
+        };
+
+        let components = items
+            .into_iter()
+            .map(|item| match item {
+                (Item::String(name), params) if params.is_empty() => Ok(name),
+                _ => Err(Error::InvalidSignature),
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { components, params })
+    }
+
+    /// Serialized value of the `@signature-params` component
+    pub(super) fn serialize(&self) -> String {
+        let mut out = String::from("(");
+
+        for (i, name) in self.components.iter().enumerate() {
+            if i > 0 {
+                out.push(' ');
+            }
+            Item::String(name.clone()).serialize(&mut out);
+        }
+
+        out.push(')');
+        serialize_parameters(&self.params, &mut out);
+
+        out
+    }
+
+    fn param(&self, key: &str) -> Option<&Item> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Integer parameter (e.g. `created`), returns [Error::InvalidSignature] if it isn't a
+    /// non-negative integer.
+    pub(super) fn integer(&self, key: &str) -> Result<Option<u64>> {
+        match self.param(key) {
+            Some(Item::Integer(value)) => u64::try_from(*value)
+                .map(Some)
+                .map_err(|_| Error::InvalidSignature),
+            Some(_) => Err(Error::InvalidSignature),
+            None => Ok(None),
+        }
+    }
+
+    /// String parameter (e.g. `keyid`), returns [Error::InvalidSignature] if it isn't a string.
+    pub(super) fn string(&self, key: &str) -> Result<Option<&str>> {
+        match self.param(key) {
+            Some(Item::String(value)) => Ok(Some(value)),
+            Some(_) => Err(Error::InvalidSignature),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Create the signature base of the request.
+///
+/// Returns [Error::MissingComponent] if a covered component is not present in the request.
+pub(super) fn signature_base<B>(request: &Request<B>, params: &SignatureParams) -> Result<Vec<u8>> {
+    let mut base = Vec::new();
+
+    for (i, name) in params.components.iter().enumerate() {
+        // components must not be repeated
+        if name == "@signature-params" || params.components[..i].contains(name) {
+            return Err(Error::InvalidSignature);
+        }
+
+        base.push(b'"');
+        base.extend_from_slice(name.as_bytes());
+        base.extend_from_slice(b"\": ");
+        base.extend_from_slice(&component_value(request, name)?);
+        base.push(b'\n');
+    }
+
+    base.extend_from_slice(b"\"@signature-params\": ");
+    base.extend_from_slice(params.serialize().as_bytes());
+
+    Ok(base)
+}
+
+/// Value of a derived component or a header field.
+fn component_value<B>(request: &Request<B>, name: &str) -> Result<Vec<u8>> {
+    let uri = request.uri();
+
+    let value = match name {
+        "@method" => request.method().as_str().to_string(),
+        "@target-uri" => format!(
+            "{}://{}{}",
+            scheme(uri),
+            authority(request)?,
+            request_target(uri)
+        ),
+        "@authority" => authority(request)?,
+        "@scheme" => scheme(uri),
+        "@request-target" => request_target(uri),
+        "@path" => path(uri).to_string(),
+        "@query" => format!("?{}", uri.query().unwrap_or_default()),
+        name if name.starts_with('@') => return Err(Error::MissingComponent(name.to_string())),
+        name => return field_value(request, name),
+    };
+
+    Ok(value.into_bytes())
+}
+
+/// Scheme of the request, `https` if the URI is in the origin form (e.g. on the server side).
+fn scheme(uri: &Uri) -> String {
+    uri.scheme_str().unwrap_or("https").to_ascii_lowercase()
+}
+
+/// Authority of the URI or the `Host` header, lowercased and without the default port.
+fn authority<B>(request: &Request<B>) -> Result<String> {
+    let authority = match request.uri().authority() {
+        Some(authority) => authority.as_str().to_string(),
+        None => request
+            .headers()
+            .get(HOST)
+            .and_then(|host| host.to_str().ok())
+            .ok_or_else(|| Error::MissingComponent("@authority".to_string()))?
+            .trim()
+            .to_string(),
+    };
+
+    let authority = authority.to_ascii_lowercase();
+
+    let default_port = match scheme(request.uri()).as_str() {
+        "http" => ":80",
+        "https" => ":443",
+        _ => return Ok(authority),
+    };
+
+    Ok(authority
+        .strip_suffix(default_port)
+        .map(str::to_string)
+        .unwrap_or(authority))
+}
+
+fn path(uri: &Uri) -> &str {
+    match uri.path() {
+        "" => "/",
+        path => path,
+    }
+}
+
+fn request_target(uri: &Uri) -> String {
+    match uri.query() {
+        Some(query) => format!("{}?{query}", path(uri)),
+        None => path(uri).to_string(),
+    }
+}
+
+/// Values of the header field trimmed and joined with `, `.
+fn field_value<B>(request: &Request<B>, name: &str) -> Result<Vec<u8>> {
+    let mut value = Vec::new();
+
+    for (i, field) in request.headers().get_all(name).iter().enumerate() {
+        if i > 0 {
+            value.extend_from_slice(b", ");
+        }
+        value.extend_from_slice(field.as_bytes().trim_ascii());
+    }
+
+    if !request.headers().contains_key(name) {
+        return Err(Error::MissingComponent(name.to_string()));
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::Request;
+
+    use super::{component_value, signature_base, Error, SignatureParams};
+    use crate::http_signature::structured::Item;
+
+    /// Derived components of the RFC 9421 section 2.2
+    #[test]
+    fn derived_components() {
+        let request = Request::post("/path?param=value&foo=bar&baz=bat%2Dman")
+            .header("Host", "www.example.com")
+            .body(())
+            .unwrap();
+
+        for (name, value) in [
+            ("@method", "POST"),
+            (
+                "@target-uri",
+                "https://www.example.com/path?param=value&foo=bar&baz=bat%2Dman",
+            ),
+            ("@authority", "www.example.com"),
+            ("@scheme", "https"),
+            ("@request-target", "/path?param=value&foo=bar&baz=bat%2Dman"),
+            ("@path", "/path"),
+            ("@query", "?param=value&foo=bar&baz=bat%2Dman"),
+        ] {
+            let component = component_value(&request, name).unwrap();
+
+            assert_eq!(String::from_utf8(component).unwrap(), value, "{name}");
+        }
+
+        // absolute URI with the default port
+        let request = Request::get("http://WWW.Example.com:80").body(()).unwrap();
+
+        for (name, value) in [
+            ("@target-uri", "http://www.example.com/"),
+            ("@authority", "www.example.com"),
+            ("@scheme", "http"),
+            ("@query", "?"),
+        ] {
+            let component = component_value(&request, name).unwrap();
+
+            assert_eq!(String::from_utf8(component).unwrap(), value, "{name}");
+        }
+    }
+
+    /// Header fields are trimmed and combined
+    #[test]
+    fn field_components() {
+        let request = Request::get("/")
+            .header("Cache-Control", "   max-age=60  ")
+            .header("Cache-Control", "must-revalidate")
+            .body(())
+            .unwrap();
+
+        let component = component_value(&request, "cache-control").unwrap();
+        assert_eq!(component, b"max-age=60, must-revalidate");
+
+        let err = component_value(&request, "content-digest").unwrap_err();
+        assert!(matches!(err, Error::MissingComponent(name) if name == "content-digest"));
+
+        let err = component_value(&request, "@status").unwrap_err();
+        assert!(matches!(err, Error::MissingComponent(name) if name == "@status"));
+    }
+
+    /// Signature base with repeated components
+    #[test]
+    fn repeated_components() {
+        let request = Request::get("/").body(()).unwrap();
+
+        for components in [["@method", "@method"], ["@method", "@signature-params"]] {
+            let params = SignatureParams {
+                components: components.map(str::to_string).to_vec(),
+                params: vec![("created".to_string(), Item::Integer(1))],
+            };
+
+            let err = signature_base(&request, &params).unwrap_err();
+            assert!(matches!(err, Error::InvalidSignature));
+        }
+    }
+}
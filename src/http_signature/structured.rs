@@ -0,0 +1,406 @@
+//! Minimal parser and serializer of the Structured Field Values (RFC 8941) used by
+//! the `Signature-Input` and `Signature` headers. Decimals are not supported.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{Error, Result};
+
+/// Bare item of a structured field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Item {
+    Integer(i64),
+    String(String),
+    Token(String),
+    ByteSequence(Vec<u8>),
+    Boolean(bool),
+}
+
+/// Ordered parameters of an item or an inner list
+pub(super) type Parameters = Vec<(String, Item)>;
+
+/// Member of a dictionary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Member {
+    Item(Item, Parameters),
+    InnerList(Vec<(Item, Parameters)>, Parameters),
+}
+
+impl Item {
+    pub(super) fn serialize(&self, out: &mut String) {
+        match self {
+            Self::Integer(integer) => out.push_str(&integer.to_string()),
+            Self::String(string) => {
+                out.push('"');
+                for c in string.chars() {
+                    if c == '"' || c == '\\' {
+                        out.push('\\');
+                    }
+                    out.push(c);
+                }
+                out.push('"');
+            }
+            Self::Token(token) => out.push_str(token),
+            Self::ByteSequence(bytes) => {
+                out.push(':');
+                out.push_str(&STANDARD.encode(bytes));
+                out.push(':');
+            }
+            Self::Boolean(boolean) => out.push_str(if *boolean { "?1" } else { "?0" }),
+        }
+    }
+}
+
+pub(super) fn serialize_parameters(params: &Parameters, out: &mut String) {
+    for (key, value) in params {
+        out.push(';');
+        out.push_str(key);
+
+        if value != &Item::Boolean(true) {
+            out.push('=');
+            value.serialize(out);
+        }
+    }
+}
+
+/// Check that the string is a valid dictionary key (e.g. a signature label).
+pub(super) fn is_key(key: &str) -> bool {
+    let mut parser = Parser::new(key);
+
+    parser.parse_key().is_ok() && parser.is_end()
+}
+
+/// Parse a dictionary, the members are returned in their order.
+pub(super) fn parse_dictionary(input: &str) -> Result<Vec<(String, Member)>> {
+    let mut parser = Parser::new(input);
+    let mut members: Vec<(String, Member)> = Vec::new();
+
+    parser.skip_sp();
+
+    while !parser.is_end() {
+        let key = parser.parse_key()?;
+
+        let member = if parser.eat(b'=') {
+            parser.parse_member()?
+        } else {
+            Member::Item(Item::Boolean(true), parser.parse_parameters()?)
+        };
+
+        // the last value of a duplicated key wins
+        members.retain(|(k, _)| k != &key);
+        members.push((key, member));
+
+        parser.skip_ows();
+
+        if parser.is_end() {
+            break;
+        }
+
+        if !parser.eat(b',') {
+            return Err(Error::InvalidSignature);
+        }
+
+        parser.skip_ows();
+
+        // trailing comma
+        if parser.is_end() {
+            return Err(Error::InvalidSignature);
+        }
+    }
+
+    Ok(members)
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input: input.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn is_end(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            return true;
+        }
+
+        false
+    }
+
+    fn skip_sp(&mut self) {
+        while self.eat(b' ') {}
+    }
+
+    fn skip_ows(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Input between `start` and the current position, only ASCII is consumed by the callers.
+    fn slice(&self, start: usize) -> String {
+        String::from_utf8_lossy(&self.input[start..self.pos]).into_owned()
+    }
+
+    fn parse_member(&mut self) -> Result<Member> {
+        if !self.eat(b'(') {
+            let item = self.parse_bare_item()?;
+            return Ok(Member::Item(item, self.parse_parameters()?));
+        }
+
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_sp();
+
+            if self.eat(b')') {
+                break;
+            }
+
+            let item = self.parse_bare_item()?;
+            items.push((item, self.parse_parameters()?));
+
+            if !matches!(self.peek(), Some(b' ' | b')')) {
+                return Err(Error::InvalidSignature);
+            }
+        }
+
+        Ok(Member::InnerList(items, self.parse_parameters()?))
+    }
+
+    fn parse_parameters(&mut self) -> Result<Parameters> {
+        let mut params: Parameters = Vec::new();
+
+        while self.eat(b';') {
+            self.skip_sp();
+
+            let key = self.parse_key()?;
+            let value = if self.eat(b'=') {
+                self.parse_bare_item()?
+            } else {
+                Item::Boolean(true)
+            };
+
+            // a duplicated key overwrites the value but keeps its position
+            match params.iter_mut().find(|(k, _)| k == &key) {
+                Some((_, v)) => *v = value,
+                None => params.push((key, value)),
+            }
+        }
+
+        Ok(params)
+    }
+
+    fn parse_key(&mut self) -> Result<String> {
+        let start = self.pos;
+
+        if !matches!(self.peek(), Some(b'a'..=b'z' | b'*')) {
+            return Err(Error::InvalidSignature);
+        }
+
+        while matches!(
+            self.peek(),
+            Some(b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'.' | b'*')
+        ) {
+            self.pos += 1;
+        }
+
+        Ok(self.slice(start))
+    }
+
+    fn parse_bare_item(&mut self) -> Result<Item> {
+        match self.peek() {
+            Some(b'-' | b'0'..=b'9') => self.parse_integer(),
+            Some(b'"') => self.parse_string(),
+            Some(b':') => self.parse_byte_sequence(),
+            Some(b'?') => self.parse_boolean(),
+            Some(b'A'..=b'Z' | b'a'..=b'z' | b'*') => self.parse_token(),
+            _ => Err(Error::InvalidSignature),
+        }
+    }
+
+    fn parse_integer(&mut self) -> Result<Item> {
+        let start = self.pos;
+        self.eat(b'-');
+
+        let digits = self.pos;
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.pos += 1;
+        }
+
+        // at most 15 digits, decimals are not supported
+        let len = self.pos - digits;
+        if len == 0 || len > 15 || self.peek() == Some(b'.') {
+            return Err(Error::InvalidSignature);
+        }
+
+        self.slice(start)
+            .parse()
+            .map(Item::Integer)
+            .map_err(|_| Error::InvalidSignature)
+    }
+
+    fn parse_string(&mut self) -> Result<Item> {
+        // opening quote
+        self.pos += 1;
+
+        let mut string = String::new();
+
+        loop {
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(Item::String(string));
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+
+                    match self.peek() {
+                        Some(c @ (b'"' | b'\\')) => {
+                            string.push(c as char);
+                            self.pos += 1;
+                        }
+                        _ => return Err(Error::InvalidSignature),
+                    }
+                }
+                Some(c @ 0x20..=0x7e) => {
+                    string.push(c as char);
+                    self.pos += 1;
+                }
+                _ => return Err(Error::InvalidSignature),
+            }
+        }
+    }
+
+    fn parse_token(&mut self) -> Result<Item> {
+        let start = self.pos;
+        self.pos += 1;
+
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~:/".contains(&c))
+        {
+            self.pos += 1;
+        }
+
+        Ok(Item::Token(self.slice(start)))
+    }
+
+    fn parse_byte_sequence(&mut self) -> Result<Item> {
+        // opening colon
+        self.pos += 1;
+
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || b"+/=".contains(&c)) {
+            self.pos += 1;
+        }
+        let end = self.pos;
+
+        if !self.eat(b':') {
+            return Err(Error::InvalidSignature);
+        }
+
+        STANDARD
+            .decode(&self.input[start..end])
+            .map(Item::ByteSequence)
+            .map_err(|_| Error::InvalidSignature)
+    }
+
+    fn parse_boolean(&mut self) -> Result<Item> {
+        // question mark
+        self.pos += 1;
+
+        let boolean = match self.peek() {
+            Some(b'1') => true,
+            Some(b'0') => false,
+            _ => return Err(Error::InvalidSignature),
+        };
+        self.pos += 1;
+
+        Ok(Item::Boolean(boolean))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_key, parse_dictionary, serialize_parameters, Error, Item, Member};
+
+    /// Parse the `Signature-Input` and `Signature` headers
+    #[test]
+    fn parse_signature_headers() {
+        let members = parse_dictionary(
+            r#"sig1=("@method" "@path";req);created=1618884473;keyid="a \"key\"";fast, sig2=:AAEC:"#,
+        )
+        .unwrap();
+
+        assert_eq!(members.len(), 2);
+
+        let (label, member) = &members[0];
+        assert_eq!(label, "sig1");
+
+        let Member::InnerList(items, params) = member else {
+            panic!("expected inner list");
+        };
+
+        assert_eq!(items[0], (Item::String("@method".to_string()), vec![]));
+        assert_eq!(
+            items[1],
+            (
+                Item::String("@path".to_string()),
+                vec![("req".to_string(), Item::Boolean(true))]
+            )
+        );
+
+        // serialize the parameters back
+        let mut out = String::new();
+        serialize_parameters(params, &mut out);
+        assert_eq!(out, r#";created=1618884473;keyid="a \"key\"";fast"#);
+
+        let (label, member) = &members[1];
+        assert_eq!(label, "sig2");
+        assert_eq!(
+            member,
+            &Member::Item(Item::ByteSequence(vec![0, 1, 2]), vec![])
+        );
+    }
+
+    /// Parse malformed dictionaries
+    #[test]
+    fn parse_invalid() {
+        for input in [
+            "sig1=(\"@method\"",
+            "sig1=:AAEC",
+            "sig1=1.5",
+            "Sig1=1",
+            "sig1=1,",
+            "sig1=1 sig2=2",
+            "sig1=\"\u{7f}\"",
+            "sig1=?2",
+        ] {
+            let err = parse_dictionary(input).unwrap_err();
+
+            assert!(matches!(err, Error::InvalidSignature), "{input}");
+        }
+    }
+
+    /// Validate dictionary keys
+    #[test]
+    fn keys() {
+        assert!(is_key("sig1"));
+        assert!(is_key("sig-b21"));
+        assert!(!is_key("Sig1"));
+        assert!(!is_key("sig 1"));
+        assert!(!is_key(""));
+    }
+}
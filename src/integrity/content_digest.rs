@@ -0,0 +1,233 @@
+//! `Content-Digest` and `Repr-Digest` headers (RFC 9530).
+//!
+//! Both headers share the same format, `Content-Digest` covers the content of the message
+//! and `Repr-Digest` the selected representation (e.g. the whole file of a range request).
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use super::{matches_any, strongest};
+use crate::{
+    sha::{Algorithm, CryptographicHash, Digest},
+    Error, Result,
+};
+
+/// Key of the algorithm in the header, only the active algorithms of the RFC are allowed.
+fn key(algo: Algorithm) -> Result<&'static str> {
+    match algo {
+        Algorithm::SHA256 => Ok("sha-256"),
+        Algorithm::SHA512 => Ok("sha-512"),
+        _ => Err(Error::InvalidAlgorithm),
+    }
+}
+
+/// Compute a `Content-Digest` (or `Repr-Digest`) header value of the body with each
+/// of the algorithms.
+///
+/// Only `SHA256` and `SHA512` are allowed, other algorithms return [Error::InvalidAlgorithm].
+///
+/// ```
+/// use crypto_utils::{integrity::content_digest, sha::Algorithm};
+///
+/// let header = content_digest(&[Algorithm::SHA256, Algorithm::SHA512], br#"{"hello": "world"}"#).unwrap();
+///
+/// assert!(header.starts_with("sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:, sha-512=:"));
+/// ```
+pub fn content_digest(algorithms: &[Algorithm], body: impl AsRef<[u8]>) -> Result<String> {
+    let digests = algorithms
+        .iter()
+        .map(|&algo| {
+            // check the algorithm before computing the hash
+            key(algo)?;
+
+            let mut hasher = CryptographicHash::new(algo);
+            hasher.update(body.as_ref());
+
+            Ok(hasher.digest())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    content_digest_from_digests(&digests)
+}
+
+/// Format already computed digests as a `Content-Digest` (or `Repr-Digest`) header value.
+///
+/// ```
+/// use crypto_utils::{
+///     integrity::content_digest_from_digests,
+///     sha::{Algorithm, CryptographicHash},
+/// };
+///
+/// let mut hasher = CryptographicHash::new(Algorithm::SHA256);
+/// hasher.update(br#"{"hello": "world"}"#);
+///
+/// let header = content_digest_from_digests(&[hasher.digest()]).unwrap();
+///
+/// assert_eq!(header, "sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:");
+/// ```
+pub fn content_digest_from_digests(digests: &[Digest]) -> Result<String> {
+    let members = digests
+        .iter()
+        .map(|digest| {
+            Ok(format!(
+                "{}=:{}:",
+                key(digest.algorithm())?,
+                STANDARD.encode(digest)
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(members.join(", "))
+}
+
+/// Parse a `Content-Digest` (or `Repr-Digest`) header value and return the digests of
+/// the strongest algorithm.
+///
+/// Members with unknown algorithms (e.g. `md5`) are ignored, [Error::InvalidDigest] is
+/// returned if the header is malformed.
+///
+/// ```
+/// use crypto_utils::{integrity::parse_content_digest, sha::Algorithm};
+///
+/// let digests = parse_content_digest("md5=:AAAA:, sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:").unwrap();
+///
+/// assert_eq!(digests.len(), 1);
+/// assert_eq!(digests[0].algorithm(), Algorithm::SHA256);
+/// ```
+pub fn parse_content_digest(header: &str) -> Result<Vec<Digest>> {
+    let mut digests = Vec::new();
+
+    for member in header.split(',') {
+        let member = member.trim_matches([' ', '\t']);
+
+        let (key, value) = member.split_once('=').ok_or(Error::InvalidDigest)?;
+
+        // the value is a byte sequence, optionally followed by parameters
+        let value = value
+            .strip_prefix(':')
+            .and_then(|value| value.split_once(':'))
+            .filter(|(_, params)| params.is_empty() || params.starts_with(';'))
+            .map(|(value, _)| value)
+            .ok_or(Error::InvalidDigest)?;
+
+        let algo = match key {
+            "sha-256" => Algorithm::SHA256,
+            "sha-512" => Algorithm::SHA512,
+            key if is_key(key) => continue,
+            _ => return Err(Error::InvalidDigest),
+        };
+
+        let bytes = STANDARD.decode(value).map_err(|_| Error::InvalidDigest)?;
+
+        if bytes.len() != algo.output_len() {
+            return Err(Error::InvalidDigest);
+        }
+
+        digests.push(Digest::new(algo, bytes));
+    }
+
+    Ok(strongest(digests))
+}
+
+/// Verify that the body matches the `Content-Digest` (or `Repr-Digest`) header value.
+///
+/// Only the strongest algorithm of the header is checked (`sha-512` over `sha-256`).
+/// [Error::InvalidDigest] is returned if the header is malformed or has no supported
+/// algorithm and [Error::DigestMismatch] if the body doesn't match.
+///
+/// ```
+/// use crypto_utils::integrity::verify_content_digest;
+///
+/// let header = "sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:";
+///
+/// verify_content_digest(header, br#"{"hello": "world"}"#).unwrap();
+/// assert!(verify_content_digest(header, br#"{"hello": "there"}"#).is_err());
+/// ```
+pub fn verify_content_digest(header: &str, body: impl AsRef<[u8]>) -> Result<()> {
+    let digests = parse_content_digest(header)?;
+
+    if digests.is_empty() {
+        return Err(Error::InvalidDigest);
+    }
+
+    if !matches_any(&digests, body.as_ref()) {
+        return Err(Error::DigestMismatch);
+    }
+
+    Ok(())
+}
+
+/// Check that the string is a valid dictionary key of a structured field.
+fn is_key(key: &str) -> bool {
+    key.starts_with(|c: char| c.is_ascii_lowercase() || c == '*')
+        && key.chars().all(|c| {
+            c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.' | '*')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{content_digest, parse_content_digest, verify_content_digest, Algorithm, Error};
+
+    const BODY: &[u8] = br#"{"hello": "world"}"#;
+
+    const SHA256: &str = "sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:";
+    const SHA512: &str = "sha-512=:WZDPaVn/7XgHaAy8pmojAkGWoRx2UFChF41A2svX+TaPm+AbwAgBWnrIiYllu7BNNyealdVLvRwEmTHWXvJwew==:";
+
+    /// Compute the headers of the RFC 9530 examples
+    #[test]
+    fn rfc9530() {
+        assert_eq!(content_digest(&[Algorithm::SHA256], BODY).unwrap(), SHA256);
+        assert_eq!(content_digest(&[Algorithm::SHA512], BODY).unwrap(), SHA512);
+
+        let header = content_digest(&[Algorithm::SHA256, Algorithm::SHA512], BODY).unwrap();
+        assert_eq!(header, format!("{SHA256}, {SHA512}"));
+
+        verify_content_digest(&header, BODY).unwrap();
+
+        // sha1 and sha384 aren't allowed
+        for algo in [Algorithm::SHA1, Algorithm::SHA384] {
+            let err = content_digest(&[algo], BODY).unwrap_err();
+            assert!(matches!(err, Error::InvalidAlgorithm));
+        }
+    }
+
+    /// Only the strongest algorithm is checked
+    #[test]
+    fn strongest_wins() {
+        // valid sha-256 but invalid sha-512, the sha-512 digest wins
+        let invalid_sha512 = format!("sha-512=:{}:", "A".repeat(86) + "==");
+
+        let err = verify_content_digest(&format!("{SHA256}, {invalid_sha512}"), BODY).unwrap_err();
+        assert!(matches!(err, Error::DigestMismatch));
+
+        // the body was modified
+        let err = verify_content_digest(SHA256, br#"{"hello": "there"}"#).unwrap_err();
+        assert!(matches!(err, Error::DigestMismatch));
+
+        // unknown algorithms and parameters are ignored
+        let digests =
+            parse_content_digest(&format!("md5=:AAAA:, {SHA256};foo=1,\t{SHA512}")).unwrap();
+        assert_eq!(digests.len(), 1);
+        assert_eq!(digests[0].algorithm(), Algorithm::SHA512);
+    }
+
+    /// Reject malformed headers
+    #[test]
+    fn parse_invalid() {
+        for header in [
+            "",
+            "md5=:AAAA:",
+            "sha-256",
+            "sha-256=X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=",
+            "sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=",
+            "sha-256=:AAAA:",
+            "sha-256=:invalid!:",
+            "SHA-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:",
+            "sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:,",
+        ] {
+            let err = verify_content_digest(header, BODY).unwrap_err();
+
+            assert!(matches!(err, Error::InvalidDigest), "{header}");
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Module for checking the integrity of resources and HTTP messages.
+//!
+//! - [Subresource Integrity](https://www.w3.org/TR/SRI/) strings used by the `integrity`
+//!   attribute of `<script>` and `<link>` elements (`sha384-<base64>`).
+//! - `Content-Digest` and `Repr-Digest` headers of HTTP messages
+//!   ([RFC 9530](https://www.rfc-editor.org/rfc/rfc9530)).
+//!
+//! When several hashes are given, only the ones computed with the strongest algorithm
+//! are checked, so adding a weaker hash can't weaken the check.
+//!
+//! ## Subresource Integrity
+//!
+//! ```
+//! use crypto_utils::{integrity, sha::Algorithm};
+//!
+//! let script = b"alert('Hello, world.');";
+//!
+//! let integrity = integrity::sri(&[Algorithm::SHA384], script).unwrap();
+//! assert_eq!(integrity, "sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO");
+//!
+//! integrity::verify_sri(&integrity, script).unwrap();
+//! ```
+//!
+//! ## Content-Digest
+//!
+//! ```
+//! use crypto_utils::{integrity, sha::Algorithm};
+//!
+//! let body = br#"{"hello": "world"}"#;
+//!
+//! let header = integrity::content_digest(&[Algorithm::SHA256], body).unwrap();
+//! assert_eq!(header, "sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:");
+//!
+//! integrity::verify_content_digest(&header, body).unwrap();
+//! ```
+
+mod content_digest;
+mod sri;
+
+pub use content_digest::*;
+pub use sri::*;
+
+use crate::sha::{CryptographicHash, Digest};
+
+/// Keep only the digests computed with the strongest algorithm.
+fn strongest(mut digests: Vec<Digest>) -> Vec<Digest> {
+    if let Some(algo) = digests.iter().map(Digest::algorithm).max() {
+        digests.retain(|digest| digest.algorithm() == algo);
+    }
+
+    digests
+}
+
+/// Check that the data matches one of the digests, all of them must use the same algorithm.
+fn matches_any(digests: &[Digest], data: &[u8]) -> bool {
+    let Some(first) = digests.first() else {
+        return false;
+    };
+
+    let computed = CryptographicHash::hash(first.algorithm(), data);
+
+    digests
+        .iter()
+        .any(|digest| digest.as_bytes() == computed.as_slice())
+}
@@ -0,0 +1,217 @@
+//! Subresource Integrity strings.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use super::{matches_any, strongest};
+use crate::{
+    sha::{Algorithm, CryptographicHash, Digest},
+    Error, Result,
+};
+
+/// Prefix of the algorithm in a SRI string, SHA-1 isn't allowed.
+fn prefix(algo: Algorithm) -> Result<&'static str> {
+    match algo {
+        Algorithm::SHA256 => Ok("sha256"),
+        Algorithm::SHA384 => Ok("sha384"),
+        Algorithm::SHA512 => Ok("sha512"),
+        Algorithm::SHA1 => Err(Error::InvalidAlgorithm),
+    }
+}
+
+/// Compute a SRI string of the data with each of the algorithms (separated by spaces).
+///
+/// Only `SHA256`, `SHA384` and `SHA512` are allowed, other algorithms return
+/// [Error::InvalidAlgorithm].
+///
+/// ```
+/// use crypto_utils::{integrity::sri, sha::Algorithm};
+///
+/// let integrity = sri(&[Algorithm::SHA256, Algorithm::SHA512], b"alert('Hello, world.');").unwrap();
+///
+/// assert!(integrity.starts_with("sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng= sha512-"));
+/// ```
+pub fn sri(algorithms: &[Algorithm], data: impl AsRef<[u8]>) -> Result<String> {
+    let digests = algorithms
+        .iter()
+        .map(|&algo| {
+            // check the algorithm before computing the hash
+            prefix(algo)?;
+
+            let mut hasher = CryptographicHash::new(algo);
+            hasher.update(data.as_ref());
+
+            Ok(hasher.digest())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    sri_from_digests(&digests)
+}
+
+/// Format already computed digests as a SRI string.
+///
+/// ```
+/// use crypto_utils::{
+///     integrity::sri_from_digests,
+///     sha::{Algorithm, CryptographicHash},
+/// };
+///
+/// let mut hasher = CryptographicHash::new(Algorithm::SHA384);
+/// hasher.update(b"alert('Hello, world.');");
+///
+/// let integrity = sri_from_digests(&[hasher.digest()]).unwrap();
+///
+/// assert_eq!(integrity, "sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO");
+/// ```
+pub fn sri_from_digests(digests: &[Digest]) -> Result<String> {
+    let hashes = digests
+        .iter()
+        .map(|digest| {
+            Ok(format!(
+                "{}-{}",
+                prefix(digest.algorithm())?,
+                STANDARD.encode(digest)
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(hashes.join(" "))
+}
+
+/// Parse a SRI string and return the digests of the strongest algorithm.
+///
+/// Hashes with unknown algorithms or invalid values are ignored, as well as the options
+/// after `?`. An empty list is returned if no hash is valid.
+///
+/// ```
+/// use crypto_utils::{integrity::parse_sri, sha::Algorithm};
+///
+/// let digests = parse_sri("sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng= md5-xxx sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO?foo");
+///
+/// assert_eq!(digests.len(), 1);
+/// assert_eq!(digests[0].algorithm(), Algorithm::SHA384);
+/// ```
+pub fn parse_sri(integrity: &str) -> Vec<Digest> {
+    let digests = integrity
+        .split_ascii_whitespace()
+        .filter_map(|token| {
+            let (algo, value) = token.split_once('-')?;
+
+            let algo = match algo.to_ascii_lowercase().as_str() {
+                "sha256" => Algorithm::SHA256,
+                "sha384" => Algorithm::SHA384,
+                "sha512" => Algorithm::SHA512,
+                _ => return None,
+            };
+
+            // strip the options
+            let value = value.split_once('?').map_or(value, |(value, _)| value);
+
+            let bytes = STANDARD.decode(value).ok()?;
+
+            (bytes.len() == algo.output_len()).then(|| Digest::new(algo, bytes))
+        })
+        .collect();
+
+    strongest(digests)
+}
+
+/// Verify that the data matches the SRI string.
+///
+/// The data must match one of the hashes computed with the strongest algorithm.
+/// [Error::InvalidDigest] is returned if the string doesn't contain any valid hash
+/// and [Error::DigestMismatch] if the data doesn't match.
+///
+/// ```
+/// use crypto_utils::integrity::verify_sri;
+///
+/// let integrity = "sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO";
+///
+/// verify_sri(integrity, b"alert('Hello, world.');").unwrap();
+/// assert!(verify_sri(integrity, b"alert('Hi');").is_err());
+/// ```
+pub fn verify_sri(integrity: &str, data: impl AsRef<[u8]>) -> Result<()> {
+    let digests = parse_sri(integrity);
+
+    if digests.is_empty() {
+        return Err(Error::InvalidDigest);
+    }
+
+    if !matches_any(&digests, data.as_ref()) {
+        return Err(Error::DigestMismatch);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_sri, sri, verify_sri, Algorithm, Error};
+
+    const SCRIPT: &[u8] = b"alert('Hello, world.');";
+
+    const SHA256: &str = "sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng=";
+    const SHA384: &str = "sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO";
+    const SHA512: &str = "sha512-Q2bFTOhEALkN8hOms2FKTDLy7eugP2zFZ1T8LCvX42Fp3WoNr3bjZSAHeOsHrbV1Fu9/A0EzCinRE7Af1ofPrw==";
+
+    /// Generate SRI strings with one and multiple algorithms
+    #[test]
+    fn generate() {
+        assert_eq!(sri(&[Algorithm::SHA384], SCRIPT).unwrap(), SHA384);
+
+        let integrity = sri(
+            &[Algorithm::SHA256, Algorithm::SHA384, Algorithm::SHA512],
+            SCRIPT,
+        )
+        .unwrap();
+        assert_eq!(integrity, format!("{SHA256} {SHA384} {SHA512}"));
+
+        // sha1 isn't allowed
+        let err = sri(&[Algorithm::SHA1], SCRIPT).unwrap_err();
+        assert!(matches!(err, Error::InvalidAlgorithm));
+    }
+
+    /// Only the strongest algorithm is checked
+    #[test]
+    fn strongest_wins() {
+        // valid sha256 but invalid sha512, the sha512 hash wins
+        let invalid_sha512 = format!("sha512-{}", "A".repeat(86) + "==");
+        let integrity = format!("{SHA256} {invalid_sha512}");
+
+        let err = verify_sri(&integrity, SCRIPT).unwrap_err();
+        assert!(matches!(err, Error::DigestMismatch));
+
+        // one of the strongest hashes matches
+        let integrity = format!("{SHA256} {invalid_sha512} {SHA512}");
+        verify_sri(&integrity, SCRIPT).unwrap();
+
+        // the algorithms are ordered by their strength, not their position
+        let digests = parse_sri(&format!("{SHA512} {SHA256} {SHA384}"));
+        assert_eq!(digests.len(), 1);
+        assert_eq!(digests[0].algorithm(), Algorithm::SHA512);
+    }
+
+    /// Ignore unknown algorithms, invalid values and options
+    #[test]
+    fn parse_invalid() {
+        // hashes which aren't valid are ignored
+        for integrity in [
+            "",
+            "md5-xxx",
+            "sha1-q1MKE+RZFJgrefm34/uplM/R8/si9xzqGvvwK0YMbR0=",
+            "sha256-invalid!",
+            "sha256-AAAA",
+            "sha256",
+        ] {
+            assert!(parse_sri(integrity).is_empty(), "{integrity}");
+
+            let err = verify_sri(integrity, SCRIPT).unwrap_err();
+            assert!(matches!(err, Error::InvalidDigest), "{integrity}");
+        }
+
+        // options are ignored
+        verify_sri(&format!("  {SHA384}?ct=application/javascript  "), SCRIPT).unwrap();
+
+        // an invalid hash doesn't affect the valid ones
+        verify_sri(&format!("sha512-invalid {SHA256}"), SCRIPT).unwrap();
+    }
+}
@@ -0,0 +1,490 @@
+use aes_gcm::{
+    aead::{Aead, OsRng, Payload},
+    AeadCore, Aes256Gcm, KeyInit, Nonce,
+};
+use aes_kw::KekAes256;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::{Claims, Token, TokenData};
+use crate::{Error, Result};
+
+/// Length of the A256GCM key in bytes
+const KEY_LEN: usize = 32;
+/// Length of the A256GCM initialization vector in bytes
+const IV_LEN: usize = 12;
+/// Length of the A256GCM authentication tag in bytes
+const TAG_LEN: usize = 16;
+/// Leeway (in seconds) for the `exp` claim, the same as in [Token::decode]
+const LEEWAY: i64 = 60;
+
+/// JWE key management algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyAlgorithm {
+    /// Direct use of a shared 256-bit key as the content encryption key
+    #[serde(rename = "dir")]
+    Dir,
+    /// Random content encryption key wrapped with a 256-bit key using AES Key Wrap ([RFC 3394](https://www.rfc-editor.org/rfc/rfc3394))
+    A256KW,
+}
+
+/// JWE content encryption algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContentAlgorithm {
+    /// AES-GCM using a 256-bit key
+    A256GCM,
+}
+
+/// JWE Header
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JweHeader {
+    /// Key management algorithm
+    pub alg: KeyAlgorithm,
+    /// Content encryption algorithm
+    pub enc: ContentAlgorithm,
+    /// Type of the token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+    /// Content type, set to `JWT` for nested (signed and then encrypted) tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cty: Option<String>,
+}
+
+impl JweHeader {
+    /// Create a new JWE header using the A256GCM content encryption.
+    pub fn new(alg: KeyAlgorithm) -> Self {
+        Self {
+            alg,
+            enc: ContentAlgorithm::A256GCM,
+            typ: Some("JWT".to_string()),
+            cty: None,
+        }
+    }
+
+    /// Returns `true` if the payload of the token is a signed token.
+    fn is_nested(&self) -> bool {
+        matches!(&self.cty, Some(cty) if cty.eq_ignore_ascii_case("JWT"))
+    }
+}
+
+/// The return type of a successful call to [decode](EncryptedToken::decode).
+#[derive(Debug, Clone)]
+pub struct EncryptedTokenData {
+    /// Token Header
+    pub header: JweHeader,
+    /// Token claims
+    pub claims: Claims,
+}
+
+/// Json Web Encryption token ([RFC 7516](https://www.rfc-editor.org/rfc/rfc7516))
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EncryptedToken {
+    /// Token Header
+    header: JweHeader,
+    /// Token claims
+    pub claims: Claims,
+    /// Encoded token to a String
+    pub encoded: String,
+}
+
+impl EncryptedToken {
+    /// Create a new encrypted token
+    ///
+    /// ```
+    /// use crypto_utils::jsonwebtoken::{Claims, EncryptedToken, KeyAlgorithm};
+    ///
+    /// // 256-bit key
+    /// let key = [0u8; 32];
+    ///
+    /// // token claims
+    /// let claims = Claims::new("user_id_1234", 24);
+    ///
+    /// // create token
+    /// let token = EncryptedToken::new(KeyAlgorithm::Dir, &key, claims).unwrap();
+    /// ```
+    pub fn new(algo: KeyAlgorithm, key: impl AsRef<[u8]>, claims: Claims) -> Result<Self> {
+        let key = key.as_ref();
+
+        // generate token header
+        let header = JweHeader::new(algo);
+
+        // encrypt token
+        let encoded = encrypt(&header, key, &serde_json::to_vec(&claims)?)?;
+
+        Ok(Self {
+            header,
+            claims,
+            encoded,
+        })
+    }
+
+    /// Create a new nested token. The claims are signed with `signing_key` (see [Token::new])
+    /// and the signed token is encrypted with `key`.
+    ///
+    /// ```
+    /// use crypto_utils::jsonwebtoken::{Claims, EncryptedToken, KeyAlgorithm};
+    ///
+    /// // 256-bit key
+    /// let key = [0u8; 32];
+    /// // jwt secret
+    /// let secret = b"secret";
+    ///
+    /// // token claims
+    /// let claims = Claims::new("user_id_1234", 24);
+    ///
+    /// // create token
+    /// let token = EncryptedToken::new_nested(KeyAlgorithm::A256KW, &key, secret, claims).unwrap();
+    /// ```
+    pub fn new_nested(
+        algo: KeyAlgorithm,
+        key: impl AsRef<[u8]>,
+        signing_key: impl AsRef<[u8]>,
+        claims: Claims,
+    ) -> Result<Self> {
+        let key = key.as_ref();
+        let signing_key = signing_key.as_ref();
+
+        // sign the claims
+        let signed = Token::new(signing_key, claims)?;
+
+        // generate token header
+        let header = JweHeader {
+            cty: Some("JWT".to_string()),
+            ..JweHeader::new(algo)
+        };
+
+        // encrypt signed token
+        let encoded = encrypt(&header, key, signed.encoded.as_bytes())?;
+
+        Ok(Self {
+            header,
+            claims: signed.claims,
+            encoded,
+        })
+    }
+
+    /// Decrypt, validate and decode token
+    ///
+    /// ```
+    /// use crypto_utils::jsonwebtoken::{Claims, EncryptedToken, KeyAlgorithm};
+    ///
+    /// // 256-bit key
+    /// let key = [0u8; 32];
+    ///
+    /// // token claims
+    /// let claims = Claims::new("user_id_1234", 24);
+    ///
+    /// // create token
+    /// let token = EncryptedToken::new(KeyAlgorithm::Dir, &key, claims).unwrap();
+    ///
+    /// // decode token
+    /// let decoded = EncryptedToken::decode(KeyAlgorithm::Dir, &key, token.encoded).unwrap();
+    /// ```
+    pub fn decode(
+        algo: KeyAlgorithm,
+        key: impl AsRef<[u8]>,
+        token: String,
+    ) -> Result<EncryptedTokenData> {
+        let key = key.as_ref();
+
+        let (header, plaintext) = decrypt(algo, key, &token)?;
+
+        // nested tokens must be verified with `decode_nested`
+        if header.is_nested() {
+            return Err(Error::InvalidToken);
+        }
+
+        let claims: Claims = serde_json::from_slice(&plaintext)?;
+
+        // validate expiration time
+        if claims.exp < Utc::now().timestamp() - LEEWAY {
+            return Err(Error::Expired);
+        }
+
+        Ok(EncryptedTokenData { header, claims })
+    }
+
+    /// Decrypt a nested token and validate and decode the signed token inside it.
+    ///
+    /// ```
+    /// use crypto_utils::jsonwebtoken::{Claims, EncryptedToken, KeyAlgorithm};
+    ///
+    /// // 256-bit key
+    /// let key = [0u8; 32];
+    /// // jwt secret
+    /// let secret = b"secret";
+    ///
+    /// // token claims
+    /// let claims = Claims::new("user_id_1234", 24);
+    ///
+    /// // create token
+    /// let token = EncryptedToken::new_nested(KeyAlgorithm::A256KW, &key, secret, claims).unwrap();
+    ///
+    /// // decode token
+    /// let decoded = EncryptedToken::decode_nested(KeyAlgorithm::A256KW, &key, secret, token.encoded).unwrap();
+    /// ```
+    pub fn decode_nested(
+        algo: KeyAlgorithm,
+        key: impl AsRef<[u8]>,
+        signing_key: impl AsRef<[u8]>,
+        token: String,
+    ) -> Result<TokenData> {
+        let key = key.as_ref();
+        let signing_key = signing_key.as_ref();
+
+        let (header, plaintext) = decrypt(algo, key, &token)?;
+
+        if !header.is_nested() {
+            return Err(Error::InvalidToken);
+        }
+
+        let signed = String::from_utf8(plaintext).map_err(|_| Error::InvalidToken)?;
+
+        Token::decode(signing_key, signed)
+    }
+}
+
+/// Encrypt the payload and serialize the token using the JWE Compact Serialization.
+fn encrypt(header: &JweHeader, key: &[u8], payload: &[u8]) -> Result<String> {
+    if key.len() != KEY_LEN {
+        return Err(Error::InvalidKeyLength);
+    }
+
+    // content encryption key and its encrypted form
+    let (cek, encrypted_key) = match header.alg {
+        KeyAlgorithm::Dir => (key.to_vec(), Vec::new()),
+        KeyAlgorithm::A256KW => {
+            let cek = Aes256Gcm::generate_key(&mut OsRng).to_vec();
+
+            let kek = KekAes256::try_from(key).map_err(|_| Error::InvalidKeyLength)?;
+            let encrypted_key = kek.wrap_vec(&cek).map_err(|_| Error::InvalidKeyLength)?;
+
+            (cek, encrypted_key)
+        }
+    };
+
+    // the encoded header is the additional authenticated data
+    let header = URL_SAFE_NO_PAD.encode(serde_json::to_vec(header)?);
+
+    let iv = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let cipher = Aes256Gcm::new_from_slice(&cek).map_err(|_| Error::InvalidKeyLength)?;
+    let mut ciphertext = cipher
+        .encrypt(
+            &iv,
+            Payload {
+                msg: payload,
+                aad: header.as_bytes(),
+            },
+        )
+        .map_err(|_| Error::InvalidToken)?;
+
+    // the authentication tag is appended to the ciphertext
+    let tag = ciphertext.split_off(ciphertext.len() - TAG_LEN);
+
+    Ok([
+        header,
+        URL_SAFE_NO_PAD.encode(encrypted_key),
+        URL_SAFE_NO_PAD.encode(iv),
+        URL_SAFE_NO_PAD.encode(ciphertext),
+        URL_SAFE_NO_PAD.encode(tag),
+    ]
+    .join("."))
+}
+
+/// Parse the JWE Compact Serialization and decrypt the payload.
+fn decrypt(algo: KeyAlgorithm, key: &[u8], token: &str) -> Result<(JweHeader, Vec<u8>)> {
+    if key.len() != KEY_LEN {
+        return Err(Error::InvalidKeyLength);
+    }
+
+    let parts: Vec<&str> = token.split('.').collect();
+
+    let [header_b64, encrypted_key, iv, ciphertext, tag] = parts[..] else {
+        return Err(Error::InvalidToken);
+    };
+
+    let header: JweHeader = serde_json::from_slice(&b64_decode(header_b64)?)?;
+
+    // the algorithm is chosen by the caller and not by the token
+    if header.alg != algo {
+        return Err(Error::InvalidAlgorithm);
+    }
+
+    let encrypted_key = b64_decode(encrypted_key)?;
+    let iv = b64_decode(iv)?;
+    let mut ciphertext = b64_decode(ciphertext)?;
+    let tag = b64_decode(tag)?;
+
+    if iv.len() != IV_LEN || tag.len() != TAG_LEN {
+        return Err(Error::InvalidToken);
+    }
+
+    // content encryption key
+    let cek = match header.alg {
+        KeyAlgorithm::Dir => {
+            if !encrypted_key.is_empty() {
+                return Err(Error::InvalidToken);
+            }
+
+            key.to_vec()
+        }
+        KeyAlgorithm::A256KW => {
+            let kek = KekAes256::try_from(key).map_err(|_| Error::InvalidKeyLength)?;
+
+            kek.unwrap_vec(&encrypted_key)
+                .map_err(|_| Error::InvalidSignature)?
+        }
+    };
+
+    ciphertext.extend_from_slice(&tag);
+
+    let cipher = Aes256Gcm::new_from_slice(&cek).map_err(|_| Error::InvalidSignature)?;
+    let plaintext = cipher
+        .decrypt(
+            Nonce::from_slice(&iv),
+            Payload {
+                msg: &ciphertext,
+                aad: header_b64.as_bytes(),
+            },
+        )
+        .map_err(|_| Error::InvalidSignature)?;
+
+    Ok((header, plaintext))
+}
+
+/// Decode a base64url (without padding) token part.
+fn b64_decode(input: &str) -> Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(input)
+        .map_err(|_| Error::InvalidToken)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Claims, EncryptedToken, Engine, Error, KeyAlgorithm, URL_SAFE_NO_PAD};
+
+    const KEY: &[u8] = &[7u8; 32];
+
+    /// Encrypt and decrypt token using a direct key
+    #[test]
+    fn encrypt_dir() {
+        let token =
+            EncryptedToken::new(KeyAlgorithm::Dir, KEY, Claims::new("user_id_1234", 24)).unwrap();
+
+        // no encrypted key for the direct encryption
+        assert_eq!(token.encoded.split('.').nth(1), Some(""));
+
+        let decoded = EncryptedToken::decode(KeyAlgorithm::Dir, KEY, token.encoded).unwrap();
+
+        assert_eq!(decoded.claims.sub, "user_id_1234");
+    }
+
+    /// Encrypt and decrypt token using a wrapped key
+    #[test]
+    fn encrypt_a256kw() {
+        let token = EncryptedToken::new(KeyAlgorithm::A256KW, KEY, Claims::new("user_id_1234", 24))
+            .unwrap();
+
+        // claims are not readable from the token
+        let claims = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&token.claims).unwrap());
+        assert!(!token.encoded.contains(&claims));
+
+        let decoded = EncryptedToken::decode(KeyAlgorithm::A256KW, KEY, token.encoded).unwrap();
+
+        assert_eq!(decoded.claims.sub, "user_id_1234");
+    }
+
+    /// Sign and then encrypt token
+    #[test]
+    fn encrypt_nested() {
+        let secret = b"secret";
+
+        let token = EncryptedToken::new_nested(
+            KeyAlgorithm::A256KW,
+            KEY,
+            secret,
+            Claims::new("user_id_1234", 24),
+        )
+        .unwrap();
+
+        let decoded =
+            EncryptedToken::decode_nested(KeyAlgorithm::A256KW, KEY, secret, token.encoded.clone())
+                .unwrap();
+
+        assert_eq!(decoded.claims.sub, "user_id_1234");
+
+        // unwrap error when decoding token with invalid signing secret
+        let err = EncryptedToken::decode_nested(
+            KeyAlgorithm::A256KW,
+            KEY,
+            b"other secret",
+            token.encoded.clone(),
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, Error::InvalidSignature));
+
+        // nested token can't be decoded as a plain encrypted token
+        let err = EncryptedToken::decode(KeyAlgorithm::A256KW, KEY, token.encoded).unwrap_err();
+
+        assert!(matches!(err, Error::InvalidToken));
+    }
+
+    /// Decode token with invalid key
+    #[test]
+    fn decode_key_invalid() {
+        for algo in [KeyAlgorithm::Dir, KeyAlgorithm::A256KW] {
+            let token = EncryptedToken::new(algo, KEY, Claims::new("user_id_1234", 24)).unwrap();
+
+            // unwrap error when decoding token
+            let err = EncryptedToken::decode(algo, [8u8; 32], token.encoded).unwrap_err();
+
+            assert!(matches!(err, Error::InvalidSignature));
+        }
+    }
+
+    /// Decode token with a modified header
+    #[test]
+    fn decode_header_modified() {
+        let token =
+            EncryptedToken::new(KeyAlgorithm::Dir, KEY, Claims::new("user_id_1234", 24)).unwrap();
+
+        // header without the `typ` field
+        let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"dir","enc":"A256GCM"}"#);
+
+        let (_, rest) = token.encoded.split_once('.').unwrap();
+        let modified = format!("{header}.{rest}");
+
+        // unwrap error when decoding token
+        let err = EncryptedToken::decode(KeyAlgorithm::Dir, KEY, modified).unwrap_err();
+
+        assert!(matches!(err, Error::InvalidSignature));
+    }
+
+    /// Decode token with other key algorithm than expected
+    #[test]
+    fn decode_algorithm_invalid() {
+        let token = EncryptedToken::new(KeyAlgorithm::A256KW, KEY, Claims::new("user_id_1234", 24))
+            .unwrap();
+
+        // unwrap error when decoding token
+        let err = EncryptedToken::decode(KeyAlgorithm::Dir, KEY, token.encoded).unwrap_err();
+
+        assert!(matches!(err, Error::InvalidAlgorithm));
+    }
+
+    /// Decode expired token
+    #[test]
+    fn decode_expired() {
+        // create a token that expired an hour ago
+        let token = EncryptedToken::new(KeyAlgorithm::Dir, KEY, Claims::new("test", -1))
+            .expect("generate token");
+
+        // unwrap error when decoding token
+        let err = EncryptedToken::decode(KeyAlgorithm::Dir, KEY, token.encoded).unwrap_err();
+
+        assert!(matches!(err, Error::Expired));
+    }
+}
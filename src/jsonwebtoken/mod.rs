@@ -0,0 +1,52 @@
+//! Module for creating and decoding json web token.
+//!
+//! ## Signed token (JWS)
+//!
+//! ```
+//! use crypto_utils::jsonwebtoken::{Claims, Token};
+//!
+//! let secret = b"secret";
+//! let user_id = "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx";
+//!
+//! // create claims
+//! let claims = Claims::new(user_id, 24);
+//!
+//! // create token
+//! let token = Token::new(secret, claims).unwrap();
+//!
+//! // decode token
+//! let decoded = Token::decode(secret, token.encoded).unwrap();
+//! ```
+//!
+//! ## Encrypted token (JWE)
+//!
+//! Requires the `jwe` feature. The claims are encrypted with A256GCM, so they can't be read
+//! by anyone without the key.
+//!
+//! ```
+//! # #[cfg(feature = "jwe")]
+//! # {
+//! use crypto_utils::jsonwebtoken::{Claims, EncryptedToken, KeyAlgorithm};
+//!
+//! // 256-bit key encryption key
+//! let key = [0u8; 32];
+//! let user_id = "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx";
+//!
+//! // create claims
+//! let claims = Claims::new(user_id, 24);
+//!
+//! // create token
+//! let token = EncryptedToken::new(KeyAlgorithm::A256KW, &key, claims).unwrap();
+//!
+//! // decode token
+//! let decoded = EncryptedToken::decode(KeyAlgorithm::A256KW, &key, token.encoded).unwrap();
+//! # }
+//! ```
+
+#[cfg(feature = "jwe")]
+mod jwe;
+mod token;
+
+#[cfg(feature = "jwe")]
+pub use jwe::*;
+pub use token::*;
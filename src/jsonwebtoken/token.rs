@@ -1,25 +1,9 @@
-//! Module for creating and decoding json web token.
-//!
-//! ```
-//! use crypto_utils::jsonwebtoken::{Claims, Token};
-//!
-//! let secret = b"secret";
-//! let user_id = "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx";
-//!
-//! // create claims
-//! let claims = Claims::new(user_id, 24);
-//!
-//! // create token
-//! let token = Token::new(secret, claims).unwrap();
-//!
-//! // decode token
-//! let decoded = Token::decode(secret, token.encoded).unwrap();
-//! ```
-
 use chrono::{Duration, Utc};
-use jsonwebtoken::{errors::Error, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 
+use crate::Result;
+
 /// Token Claims
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
@@ -82,7 +66,9 @@ impl Token {
     /// // create token
     /// let token = Token::new(secret, claims).unwrap();
     /// ```
-    pub fn new(key: &[u8], claims: Claims) -> Result<Self, Error> {
+    pub fn new(key: impl AsRef<[u8]>, claims: Claims) -> Result<Self> {
+        let key = key.as_ref();
+
         // generate token header
         let header = Header::new(Algorithm::HS256);
 
@@ -113,18 +99,21 @@ impl Token {
     /// // decode token
     /// let decoded = Token::decode(secret, token.encoded).unwrap();
     /// ```
-    pub fn decode(key: &[u8], token: String) -> Result<TokenData, Error> {
-        jsonwebtoken::decode::<Claims>(
+    pub fn decode(key: impl AsRef<[u8]>, token: String) -> Result<TokenData> {
+        let key = key.as_ref();
+
+        Ok(jsonwebtoken::decode::<Claims>(
             &token,
             &DecodingKey::from_secret(key),
             &Validation::default(),
-        )
+        )?)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{Claims, Token};
+    use crate::Error;
 
     /// Decode token with invalid secret
     #[test]
@@ -140,7 +129,22 @@ mod tests {
         // unwrap error when decoding token
         let err = Token::decode(b"other secret", token.encoded).unwrap_err();
 
-        assert_eq!(err.to_string(), "InvalidSignature");
+        assert!(matches!(err, Error::InvalidSignature));
+    }
+
+    /// Create and decode token using the SecretKey
+    #[cfg(feature = "secret")]
+    #[test]
+    fn secret_key() {
+        let key = crate::secret::SecretKey::generate(32);
+
+        // create token
+        let token = Token::new(&key, Claims::new("user_id_1234", 24)).unwrap();
+
+        // decode token
+        let decoded = Token::decode(&key, token.encoded).unwrap();
+
+        assert_eq!(decoded.claims.sub, "user_id_1234");
     }
 
     /// Decode expired token
@@ -154,6 +158,6 @@ mod tests {
         // unwrap error when decoding token
         let err = Token::decode(key, token.encoded).unwrap_err();
 
-        assert_eq!(err.to_string(), "ExpiredSignature");
+        assert!(matches!(err, Error::Expired));
     }
 }
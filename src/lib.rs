@@ -21,18 +21,89 @@
 //!
 //! ## How to use?
 //!
-//! Check [jsonwebtoken] and [sha] modules.
+//! Check [aead], [audit], [aws_sigv4], [cas], [checksum], [cookie], [git], [hpke], [http_signature], [integrity], [jsonwebtoken], [merkle], [multiformats], [paseto], `parallel`, [random], [secret], [sha], [sign], [signed_url] and [webhook] modules. All of them return the same [enum@Error].
 //!
 //! ## All Feature flags
 //!
 //! | Feature  | Description                                                 | Dependencies                            | Default |
 //! |:---------|:------------------------------------------------------------|:----------------------------------------|:--------|
-//! | `sha`    | Enable support for the Sha1, Sha256 and Sha512 hasher       | `sha` and `sha2`                        | yes     |
+//! | `sha`    | Enable support for the Sha1, Sha256, Sha384 and Sha512 hasher | `sha` and `sha2`                        | yes     |
 //! | `jwt`    | Enable support for the Json Web Token utils                 | `chrono`, `serde` and `jsonwebtoken`    | yes     |
+//! | `jwe`    | Enable support for the encrypted Json Web Token (JWE)       | `jwt`, `aes-gcm`, `aes-kw` and `base64` | yes     |
+//! | `paseto` | Enable support for the PASETO v4 local and public tokens    | `jwt`, `chacha20`, `blake2` and `ed25519-dalek` | yes |
+//! | `aead`   | Enable support for the authenticated (streaming) encryption | `sha`, `aes-gcm` and `chacha20poly1305` | yes     |
+//! | `sign`   | Enable support for the Ed25519 and ECDSA signatures         | `sha`, `ed25519-dalek`, `p256` and `p384` | yes   |
+//! | `hpke`   | Enable support for the X25519 and HPKE public-key encryption | `sha`, `x25519-dalek`, `aes-gcm` and `chacha20poly1305` | yes |
+//! | `random` | Enable support for the random keys, strings and UUIDs       | `sha`, `secret`, `rand` and `uuid`      | yes     |
+//! | `secret` | Enable support for the zeroizing secret keys                | `base64`, `rand`, `subtle` and `zeroize` | yes    |
+//! | `webhook` | Enable support for the signed webhooks (GitHub, Stripe, Slack) | `sha` and `hex`                     | yes     |
+//! | `http-signature` | Enable support for the HTTP message signatures (RFC 9421) | `sign`, `http` and `base64` | yes |
+//! | `aws-sigv4` | Enable support for the AWS Signature Version 4 signing     | `sha`, `secret`, `http`, `hex` and `chrono` | yes     |
+//! | `signed-url` | Enable support for the signed, expiring URLs             | `sha` and `base64`                      | yes     |
+//! | `cookie` | Enable support for the signed and encrypted cookies         | `sha`, `aead`, `serde` and `base64`     | yes     |
+//! | `integrity` | Enable support for the SRI and `Content-Digest` (RFC 9530) helpers | `sha` and `base64`          | yes     |
+//! | `checksum` | Enable support for the `sha256sum` compatible checksum manifests and tree hashing | `sha` and `hex`             | yes     |
+//! | `merkle` | Enable support for the Merkle trees with inclusion and consistency proofs | `sha`          | yes     |
+//! | `audit`  | Enable support for the tamper-evident, hash-chained audit log | `sha`, `sign`, `serde` and `hex` | yes |
+//! | `multiformats` | Enable support for the multihash, multibase and CID v1 encodings | `sha` and `base64` | yes  |
+//! | `cas`    | Enable support for the content-addressable blob store      | `sha` and `hex`                         | yes     |
+//! | `git`    | Enable support for the git blob, tree and commit ids (SHA-1 and SHA-256) | `sha`            | yes     |
+//! | `sha1dc` | Enable support for the Sha1 hasher with collision detection | `sha` and `sha1-checked`               | yes     |
+//! | `resumable` | Enable support for the hashers with an exportable state  | `sha` and `zeroize`                     | yes     |
+//! | `parallel` | Enable support for hashing many files or buffers concurrently | `sha` and `rayon`             | no      |
 
 #![warn(missing_docs)]
 
+mod error;
+#[cfg(any(
+    feature = "audit",
+    feature = "cookie",
+    feature = "http-signature",
+    feature = "webhook"
+))]
+mod time;
+
+pub use error::*;
+
+#[cfg(feature = "aead")]
+pub mod aead;
+#[cfg(feature = "audit")]
+pub mod audit;
+#[cfg(feature = "aws-sigv4")]
+pub mod aws_sigv4;
+#[cfg(feature = "cas")]
+pub mod cas;
+#[cfg(feature = "checksum")]
+pub mod checksum;
+#[cfg(feature = "cookie")]
+pub mod cookie;
+#[cfg(feature = "git")]
+pub mod git;
+#[cfg(feature = "hpke")]
+pub mod hpke;
+#[cfg(feature = "http-signature")]
+pub mod http_signature;
+#[cfg(feature = "integrity")]
+pub mod integrity;
 #[cfg(feature = "jwt")]
 pub mod jsonwebtoken;
+#[cfg(feature = "merkle")]
+pub mod merkle;
+#[cfg(feature = "multiformats")]
+pub mod multiformats;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+#[cfg(feature = "paseto")]
+pub mod paseto;
+#[cfg(feature = "random")]
+pub mod random;
+#[cfg(feature = "secret")]
+pub mod secret;
 #[cfg(feature = "sha")]
 pub mod sha;
+#[cfg(feature = "sign")]
+pub mod sign;
+#[cfg(feature = "signed-url")]
+pub mod signed_url;
+#[cfg(feature = "webhook")]
+pub mod webhook;
@@ -0,0 +1,614 @@
+//! Module for binary Merkle trees with inclusion and consistency proofs.
+//!
+//! The trees follow the Certificate Transparency log ([RFC 6962](https://www.rfc-editor.org/rfc/rfc6962#section-2.1)),
+//! the leaf hashes are prefixed with `0x00` and the node hashes with `0x01`, so a leaf can't be
+//! presented as a node. Any [Algorithm] can be used, SHA-256 gives the same roots as the CT logs.
+//!
+//! ```
+//! use crypto_utils::{merkle::MerkleTree, sha::Algorithm};
+//!
+//! let mut tree = MerkleTree::new(Algorithm::SHA256);
+//! tree.push(b"first entry");
+//! tree.push(b"second entry");
+//!
+//! let old_root = tree.root();
+//! tree.push(b"third entry");
+//! let root = tree.root();
+//!
+//! // the entry is in the tree
+//! let proof = tree.inclusion_proof(1, tree.len()).unwrap();
+//! proof.verify(b"second entry", &root).unwrap();
+//!
+//! // the tree only appended entries since the old root
+//! let proof = tree.consistency_proof(2, tree.len()).unwrap();
+//! proof.verify(&old_root, &root).unwrap();
+//! ```
+
+use crate::{
+    sha::{Algorithm, CryptographicHash, Digest},
+    Error, Result,
+};
+
+/// Prefix of a leaf hash
+const LEAF_PREFIX: u8 = 0x00;
+/// Prefix of a node hash
+const NODE_PREFIX: u8 = 0x01;
+
+/// Compute the hash of the leaf data.
+///
+/// ```
+/// use crypto_utils::{merkle::leaf_hash, sha::Algorithm};
+///
+/// let hash = leaf_hash(Algorithm::SHA256, b"");
+///
+/// assert_eq!(hash.to_string(), "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d");
+/// ```
+pub fn leaf_hash(algo: Algorithm, leaf: impl AsRef<[u8]>) -> Digest {
+    let mut hasher = CryptographicHash::new(algo);
+    hasher.update(&[LEAF_PREFIX]);
+    hasher.update(leaf.as_ref());
+
+    hasher.digest()
+}
+
+/// Compute the hash of a node from the hashes of its children.
+fn node_hash(algo: Algorithm, left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut hasher = CryptographicHash::new(algo);
+    hasher.update(&[NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+
+    hasher.finalize()
+}
+
+/// Largest power of two smaller than `n`, `n` must be greater than one.
+fn split(n: usize) -> usize {
+    let mut k = 1;
+
+    while k << 1 < n {
+        k <<= 1;
+    }
+
+    k
+}
+
+/// Append-only Merkle tree, only the leaf hashes are kept in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleTree {
+    algorithm: Algorithm,
+    leaves: Vec<Vec<u8>>,
+}
+
+impl MerkleTree {
+    /// Create an empty tree using the algorithm.
+    pub fn new(algo: Algorithm) -> Self {
+        Self {
+            algorithm: algo,
+            leaves: Vec::new(),
+        }
+    }
+
+    /// Create a tree from the leaves.
+    ///
+    /// ```
+    /// use crypto_utils::{merkle::MerkleTree, sha::Algorithm};
+    ///
+    /// let tree = MerkleTree::from_leaves(Algorithm::SHA256, ["a", "b", "c"]);
+    ///
+    /// assert_eq!(tree.len(), 3);
+    /// ```
+    pub fn from_leaves<I>(algo: Algorithm, leaves: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut tree = Self::new(algo);
+
+        for leaf in leaves {
+            tree.push(leaf);
+        }
+
+        tree
+    }
+
+    /// Append the leaf and return its index.
+    pub fn push(&mut self, leaf: impl AsRef<[u8]>) -> usize {
+        self.leaves
+            .push(leaf_hash(self.algorithm, leaf).into_bytes());
+
+        self.leaves.len() - 1
+    }
+
+    /// Append the hash of a leaf computed with [leaf_hash] and return its index.
+    ///
+    /// [Error::InvalidAlgorithm] is returned if the hash was computed with another algorithm
+    /// and [Error::InvalidDigest] if it doesn't have the length of the algorithm.
+    pub fn push_hash(&mut self, hash: Digest) -> Result<usize> {
+        if hash.algorithm() != self.algorithm {
+            return Err(Error::InvalidAlgorithm);
+        }
+
+        if hash.as_bytes().len() != self.algorithm.output_len() {
+            return Err(Error::InvalidDigest);
+        }
+
+        self.leaves.push(hash.into_bytes());
+
+        Ok(self.leaves.len() - 1)
+    }
+
+    /// Algorithm used by the tree
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Number of the leaves
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Check that the tree doesn't have any leaf
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Root hash of the tree, the root of an empty tree is the hash of no data.
+    pub fn root(&self) -> Digest {
+        Digest::new(self.algorithm, self.subtree_root(&self.leaves))
+    }
+
+    /// Root hash of the tree when it had `tree_size` leaves.
+    pub fn root_at(&self, tree_size: usize) -> Result<Digest> {
+        let leaves = self.leaves.get(..tree_size).ok_or(Error::InvalidProof)?;
+
+        Ok(Digest::new(self.algorithm, self.subtree_root(leaves)))
+    }
+
+    /// Generate a proof that the leaf at `index` is in the tree of `tree_size` leaves
+    /// (the current tree or an older one).
+    pub fn inclusion_proof(&self, index: usize, tree_size: usize) -> Result<InclusionProof> {
+        if index >= tree_size || tree_size > self.len() {
+            return Err(Error::InvalidProof);
+        }
+
+        let mut path = Vec::new();
+        self.inclusion_path(index, &self.leaves[..tree_size], &mut path);
+
+        Ok(InclusionProof {
+            index,
+            tree_size,
+            path: self.digests(path),
+        })
+    }
+
+    /// Generate a proof that the tree of `new_size` leaves only appended leaves to the tree
+    /// of `old_size` leaves.
+    pub fn consistency_proof(&self, old_size: usize, new_size: usize) -> Result<ConsistencyProof> {
+        if old_size == 0 || old_size > new_size || new_size > self.len() {
+            return Err(Error::InvalidProof);
+        }
+
+        let mut path = Vec::new();
+        self.consistency_path(old_size, &self.leaves[..new_size], true, &mut path);
+
+        Ok(ConsistencyProof {
+            old_size,
+            new_size,
+            path: self.digests(path),
+        })
+    }
+
+    fn digests(&self, path: Vec<Vec<u8>>) -> Vec<Digest> {
+        path.into_iter()
+            .map(|hash| Digest::new(self.algorithm, hash))
+            .collect()
+    }
+
+    /// MTH of the RFC
+    fn subtree_root(&self, leaves: &[Vec<u8>]) -> Vec<u8> {
+        match leaves {
+            [] => CryptographicHash::hash(self.algorithm, b""),
+            [leaf] => leaf.clone(),
+            _ => {
+                let (left, right) = leaves.split_at(split(leaves.len()));
+
+                node_hash(
+                    self.algorithm,
+                    &self.subtree_root(left),
+                    &self.subtree_root(right),
+                )
+            }
+        }
+    }
+
+    /// PATH of the RFC
+    fn inclusion_path(&self, index: usize, leaves: &[Vec<u8>], path: &mut Vec<Vec<u8>>) {
+        if leaves.len() <= 1 {
+            return;
+        }
+
+        let k = split(leaves.len());
+        let (left, right) = leaves.split_at(k);
+
+        if index < k {
+            self.inclusion_path(index, left, path);
+            path.push(self.subtree_root(right));
+        } else {
+            self.inclusion_path(index - k, right, path);
+            path.push(self.subtree_root(left));
+        }
+    }
+
+    /// SUBPROOF of the RFC
+    fn consistency_path(
+        &self,
+        old_size: usize,
+        leaves: &[Vec<u8>],
+        complete: bool,
+        path: &mut Vec<Vec<u8>>,
+    ) {
+        if old_size == leaves.len() {
+            if !complete {
+                path.push(self.subtree_root(leaves));
+            }
+            return;
+        }
+
+        let k = split(leaves.len());
+        let (left, right) = leaves.split_at(k);
+
+        if old_size <= k {
+            self.consistency_path(old_size, left, complete, path);
+            path.push(self.subtree_root(right));
+        } else {
+            self.consistency_path(old_size - k, right, false, path);
+            path.push(self.subtree_root(left));
+        }
+    }
+}
+
+/// Proof that a leaf is in the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionProof {
+    /// Index of the leaf
+    pub index: usize,
+    /// Number of the leaves of the tree
+    pub tree_size: usize,
+    /// Hashes from the leaf to the root
+    pub path: Vec<Digest>,
+}
+
+impl InclusionProof {
+    /// Verify that the leaf data is in the tree with the root.
+    ///
+    /// [Error::InvalidProof] is returned if the proof doesn't match the root.
+    pub fn verify(&self, leaf: impl AsRef<[u8]>, root: &Digest) -> Result<()> {
+        self.verify_hash(&leaf_hash(root.algorithm(), leaf), root)
+    }
+
+    /// Verify that the leaf hash computed with [leaf_hash] is in the tree with the root.
+    pub fn verify_hash(&self, leaf_hash: &Digest, root: &Digest) -> Result<()> {
+        let algo = root.algorithm();
+        check_algorithm(algo, leaf_hash, &self.path)?;
+
+        if self.index >= self.tree_size {
+            return Err(Error::InvalidProof);
+        }
+
+        let mut fn_ = self.index;
+        let mut sn = self.tree_size - 1;
+        let mut hash = leaf_hash.as_bytes().to_vec();
+
+        for sibling in &self.path {
+            if sn == 0 {
+                return Err(Error::InvalidProof);
+            }
+
+            if fn_ & 1 == 1 || fn_ == sn {
+                hash = node_hash(algo, sibling.as_bytes(), &hash);
+
+                while fn_ & 1 == 0 && fn_ != 0 {
+                    fn_ >>= 1;
+                    sn >>= 1;
+                }
+            } else {
+                hash = node_hash(algo, &hash, sibling.as_bytes());
+            }
+
+            fn_ >>= 1;
+            sn >>= 1;
+        }
+
+        if sn != 0 || hash != root.as_bytes() {
+            return Err(Error::InvalidProof);
+        }
+
+        Ok(())
+    }
+}
+
+/// Proof that a tree is an append-only extension of an older tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsistencyProof {
+    /// Number of the leaves of the old tree
+    pub old_size: usize,
+    /// Number of the leaves of the new tree
+    pub new_size: usize,
+    /// Hashes needed to compute both roots
+    pub path: Vec<Digest>,
+}
+
+impl ConsistencyProof {
+    /// Verify that the tree with the new root contains the tree with the old root.
+    ///
+    /// [Error::InvalidProof] is returned if the proof doesn't match the roots.
+    pub fn verify(&self, old_root: &Digest, new_root: &Digest) -> Result<()> {
+        let algo = new_root.algorithm();
+        check_algorithm(algo, old_root, &self.path)?;
+
+        if self.old_size == 0 || self.old_size > self.new_size {
+            return Err(Error::InvalidProof);
+        }
+
+        if self.old_size == self.new_size {
+            if !self.path.is_empty() || old_root != new_root {
+                return Err(Error::InvalidProof);
+            }
+
+            return Ok(());
+        }
+
+        let mut path = self.path.iter().map(Digest::as_bytes).collect::<Vec<_>>();
+
+        // the old tree is a complete subtree, its root is the first hash
+        if self.old_size.is_power_of_two() {
+            path.insert(0, old_root.as_bytes());
+        }
+
+        let Some((first, rest)) = path.split_first() else {
+            return Err(Error::InvalidProof);
+        };
+
+        let mut fn_ = self.old_size - 1;
+        let mut sn = self.new_size - 1;
+
+        while fn_ & 1 == 1 {
+            fn_ >>= 1;
+            sn >>= 1;
+        }
+
+        let mut old_hash = first.to_vec();
+        let mut new_hash = first.to_vec();
+
+        for &hash in rest {
+            if sn == 0 {
+                return Err(Error::InvalidProof);
+            }
+
+            if fn_ & 1 == 1 || fn_ == sn {
+                old_hash = node_hash(algo, hash, &old_hash);
+                new_hash = node_hash(algo, hash, &new_hash);
+
+                while fn_ & 1 == 0 && fn_ != 0 {
+                    fn_ >>= 1;
+                    sn >>= 1;
+                }
+            } else {
+                new_hash = node_hash(algo, &new_hash, hash);
+            }
+
+            fn_ >>= 1;
+            sn >>= 1;
+        }
+
+        if sn != 0 || old_hash != old_root.as_bytes() || new_hash != new_root.as_bytes() {
+            return Err(Error::InvalidProof);
+        }
+
+        Ok(())
+    }
+}
+
+/// Check that all hashes were computed with the algorithm.
+fn check_algorithm(algo: Algorithm, hash: &Digest, path: &[Digest]) -> Result<()> {
+    if hash.algorithm() != algo || path.iter().any(|digest| digest.algorithm() != algo) {
+        return Err(Error::InvalidAlgorithm);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{leaf_hash, Algorithm, Digest, Error, MerkleTree};
+
+    /// Leaves of the Certificate Transparency test vectors
+    fn leaves() -> Vec<Vec<u8>> {
+        vec![
+            vec![],
+            vec![0x00],
+            vec![0x10],
+            vec![0x20, 0x21],
+            vec![0x30, 0x31],
+            vec![0x40, 0x41, 0x42, 0x43],
+            (0x50..0x58).collect(),
+            (0x60..0x70).collect(),
+        ]
+    }
+
+    /// Roots of the trees with 1 to 8 leaves
+    const ROOTS: [&str; 8] = [
+        "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+        "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+        "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+        "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+        "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+        "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+        "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+        "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+    ];
+
+    /// Compute the roots of the test vectors
+    #[test]
+    fn roots() {
+        let tree = MerkleTree::from_leaves(Algorithm::SHA256, leaves());
+
+        for (size, expected) in ROOTS.iter().enumerate() {
+            assert_eq!(tree.root_at(size + 1).unwrap().to_string(), *expected);
+        }
+
+        assert_eq!(tree.root().to_string(), ROOTS[7]);
+
+        // empty tree
+        assert_eq!(
+            MerkleTree::new(Algorithm::SHA256).root().to_string(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    /// Generate the inclusion proofs of the test vectors
+    #[test]
+    fn inclusion_proof_vectors() {
+        let tree = MerkleTree::from_leaves(Algorithm::SHA256, leaves());
+
+        for (index, expected) in [
+            (
+                0,
+                [
+                    "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+                    "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+                    "6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4",
+                ],
+            ),
+            (
+                5,
+                [
+                    "bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+                    "ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0",
+                    "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+                ],
+            ),
+        ] {
+            let proof = tree.inclusion_proof(index, 8).unwrap();
+            let path = proof
+                .path
+                .iter()
+                .map(|hash| hash.to_string())
+                .collect::<Vec<_>>();
+
+            assert_eq!(path, expected);
+        }
+    }
+
+    /// Generate the consistency proofs of the test vectors
+    #[test]
+    fn consistency_proof_vectors() {
+        let tree = MerkleTree::from_leaves(Algorithm::SHA256, leaves());
+
+        let proof = tree.consistency_proof(3, 7).unwrap();
+        let path = proof
+            .path
+            .iter()
+            .map(|hash| hash.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            path,
+            [
+                "0298d122906dcfc10892cb53a73992fc5b9f493ea4c9badb27b791b4127a7fe7",
+                "07506a85fd9dd2f120eb694f86011e5bb4662e5c415a62917033d4a9624487e7",
+                "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+                "837dbb152e9b079010717e84e865da4ebc0fa198a806d59d31bf15accef22d0e",
+            ]
+        );
+
+        let proof = tree.consistency_proof(4, 7).unwrap();
+        assert_eq!(
+            proof.path[0].to_string(),
+            "837dbb152e9b079010717e84e865da4ebc0fa198a806d59d31bf15accef22d0e"
+        );
+    }
+
+    /// Verify the proofs of every leaf and every pair of tree sizes
+    #[test]
+    fn verify_all_proofs() {
+        for algo in [Algorithm::SHA1, Algorithm::SHA384, Algorithm::SHA512] {
+            let leaves = (0..20u8).map(|i| vec![i; i as usize]).collect::<Vec<_>>();
+            let tree = MerkleTree::from_leaves(algo, &leaves);
+
+            for size in 1..=tree.len() {
+                let root = tree.root_at(size).unwrap();
+
+                // inclusion proofs
+                for (index, leaf) in leaves[..size].iter().enumerate() {
+                    let proof = tree.inclusion_proof(index, size).unwrap();
+                    proof.verify(leaf, &root).unwrap();
+
+                    // another leaf isn't included at the index
+                    let err = proof.verify(b"other", &root).unwrap_err();
+                    assert!(matches!(err, Error::InvalidProof));
+                }
+
+                // consistency proofs
+                for old_size in 1..=size {
+                    let old_root = tree.root_at(old_size).unwrap();
+
+                    let proof = tree.consistency_proof(old_size, size).unwrap();
+                    proof.verify(&old_root, &root).unwrap();
+                }
+            }
+        }
+    }
+
+    /// Reject modified proofs and invalid sizes
+    #[test]
+    fn invalid_proofs() {
+        let tree = MerkleTree::from_leaves(Algorithm::SHA256, leaves());
+        let root = tree.root();
+
+        // modified inclusion proof
+        let mut proof = tree.inclusion_proof(2, 8).unwrap();
+        proof.index = 3;
+        assert!(matches!(
+            proof.verify([0x10], &root).unwrap_err(),
+            Error::InvalidProof
+        ));
+
+        let mut proof = tree.inclusion_proof(2, 8).unwrap();
+        proof.path.pop();
+        assert!(matches!(
+            proof.verify([0x10], &root).unwrap_err(),
+            Error::InvalidProof
+        ));
+
+        // the old tree was modified
+        let modified = MerkleTree::from_leaves(Algorithm::SHA256, [b"x"; 5]);
+        let proof = tree.consistency_proof(5, 8).unwrap();
+        assert!(matches!(
+            proof.verify(&modified.root(), &root).unwrap_err(),
+            Error::InvalidProof
+        ));
+
+        // invalid sizes
+        assert!(tree.inclusion_proof(8, 8).is_err());
+        assert!(tree.inclusion_proof(0, 9).is_err());
+        assert!(tree.consistency_proof(0, 8).is_err());
+        assert!(tree.consistency_proof(5, 4).is_err());
+
+        // another algorithm
+        let mut tree = MerkleTree::new(Algorithm::SHA512);
+        let err = tree
+            .push_hash(leaf_hash(Algorithm::SHA256, b""))
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidAlgorithm));
+
+        // truncated hash
+        let mut hash = leaf_hash(Algorithm::SHA512, b"").into_bytes();
+        hash.pop();
+        let err = tree
+            .push_hash(Digest::new(Algorithm::SHA512, hash))
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidDigest));
+        assert!(tree.is_empty());
+    }
+}
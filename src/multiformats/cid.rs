@@ -0,0 +1,245 @@
+//! Content identifiers (CID v1).
+
+use std::{fmt, str::FromStr};
+
+use super::{
+    multibase_decode, multibase_encode, read_multihash, read_varint, write_multihash, write_varint,
+    Base,
+};
+use crate::{
+    sha::{Algorithm, CryptographicHash, Digest},
+    Error, Result,
+};
+
+/// Version of the supported CIDs
+const VERSION: u64 = 1;
+
+/// Codec of the content addressed by a CID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Codec {
+    /// Raw bytes (`0x55`)
+    Raw,
+    /// MerkleDAG protobuf node (`0x70`), e.g. an IPFS UnixFS file or directory
+    DagPb,
+}
+
+impl Codec {
+    /// Code of the codec in the multicodec table
+    pub fn code(&self) -> u64 {
+        match self {
+            Self::Raw => 0x55,
+            Self::DagPb => 0x70,
+        }
+    }
+
+    fn from_code(code: u64) -> Result<Self> {
+        match code {
+            0x55 => Ok(Self::Raw),
+            0x70 => Ok(Self::DagPb),
+            _ => Err(Error::InvalidEncoding),
+        }
+    }
+}
+
+/// Content identifier version 1: codec of the content and multihash of its bytes.
+///
+/// It's displayed as a base32 multibase string, other encodings can be used with
+/// [Cid::encode].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cid {
+    codec: Codec,
+    digest: Digest,
+}
+
+impl Cid {
+    /// Create a CID of the content with the already computed digest.
+    pub fn new(codec: Codec, digest: Digest) -> Self {
+        Self { codec, digest }
+    }
+
+    /// Hash the block and create its CID.
+    ///
+    /// The block must already be encoded with the codec, e.g. a serialized protobuf
+    /// node for [Codec::DagPb].
+    ///
+    /// ```
+    /// use crypto_utils::{
+    ///     multiformats::{Cid, Codec},
+    ///     sha::Algorithm,
+    /// };
+    ///
+    /// let cid = Cid::hash(Codec::Raw, Algorithm::SHA256, b"hello world");
+    ///
+    /// assert_eq!(cid.to_string(), "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e");
+    /// ```
+    pub fn hash(codec: Codec, algo: Algorithm, block: impl AsRef<[u8]>) -> Self {
+        let mut hasher = CryptographicHash::new(algo);
+        hasher.update(block.as_ref());
+
+        Self::new(codec, hasher.digest())
+    }
+
+    /// Codec of the content
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+
+    /// Digest of the content
+    pub fn digest(&self) -> &Digest {
+        &self.digest
+    }
+
+    /// Check that the block matches the CID.
+    ///
+    /// Returns [Error::DigestMismatch] if the block was modified.
+    pub fn verify(&self, block: impl AsRef<[u8]>) -> Result<()> {
+        let computed = CryptographicHash::hash(self.digest.algorithm(), block.as_ref());
+
+        match computed == self.digest.as_bytes() {
+            true => Ok(()),
+            false => Err(Error::DigestMismatch),
+        }
+    }
+
+    /// Binary form of the CID: varint version, varint codec and the multihash.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut output = Vec::new();
+        write_varint(VERSION, &mut output);
+        write_varint(self.codec.code(), &mut output);
+        write_multihash(&self.digest, &mut output);
+
+        output
+    }
+
+    /// Parse the binary form of the CID, only the version 1 is supported.
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self> {
+        if read_varint(&mut bytes)? != VERSION {
+            return Err(Error::InvalidEncoding);
+        }
+
+        let codec = Codec::from_code(read_varint(&mut bytes)?)?;
+        let digest = read_multihash(&mut bytes)?;
+
+        match bytes.is_empty() {
+            true => Ok(Self::new(codec, digest)),
+            false => Err(Error::InvalidEncoding),
+        }
+    }
+
+    /// Encode the CID as a multibase string.
+    ///
+    /// ```
+    /// use crypto_utils::{
+    ///     multiformats::{Base, Cid, Codec},
+    ///     sha::Algorithm,
+    /// };
+    ///
+    /// let cid = Cid::hash(Codec::Raw, Algorithm::SHA256, b"hello world");
+    ///
+    /// assert!(cid.encode(Base::Base58Btc).starts_with("zb2rh"));
+    /// ```
+    pub fn encode(&self, base: Base) -> String {
+        multibase_encode(base, self.to_bytes())
+    }
+}
+
+/// Base32 multibase string of the CID
+impl fmt::Display for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode(Base::Base32))
+    }
+}
+
+/// Parse a multibase string of the CID in any supported base
+impl FromStr for Cid {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (_, bytes) = multibase_decode(s)?;
+
+        Self::from_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Algorithm, Base, Cid, Codec, Error};
+
+    const HELLO_WORLD: &str = "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e";
+
+    /// Compute the CID of a raw block
+    #[test]
+    fn raw() {
+        let cid = Cid::hash(Codec::Raw, Algorithm::SHA256, b"hello world");
+
+        assert_eq!(cid.to_string(), HELLO_WORLD);
+        assert_eq!(HELLO_WORLD.parse::<Cid>().unwrap(), cid);
+
+        cid.verify(b"hello world").unwrap();
+        assert!(matches!(cid.verify(b"hello"), Err(Error::DigestMismatch)));
+    }
+
+    /// Compute the CID of a dag-pb block
+    #[test]
+    fn dag_pb() {
+        // empty UnixFS directory
+        let cid = Cid::hash(Codec::DagPb, Algorithm::SHA256, [0x0a, 0x02, 0x08, 0x01]);
+
+        assert_eq!(
+            cid.to_string(),
+            "bafybeiczsscdsbs7ffqz55asqdf3smv6klcw3gofszvwlyarci47bgf354"
+        );
+    }
+
+    /// Parse the CID in all bases and with all algorithms
+    #[test]
+    fn roundtrip() {
+        for algo in [
+            Algorithm::SHA1,
+            Algorithm::SHA256,
+            Algorithm::SHA384,
+            Algorithm::SHA512,
+        ] {
+            for base in [
+                Base::Base16,
+                Base::Base32,
+                Base::Base58Btc,
+                Base::Base64,
+                Base::Base64Url,
+            ] {
+                let cid = Cid::hash(Codec::Raw, algo, b"content");
+                let encoded = cid.encode(base);
+
+                assert_eq!(encoded.parse::<Cid>().unwrap(), cid);
+            }
+        }
+    }
+
+    /// Reject CID v0, unknown codecs and trailing bytes
+    #[test]
+    fn invalid() {
+        // CID v0
+        let err = "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH"
+            .parse::<Cid>()
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidEncoding));
+
+        let cid = Cid::hash(Codec::Raw, Algorithm::SHA256, b"hello world");
+
+        // unknown codec (dag-cbor)
+        let mut bytes = cid.to_bytes();
+        bytes[1] = 0x71;
+        assert!(matches!(
+            Cid::from_bytes(&bytes),
+            Err(Error::InvalidEncoding)
+        ));
+
+        // trailing bytes
+        let mut bytes = cid.to_bytes();
+        bytes.push(0);
+        assert!(matches!(
+            Cid::from_bytes(&bytes),
+            Err(Error::InvalidEncoding)
+        ));
+    }
+}
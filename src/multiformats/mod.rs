@@ -0,0 +1,124 @@
+//! Module for the self-describing hashes of the [multiformats](https://multiformats.io/) project,
+//! used e.g. by IPFS for content addressing.
+//!
+//! - [multihash](https://multiformats.io/multihash/) - digest prefixed with the code of the
+//!   hash function and its length
+//! - [multibase](https://github.com/multiformats/multibase) - string prefixed with the code of
+//!   its base encoding
+//! - [CID v1](https://github.com/multiformats/cid) - multihash of a block and the codec of its content
+//!
+//! ## Multihash
+//!
+//! ```
+//! use crypto_utils::{
+//!     multiformats::{from_multihash, to_multihash},
+//!     sha::{Algorithm, CryptographicHash},
+//! };
+//!
+//! let mut hasher = CryptographicHash::new(Algorithm::SHA256);
+//! hasher.update(b"hello world");
+//! let digest = hasher.digest();
+//!
+//! let multihash: Vec<u8> = to_multihash(&digest);
+//! assert_eq!(&multihash[..2], [0x12, 0x20]);
+//!
+//! assert_eq!(from_multihash(&multihash).unwrap(), digest);
+//! ```
+//!
+//! ## CID
+//!
+//! ```
+//! use crypto_utils::{
+//!     multiformats::{Cid, Codec},
+//!     sha::Algorithm,
+//! };
+//!
+//! let cid = Cid::hash(Codec::Raw, Algorithm::SHA256, b"hello world");
+//! assert_eq!(cid.to_string(), "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e");
+//!
+//! let parsed: Cid = "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e".parse().unwrap();
+//! assert_eq!(parsed, cid);
+//! ```
+
+mod cid;
+mod multibase;
+mod multihash;
+
+pub use cid::*;
+pub use multibase::*;
+pub use multihash::*;
+
+use crate::{Error, Result};
+
+/// Maximum length of a varint, the multiformats spec limits them to 63 bits.
+const MAX_VARINT_LEN: usize = 9;
+
+/// Append an unsigned LEB128 varint.
+fn write_varint(mut value: u64, output: &mut Vec<u8>) {
+    while value >= 0x80 {
+        output.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+
+    output.push(value as u8);
+}
+
+/// Read an unsigned LEB128 varint, only the minimal encoding is accepted.
+fn read_varint(input: &mut &[u8]) -> Result<u64> {
+    let mut value = 0u64;
+
+    for (i, &byte) in input.iter().enumerate().take(MAX_VARINT_LEN) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+
+        if byte & 0x80 == 0 {
+            // a trailing zero byte is a non-minimal encoding
+            if byte == 0 && i > 0 {
+                return Err(Error::InvalidEncoding);
+            }
+
+            *input = &input[i + 1..];
+            return Ok(value);
+        }
+    }
+
+    Err(Error::InvalidEncoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_varint, write_varint, Error};
+
+    /// Encode and decode varints from the multiformats spec
+    #[test]
+    fn varint() {
+        let vectors: [(u64, &[u8]); 5] = [
+            (1, &[0x01]),
+            (127, &[0x7f]),
+            (128, &[0x80, 0x01]),
+            (255, &[0xff, 0x01]),
+            (16384, &[0x80, 0x80, 0x01]),
+        ];
+
+        for (value, bytes) in vectors {
+            let mut output = Vec::new();
+            write_varint(value, &mut output);
+            assert_eq!(output, bytes);
+
+            let mut input = bytes;
+            assert_eq!(read_varint(&mut input).unwrap(), value);
+            assert!(input.is_empty());
+        }
+    }
+
+    /// Reject truncated and non-minimal varints
+    #[test]
+    fn invalid_varint() {
+        for bytes in [&[0x80][..], &[0x81, 0x00], &[0xff; 10]] {
+            let mut input = bytes;
+            assert!(matches!(
+                read_varint(&mut input),
+                Err(Error::InvalidEncoding)
+            ));
+        }
+    }
+}
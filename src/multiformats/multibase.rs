@@ -0,0 +1,296 @@
+//! Multibase encoding of bytes.
+
+use base64::{
+    engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD},
+    Engine,
+};
+
+use crate::{Error, Result};
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Base encodings of a multibase string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base {
+    /// Lowercase hexadecimal (`f`)
+    Base16,
+    /// Lowercase RFC 4648 base32 without padding (`b`), the default of CID v1
+    Base32,
+    /// Bitcoin base58 (`z`)
+    Base58Btc,
+    /// RFC 4648 base64 without padding (`m`)
+    Base64,
+    /// RFC 4648 URL safe base64 without padding (`u`)
+    Base64Url,
+}
+
+impl Base {
+    /// Prefix of the encoding in a multibase string
+    pub fn prefix(&self) -> char {
+        match self {
+            Self::Base16 => 'f',
+            Self::Base32 => 'b',
+            Self::Base58Btc => 'z',
+            Self::Base64 => 'm',
+            Self::Base64Url => 'u',
+        }
+    }
+
+    fn from_prefix(prefix: char) -> Result<Self> {
+        match prefix {
+            'f' => Ok(Self::Base16),
+            'b' => Ok(Self::Base32),
+            'z' => Ok(Self::Base58Btc),
+            'm' => Ok(Self::Base64),
+            'u' => Ok(Self::Base64Url),
+            _ => Err(Error::InvalidEncoding),
+        }
+    }
+}
+
+/// Encode the bytes as a multibase string.
+///
+/// ```
+/// use crypto_utils::multiformats::{multibase_encode, Base};
+///
+/// assert_eq!(multibase_encode(Base::Base58Btc, b"yes mani !"), "z7paNL19xttacUY");
+/// ```
+pub fn multibase_encode(base: Base, data: impl AsRef<[u8]>) -> String {
+    let data = data.as_ref();
+
+    let encoded = match base {
+        Base::Base16 => data.iter().map(|byte| format!("{byte:02x}")).collect(),
+        Base::Base32 => base32_encode(data),
+        Base::Base58Btc => base58_encode(data),
+        Base::Base64 => STANDARD_NO_PAD.encode(data),
+        Base::Base64Url => URL_SAFE_NO_PAD.encode(data),
+    };
+
+    format!("{}{encoded}", base.prefix())
+}
+
+/// Decode a multibase string and return its base encoding with the bytes.
+///
+/// Unsupported prefixes and malformed strings return [Error::InvalidEncoding].
+///
+/// ```
+/// use crypto_utils::multiformats::{multibase_decode, Base};
+///
+/// let (base, data) = multibase_decode("bpfsxgidnmfxgsibb").unwrap();
+///
+/// assert_eq!(base, Base::Base32);
+/// assert_eq!(data, b"yes mani !");
+/// ```
+pub fn multibase_decode(input: &str) -> Result<(Base, Vec<u8>)> {
+    let mut chars = input.chars();
+    let base = Base::from_prefix(chars.next().ok_or(Error::InvalidEncoding)?)?;
+    let encoded = chars.as_str();
+
+    let data = match base {
+        Base::Base16 => base16_decode(encoded),
+        Base::Base32 => base32_decode(encoded),
+        Base::Base58Btc => base58_decode(encoded),
+        Base::Base64 => STANDARD_NO_PAD
+            .decode(encoded)
+            .map_err(|_| Error::InvalidEncoding),
+        Base::Base64Url => URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|_| Error::InvalidEncoding),
+    }?;
+
+    Ok((base, data))
+}
+
+fn base16_decode(input: &str) -> Result<Vec<u8>> {
+    if !input.len().is_multiple_of(2) {
+        return Err(Error::InvalidEncoding);
+    }
+
+    input
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).map_err(|_| Error::InvalidEncoding)?;
+
+            // only the lowercase digits belong to the `f` prefix
+            match pair
+                .bytes()
+                .all(|c| c.is_ascii_digit() || (b'a'..=b'f').contains(&c))
+            {
+                true => u8::from_str_radix(pair, 16).map_err(|_| Error::InvalidEncoding),
+                false => Err(Error::InvalidEncoding),
+            }
+        })
+        .collect()
+}
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut output = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for &byte in data {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32_ALPHABET[(buffer >> bits) as usize & 31] as char);
+        }
+    }
+
+    if bits > 0 {
+        output.push(BASE32_ALPHABET[(buffer << (5 - bits)) as usize & 31] as char);
+    }
+
+    output
+}
+
+fn base32_decode(input: &str) -> Result<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for c in input.bytes() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .ok_or(Error::InvalidEncoding)?;
+
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+
+    // the rest are padding bits, they must be zeros and shorter than a character
+    if bits >= 5 || buffer & ((1 << bits) - 1) != 0 {
+        return Err(Error::InvalidEncoding);
+    }
+
+    Ok(output)
+}
+
+fn base58_encode(data: &[u8]) -> String {
+    let zeros = data.iter().take_while(|&&byte| byte == 0).count();
+
+    // digits in base 58, the least significant first
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in &data[zeros..] {
+        let mut carry = u32::from(byte);
+
+        for digit in digits.iter_mut() {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    // every leading zero byte is encoded as the first character
+    std::iter::repeat_n('1', zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|&d| BASE58_ALPHABET[d as usize] as char),
+        )
+        .collect()
+}
+
+fn base58_decode(input: &str) -> Result<Vec<u8>> {
+    let zeros = input.bytes().take_while(|&c| c == b'1').count();
+
+    // bytes of the number, the least significant first
+    let mut bytes: Vec<u8> = Vec::with_capacity(input.len());
+    for c in input.bytes().skip(zeros) {
+        let mut carry = BASE58_ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .ok_or(Error::InvalidEncoding)? as u32;
+
+        for byte in bytes.iter_mut() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut output = vec![0; zeros];
+    output.extend(bytes.iter().rev());
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{multibase_decode, multibase_encode, Base, Error};
+
+    const INPUT: &[u8] = b"yes mani !";
+
+    /// Test vectors of the multibase spec
+    #[test]
+    fn vectors() {
+        let vectors = [
+            (Base::Base16, "f796573206d616e692021"),
+            (Base::Base32, "bpfsxgidnmfxgsibb"),
+            (Base::Base58Btc, "z7paNL19xttacUY"),
+            (Base::Base64, "meWVzIG1hbmkgIQ"),
+            (Base::Base64Url, "ueWVzIG1hbmkgIQ"),
+        ];
+
+        for (base, encoded) in vectors {
+            assert_eq!(multibase_encode(base, INPUT), encoded);
+            assert_eq!(multibase_decode(encoded).unwrap(), (base, INPUT.to_vec()));
+        }
+    }
+
+    /// Keep the leading zeros in base58
+    #[test]
+    fn base58_leading_zeros() {
+        let mut input = vec![0, 0];
+        input.extend_from_slice(INPUT);
+
+        let encoded = multibase_encode(Base::Base58Btc, &input);
+        assert_eq!(encoded, "z117paNL19xttacUY");
+        assert_eq!(multibase_decode(&encoded).unwrap().1, input);
+
+        assert_eq!(multibase_encode(Base::Base58Btc, [0]), "z1");
+        assert_eq!(multibase_decode("z").unwrap().1, b"");
+    }
+
+    /// Encode and decode all lengths around the base32 groups
+    #[test]
+    fn roundtrip() {
+        let input = (0..=255u8).collect::<Vec<_>>();
+
+        for len in 0..20 {
+            for base in [Base::Base16, Base::Base32, Base::Base58Btc] {
+                let encoded = multibase_encode(base, &input[..len]);
+                assert_eq!(multibase_decode(&encoded).unwrap().1, &input[..len]);
+            }
+        }
+    }
+
+    /// Reject unknown prefixes and invalid characters
+    #[test]
+    fn invalid() {
+        for input in ["", "x1234", "f7", "F796573", "b0", "baf", "z0OIl", "m!!"] {
+            let err = multibase_decode(input).unwrap_err();
+            assert!(matches!(err, Error::InvalidEncoding), "{input}");
+        }
+    }
+}
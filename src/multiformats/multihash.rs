@@ -0,0 +1,161 @@
+//! Multihash encoding of digests.
+
+use super::{read_varint, write_varint};
+use crate::{
+    sha::{Algorithm, Digest},
+    Error, Result,
+};
+
+/// Code of the hash function in the multicodec table.
+///
+/// ```
+/// use crypto_utils::{multiformats::multihash_code, sha::Algorithm};
+///
+/// assert_eq!(multihash_code(Algorithm::SHA256), 0x12);
+/// ```
+pub fn multihash_code(algo: Algorithm) -> u64 {
+    match algo {
+        Algorithm::SHA1 => 0x11,
+        Algorithm::SHA256 => 0x12,
+        Algorithm::SHA384 => 0x20,
+        Algorithm::SHA512 => 0x13,
+    }
+}
+
+/// Algorithm of the multicodec code, unsupported codes return [Error::InvalidAlgorithm].
+fn algorithm(code: u64) -> Result<Algorithm> {
+    match code {
+        0x11 => Ok(Algorithm::SHA1),
+        0x12 => Ok(Algorithm::SHA256),
+        0x20 => Ok(Algorithm::SHA384),
+        0x13 => Ok(Algorithm::SHA512),
+        _ => Err(Error::InvalidAlgorithm),
+    }
+}
+
+/// Encode the digest as a multihash: varint code of the hash function, varint length and
+/// the digest.
+///
+/// ```
+/// use crypto_utils::{
+///     multiformats::to_multihash,
+///     sha::{Algorithm, Digest},
+/// };
+///
+/// let digest = Digest::new(Algorithm::SHA1, vec![0xab; 20]);
+///
+/// assert_eq!(&to_multihash(&digest)[..2], [0x11, 20]);
+/// ```
+pub fn to_multihash(digest: &Digest) -> Vec<u8> {
+    let mut output = Vec::with_capacity(digest.as_bytes().len() + 2);
+    write_multihash(digest, &mut output);
+
+    output
+}
+
+/// Decode a multihash.
+///
+/// Unsupported hash functions return [Error::InvalidAlgorithm], a multihash with a wrong
+/// length or trailing bytes returns [Error::InvalidDigest].
+///
+/// ```
+/// use crypto_utils::{multiformats::from_multihash, sha::Algorithm};
+///
+/// let mut multihash = vec![0x12, 32];
+/// multihash.extend_from_slice(&[0; 32]);
+///
+/// let digest = from_multihash(&multihash).unwrap();
+///
+/// assert_eq!(digest.algorithm(), Algorithm::SHA256);
+/// ```
+pub fn from_multihash(mut bytes: &[u8]) -> Result<Digest> {
+    let digest = read_multihash(&mut bytes)?;
+
+    match bytes.is_empty() {
+        true => Ok(digest),
+        false => Err(Error::InvalidDigest),
+    }
+}
+
+pub(super) fn write_multihash(digest: &Digest, output: &mut Vec<u8>) {
+    write_varint(multihash_code(digest.algorithm()), output);
+    write_varint(digest.as_bytes().len() as u64, output);
+    output.extend_from_slice(digest.as_bytes());
+}
+
+pub(super) fn read_multihash(input: &mut &[u8]) -> Result<Digest> {
+    let algo = algorithm(read_varint(input)?)?;
+    let len = read_varint(input)?;
+
+    // truncated digests aren't supported
+    let expected_len = algo.output_len();
+    if len != expected_len as u64 || input.len() < expected_len {
+        return Err(Error::InvalidDigest);
+    }
+
+    let (bytes, rest) = input.split_at(expected_len);
+    *input = rest;
+
+    Ok(Digest::new(algo, bytes.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_multihash, to_multihash, Algorithm, Error};
+    use crate::sha::CryptographicHash;
+
+    /// Encode and decode the digests of all algorithms
+    #[test]
+    fn roundtrip() {
+        for (algo, prefix) in [
+            (Algorithm::SHA1, [0x11, 20]),
+            (Algorithm::SHA256, [0x12, 32]),
+            (Algorithm::SHA384, [0x20, 48]),
+            (Algorithm::SHA512, [0x13, 64]),
+        ] {
+            let mut hasher = CryptographicHash::new(algo);
+            hasher.update(b"multihash");
+            let digest = hasher.digest();
+
+            let multihash = to_multihash(&digest);
+            assert_eq!(multihash[..2], prefix);
+            assert_eq!(&multihash[2..], digest.as_bytes());
+
+            assert_eq!(from_multihash(&multihash).unwrap(), digest);
+        }
+    }
+
+    /// Reject unknown codes, wrong lengths and trailing bytes
+    #[test]
+    fn invalid() {
+        let mut multihash = vec![0x12, 32];
+        multihash.extend_from_slice(&[0; 32]);
+
+        // unknown hash function (blake2b-256)
+        let mut unknown = vec![0xa0, 0xe4, 0x02, 32];
+        unknown.extend_from_slice(&[0; 32]);
+        assert!(matches!(
+            from_multihash(&unknown),
+            Err(Error::InvalidAlgorithm)
+        ));
+
+        // truncated digest
+        let err = from_multihash(&multihash[..20]).unwrap_err();
+        assert!(matches!(err, Error::InvalidDigest));
+
+        // length of a truncated hash
+        let mut truncated = vec![0x12, 20];
+        truncated.extend_from_slice(&[0; 20]);
+        assert!(matches!(
+            from_multihash(&truncated),
+            Err(Error::InvalidDigest)
+        ));
+
+        // trailing bytes
+        multihash.push(0);
+        assert!(matches!(
+            from_multihash(&multihash),
+            Err(Error::InvalidDigest)
+        ));
+    }
+}
@@ -0,0 +1,268 @@
+//! Module for hashing many files or buffers concurrently on the [rayon] thread pool.
+//!
+//! ```no_run
+//! use crypto_utils::{parallel::ParallelHasher, sha::Algorithm};
+//!
+//! let paths = ["release/app.tar.gz", "release/app.zip", "release/app.deb"];
+//!
+//! let digests = ParallelHasher::new(Algorithm::SHA256)
+//!     .on_progress(|progress| eprintln!("{}/{} files", progress.completed, progress.total))
+//!     .hash_files(&paths)
+//!     .unwrap();
+//!
+//! for (path, digest) in &digests {
+//!     println!("{digest}  {}", path.display());
+//! }
+//! ```
+//!
+//! The number of threads can be changed with the [rayon::ThreadPoolBuilder].
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+};
+
+use rayon::prelude::*;
+
+use crate::{
+    sha::{Algorithm, CryptographicHash, Digest},
+    Result,
+};
+
+/// Progress of the hashing, passed to the [ParallelHasher::on_progress] callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Number of the hashed items, including the current one
+    pub completed: usize,
+    /// Number of all items
+    pub total: usize,
+    /// Number of the hashed bytes
+    pub bytes: u64,
+}
+
+type ProgressCallback = Box<dyn Fn(Progress) + Send + Sync>;
+
+/// Hasher of many files or buffers.
+///
+/// Every item is hashed by a clone of the same [CryptographicHash], so the hasher can be
+/// created with [ParallelHasher::from_hasher] to prepend e.g. a salt to every item.
+pub struct ParallelHasher {
+    hasher: CryptographicHash,
+    on_progress: Option<ProgressCallback>,
+}
+
+impl ParallelHasher {
+    /// Create a new hasher using the algorithm.
+    pub fn new(algo: Algorithm) -> Self {
+        Self::from_hasher(CryptographicHash::new(algo))
+    }
+
+    /// Create a new hasher which clones the hasher for every item.
+    ///
+    /// ```
+    /// use crypto_utils::{
+    ///     parallel::ParallelHasher,
+    ///     sha::{Algorithm, CryptographicHash},
+    /// };
+    ///
+    /// let mut hasher = CryptographicHash::new(Algorithm::SHA256);
+    /// hasher.update(b"salt");
+    ///
+    /// let digests = ParallelHasher::from_hasher(hasher).hash_buffers(&[b"a", b"b"]);
+    ///
+    /// assert_eq!(digests[0].as_bytes(), CryptographicHash::hash(Algorithm::SHA256, b"salta"));
+    /// ```
+    pub fn from_hasher(hasher: CryptographicHash) -> Self {
+        Self {
+            hasher,
+            on_progress: None,
+        }
+    }
+
+    /// Call the function after each hashed item.
+    ///
+    /// The callback is called from the worker threads, so the calls may come out of order.
+    pub fn on_progress(mut self, callback: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Hash the files and return a map of their paths to the digests.
+    ///
+    /// The files are streamed to the hasher, the first error stops the hashing.
+    pub fn hash_files<P>(&self, paths: &[P]) -> Result<BTreeMap<PathBuf, Digest>>
+    where
+        P: AsRef<Path> + Sync,
+    {
+        let tracker = Tracker::new(self, paths.len());
+
+        paths
+            .par_iter()
+            .map(|path| {
+                let path = path.as_ref();
+
+                let mut file = File::open(path)?;
+                let mut hasher = self.hasher.clone();
+
+                let bytes = io::copy(&mut file, &mut hasher)?;
+                tracker.completed(bytes);
+
+                Ok((path.to_path_buf(), hasher.digest()))
+            })
+            .collect()
+    }
+
+    /// Hash the buffers and return the digests in the same order.
+    ///
+    /// ```
+    /// use crypto_utils::{parallel::ParallelHasher, sha::Algorithm};
+    ///
+    /// let buffers: Vec<Vec<u8>> = (0..100u8).map(|i| vec![i; 1024]).collect();
+    ///
+    /// let digests = ParallelHasher::new(Algorithm::SHA512).hash_buffers(&buffers);
+    ///
+    /// assert_eq!(digests.len(), 100);
+    /// ```
+    pub fn hash_buffers<B>(&self, buffers: &[B]) -> Vec<Digest>
+    where
+        B: AsRef<[u8]> + Sync,
+    {
+        let tracker = Tracker::new(self, buffers.len());
+
+        buffers
+            .par_iter()
+            .map(|buffer| {
+                let buffer = buffer.as_ref();
+
+                let mut hasher = self.hasher.clone();
+                hasher.update(buffer);
+                tracker.completed(buffer.len() as u64);
+
+                hasher.digest()
+            })
+            .collect()
+    }
+}
+
+/// Shared counters of the progress
+struct Tracker<'a> {
+    callback: Option<&'a ProgressCallback>,
+    total: usize,
+    completed: AtomicUsize,
+    bytes: AtomicU64,
+}
+
+impl<'a> Tracker<'a> {
+    fn new(hasher: &'a ParallelHasher, total: usize) -> Self {
+        Self {
+            callback: hasher.on_progress.as_ref(),
+            total,
+            completed: AtomicUsize::new(0),
+            bytes: AtomicU64::new(0),
+        }
+    }
+
+    fn completed(&self, bytes: u64) {
+        let completed = self.completed.fetch_add(1, Ordering::Relaxed) + 1;
+        let bytes = self.bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+
+        if let Some(callback) = self.callback {
+            callback(Progress {
+                completed,
+                total: self.total,
+                bytes,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        sync::{
+            atomic::{AtomicU64, AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use super::{Algorithm, CryptographicHash, ParallelHasher};
+    use crate::Error;
+
+    /// Hash files and compare them with the sequential hashes
+    #[test]
+    fn hash_files() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // create the files
+        let paths = (0..50)
+            .map(|i| {
+                let path = dir.path().join(format!("file-{i}"));
+                fs::write(&path, vec![i as u8; i * 100]).unwrap();
+                path
+            })
+            .collect::<Vec<_>>();
+
+        // hash the files and count the progress calls
+        let calls = Arc::new(AtomicUsize::new(0));
+        let bytes = Arc::new(AtomicU64::new(0));
+
+        let digests = ParallelHasher::new(Algorithm::SHA256)
+            .on_progress({
+                let calls = calls.clone();
+                let bytes = bytes.clone();
+
+                move |progress| {
+                    assert_eq!(progress.total, 50);
+
+                    calls.fetch_add(1, Ordering::Relaxed);
+                    bytes.fetch_max(progress.bytes, Ordering::Relaxed);
+                }
+            })
+            .hash_files(&paths)
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::Relaxed), 50);
+        assert_eq!(bytes.load(Ordering::Relaxed), (0..50).sum::<u64>() * 100);
+
+        // validate the digests
+        assert_eq!(digests.len(), 50);
+
+        for (i, path) in paths.iter().enumerate() {
+            let expected = CryptographicHash::hash(Algorithm::SHA256, &vec![i as u8; i * 100]);
+
+            assert_eq!(digests[path].as_bytes(), expected);
+        }
+    }
+
+    /// Keep the order of the buffers
+    #[test]
+    fn hash_buffers() {
+        let buffers = (0..200u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+
+        let digests = ParallelHasher::new(Algorithm::SHA1).hash_buffers(&buffers);
+
+        for (buffer, digest) in buffers.iter().zip(&digests) {
+            assert_eq!(digest.algorithm(), Algorithm::SHA1);
+            assert_eq!(
+                digest.as_bytes(),
+                CryptographicHash::hash(Algorithm::SHA1, buffer)
+            );
+        }
+    }
+
+    /// Return the error of a missing file
+    #[test]
+    fn missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let err = ParallelHasher::new(Algorithm::SHA256)
+            .hash_files(&[dir.path().join("missing")])
+            .unwrap_err();
+
+        assert!(matches!(err, Error::Io(_)));
+    }
+}
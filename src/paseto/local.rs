@@ -0,0 +1,331 @@
+use blake2::{
+    digest::{
+        consts::{U32, U56},
+        Mac,
+    },
+    Blake2bMac,
+};
+use chacha20::{
+    cipher::{KeyIvInit, StreamCipher},
+    XChaCha20,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    token::{decode_claims, decode_token, encode_claims, encode_token, pae},
+    Error, Result, TokenData,
+};
+use crate::jsonwebtoken::Claims;
+
+/// Header of the `v4.local` tokens
+const HEADER: &str = "v4.local.";
+/// Length of the key in bytes
+const KEY_LEN: usize = 32;
+/// Length of the nonce in bytes
+const NONCE_LEN: usize = 32;
+/// Length of the authentication tag in bytes
+const TAG_LEN: usize = 32;
+
+/// PASETO `v4.local` token (XChaCha20 encrypted and BLAKE2b authenticated) using a shared 256-bit key.
+///
+/// ```
+/// use crypto_utils::{jsonwebtoken::Claims, paseto::LocalToken};
+///
+/// // 256-bit key
+/// let key = [0u8; 32];
+///
+/// // token claims
+/// let claims = Claims::new("user_id_1234", 24);
+///
+/// // create token
+/// let token = LocalToken::new(&key, claims).unwrap();
+///
+/// // decode token
+/// let decoded = LocalToken::decode(&key, token.encoded).unwrap();
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LocalToken {
+    /// Token claims
+    pub claims: Claims,
+    /// Encoded token to a String
+    pub encoded: String,
+}
+
+impl LocalToken {
+    /// Create a new token
+    ///
+    /// ```
+    /// use crypto_utils::{jsonwebtoken::Claims, paseto::LocalToken};
+    ///
+    /// // 256-bit key
+    /// let key = [0u8; 32];
+    ///
+    /// // token claims
+    /// let claims = Claims::new("user_id_1234", 24);
+    ///
+    /// // create token
+    /// let token = LocalToken::new(&key, claims).unwrap();
+    /// ```
+    pub fn new(key: impl AsRef<[u8]>, claims: Claims) -> Result<Self> {
+        let key = key.as_ref();
+
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        let encoded = encrypt(key, &encode_claims(&claims)?, b"", b"", &nonce)?;
+
+        Ok(Self { claims, encoded })
+    }
+
+    /// Decrypt, validate and decode token
+    ///
+    /// ```
+    /// use crypto_utils::{jsonwebtoken::Claims, paseto::LocalToken};
+    ///
+    /// // 256-bit key
+    /// let key = [0u8; 32];
+    ///
+    /// // create token
+    /// let token = LocalToken::new(&key, Claims::new("user_id_1234", 24)).unwrap();
+    ///
+    /// // decode token
+    /// let decoded = LocalToken::decode(&key, token.encoded).unwrap();
+    /// ```
+    pub fn decode(key: impl AsRef<[u8]>, token: String) -> Result<TokenData> {
+        let key = key.as_ref();
+
+        let (payload, _footer) = decrypt(key, &token, b"")?;
+
+        decode_claims(&payload)
+    }
+}
+
+/// Split the key into the encryption key, the XChaCha20 nonce and the authentication key.
+fn split_key(key: &[u8], nonce: &[u8]) -> Result<([u8; 32], [u8; 24], Vec<u8>)> {
+    if key.len() != KEY_LEN {
+        return Err(Error::InvalidKeyLength);
+    }
+
+    let mut mac = Blake2bMac::<U56>::new_from_slice(key).map_err(|_| Error::InvalidKeyLength)?;
+    mac.update(b"paseto-encryption-key");
+    mac.update(nonce);
+    let tmp = mac.finalize().into_bytes();
+
+    let mut encryption_key = [0u8; 32];
+    encryption_key.copy_from_slice(&tmp[..32]);
+    let mut counter_nonce = [0u8; 24];
+    counter_nonce.copy_from_slice(&tmp[32..]);
+
+    let mut mac = Blake2bMac::<U32>::new_from_slice(key).map_err(|_| Error::InvalidKeyLength)?;
+    mac.update(b"paseto-auth-key-for-aead");
+    mac.update(nonce);
+    let auth_key = mac.finalize().into_bytes().to_vec();
+
+    Ok((encryption_key, counter_nonce, auth_key))
+}
+
+/// Encrypt a message using the given nonce.
+fn encrypt(
+    key: &[u8],
+    message: &[u8],
+    footer: &[u8],
+    implicit: &[u8],
+    nonce: &[u8; NONCE_LEN],
+) -> Result<String> {
+    let (encryption_key, counter_nonce, auth_key) = split_key(key, nonce)?;
+
+    let mut ciphertext = message.to_vec();
+    XChaCha20::new(&encryption_key.into(), &counter_nonce.into()).apply_keystream(&mut ciphertext);
+
+    let mut mac =
+        Blake2bMac::<U32>::new_from_slice(&auth_key).map_err(|_| Error::InvalidKeyLength)?;
+    mac.update(&pae(&[
+        HEADER.as_bytes(),
+        nonce,
+        &ciphertext,
+        footer,
+        implicit,
+    ]));
+    let tag = mac.finalize().into_bytes();
+
+    let body = [nonce.as_slice(), &ciphertext, &tag].concat();
+
+    Ok(encode_token(HEADER, &body, footer))
+}
+
+/// Verify and decrypt a token, returns the message and the footer.
+fn decrypt(key: &[u8], token: &str, implicit: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    let (body, footer) = decode_token(HEADER, token)?;
+
+    if body.len() < NONCE_LEN + TAG_LEN {
+        return Err(Error::InvalidToken);
+    }
+
+    let (nonce, rest) = body.split_at(NONCE_LEN);
+    let (ciphertext, tag) = rest.split_at(rest.len() - TAG_LEN);
+
+    let (encryption_key, counter_nonce, auth_key) = split_key(key, nonce)?;
+
+    // verify the tag (in constant time) before decrypting
+    let mut mac =
+        Blake2bMac::<U32>::new_from_slice(&auth_key).map_err(|_| Error::InvalidKeyLength)?;
+    mac.update(&pae(&[
+        HEADER.as_bytes(),
+        nonce,
+        ciphertext,
+        &footer,
+        implicit,
+    ]));
+    mac.verify_slice(tag).map_err(|_| Error::InvalidSignature)?;
+
+    let mut message = ciphertext.to_vec();
+    XChaCha20::new(&encryption_key.into(), &counter_nonce.into()).apply_keystream(&mut message);
+
+    Ok((message, footer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt, encrypt, Claims, Error, LocalToken};
+
+    const KEY: &str = "707172737475767778797a7b7c7d7e7f808182838485868788898a8b8c8d8e8f";
+    const NONCE_ZERO: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+    const NONCE: &str = "df654812bac492663825520ba2f6e67cf5ca5bdc13d4e7507a98cc4c2fcc3ad8";
+
+    const SECRET_MESSAGE: &str =
+        r#"{"data":"this is a secret message","exp":"2022-01-01T00:00:00+00:00"}"#;
+    const HIDDEN_MESSAGE: &str =
+        r#"{"data":"this is a hidden message","exp":"2022-01-01T00:00:00+00:00"}"#;
+    const FOOTER: &str = r#"{"kid":"zVhMiPBP9fRf2snEcT7gFTioeA9COcNy9DfgL1W60haN"}"#;
+
+    /// Official PASETO test vectors (`4-E-*`)
+    const VECTORS: &[(&str, &str, &str, &str, &str, &str)] = &[
+        (
+            "4-E-1",
+            NONCE_ZERO,
+            SECRET_MESSAGE,
+            "",
+            "",
+            "v4.local.AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAQAr68PS4AXe7If_ZgesdkUMvSwscFlAl1pk5HC0e8kApeaqMfGo_7OpBnwJOAbY9V7WU6abu74MmcUE8YWAiaArVI8XJ5hOb_4v9RmDkneN0S92dx0OW4pgy7omxgf3S8c3LlQg",
+        ),
+        (
+            "4-E-2",
+            NONCE_ZERO,
+            HIDDEN_MESSAGE,
+            "",
+            "",
+            "v4.local.AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAQAr68PS4AXe7If_ZgesdkUMvS2csCgglvpk5HC0e8kApeaqMfGo_7OpBnwJOAbY9V7WU6abu74MmcUE8YWAiaArVI8XIemu9chy3WVKvRBfg6t8wwYHK0ArLxxfZP73W_vfwt5A",
+        ),
+        (
+            "4-E-3",
+            NONCE,
+            SECRET_MESSAGE,
+            "",
+            "",
+            "v4.local.32VIErrEkmY4JVILovbmfPXKW9wT1OdQepjMTC_MOtjA4kiqw7_tcaOM5GNEcnTxl60WkwMsYXw6FSNb_UdJPXjpzm0KW9ojM5f4O2mRvE2IcweP-PRdoHjd5-RHCiExR1IK6t6-tyebyWG6Ov7kKvBdkrrAJ837lKP3iDag2hzUPHuMKA",
+        ),
+        (
+            "4-E-4",
+            NONCE,
+            HIDDEN_MESSAGE,
+            "",
+            "",
+            "v4.local.32VIErrEkmY4JVILovbmfPXKW9wT1OdQepjMTC_MOtjA4kiqw7_tcaOM5GNEcnTxl60WiA8rd3wgFSNb_UdJPXjpzm0KW9ojM5f4O2mRvE2IcweP-PRdoHjd5-RHCiExR1IK6t4gt6TiLm55vIH8c_lGxxZpE3AWlH4WTR0v45nsWoU3gQ",
+        ),
+        (
+            "4-E-5",
+            NONCE,
+            SECRET_MESSAGE,
+            FOOTER,
+            "",
+            "v4.local.32VIErrEkmY4JVILovbmfPXKW9wT1OdQepjMTC_MOtjA4kiqw7_tcaOM5GNEcnTxl60WkwMsYXw6FSNb_UdJPXjpzm0KW9ojM5f4O2mRvE2IcweP-PRdoHjd5-RHCiExR1IK6t4x-RMNXtQNbz7FvFZ_G-lFpk5RG3EOrwDL6CgDqcerSQ.eyJraWQiOiJ6VmhNaVBCUDlmUmYyc25FY1Q3Z0ZUaW9lQTlDT2NOeTlEZmdMMVc2MGhhTiJ9",
+        ),
+        (
+            "4-E-6",
+            NONCE,
+            HIDDEN_MESSAGE,
+            FOOTER,
+            "",
+            "v4.local.32VIErrEkmY4JVILovbmfPXKW9wT1OdQepjMTC_MOtjA4kiqw7_tcaOM5GNEcnTxl60WiA8rd3wgFSNb_UdJPXjpzm0KW9ojM5f4O2mRvE2IcweP-PRdoHjd5-RHCiExR1IK6t6pWSA5HX2wjb3P-xLQg5K5feUCX4P2fpVK3ZLWFbMSxQ.eyJraWQiOiJ6VmhNaVBCUDlmUmYyc25FY1Q3Z0ZUaW9lQTlDT2NOeTlEZmdMMVc2MGhhTiJ9",
+        ),
+        (
+            "4-E-7",
+            NONCE,
+            SECRET_MESSAGE,
+            FOOTER,
+            r#"{"test-vector":"4-E-7"}"#,
+            "v4.local.32VIErrEkmY4JVILovbmfPXKW9wT1OdQepjMTC_MOtjA4kiqw7_tcaOM5GNEcnTxl60WkwMsYXw6FSNb_UdJPXjpzm0KW9ojM5f4O2mRvE2IcweP-PRdoHjd5-RHCiExR1IK6t40KCCWLA7GYL9KFHzKlwY9_RnIfRrMQpueydLEAZGGcA.eyJraWQiOiJ6VmhNaVBCUDlmUmYyc25FY1Q3Z0ZUaW9lQTlDT2NOeTlEZmdMMVc2MGhhTiJ9",
+        ),
+        (
+            "4-E-8",
+            NONCE,
+            HIDDEN_MESSAGE,
+            FOOTER,
+            r#"{"test-vector":"4-E-8"}"#,
+            "v4.local.32VIErrEkmY4JVILovbmfPXKW9wT1OdQepjMTC_MOtjA4kiqw7_tcaOM5GNEcnTxl60WiA8rd3wgFSNb_UdJPXjpzm0KW9ojM5f4O2mRvE2IcweP-PRdoHjd5-RHCiExR1IK6t5uvqQbMGlLLNYBc7A6_x7oqnpUK5WLvj24eE4DVPDZjw.eyJraWQiOiJ6VmhNaVBCUDlmUmYyc25FY1Q3Z0ZUaW9lQTlDT2NOeTlEZmdMMVc2MGhhTiJ9",
+        ),
+        (
+            "4-E-9",
+            NONCE,
+            HIDDEN_MESSAGE,
+            "arbitrary-string-that-isn't-json",
+            r#"{"test-vector":"4-E-9"}"#,
+            "v4.local.32VIErrEkmY4JVILovbmfPXKW9wT1OdQepjMTC_MOtjA4kiqw7_tcaOM5GNEcnTxl60WiA8rd3wgFSNb_UdJPXjpzm0KW9ojM5f4O2mRvE2IcweP-PRdoHjd5-RHCiExR1IK6t6tybdlmnMwcDMw0YxA_gFSE_IUWl78aMtOepFYSWYfQA.YXJiaXRyYXJ5LXN0cmluZy10aGF0LWlzbid0LWpzb24",
+        ),
+    ];
+
+    /// Test the official PASETO `v4.local` test vectors
+    #[test]
+    fn test_vectors() {
+        let key = hex::decode(KEY).unwrap();
+
+        for (name, nonce, message, footer, implicit, expected) in VECTORS {
+            let nonce: [u8; 32] = hex::decode(nonce).unwrap().try_into().unwrap();
+
+            // encrypt message
+            let token = encrypt(
+                &key,
+                message.as_bytes(),
+                footer.as_bytes(),
+                implicit.as_bytes(),
+                &nonce,
+            )
+            .unwrap();
+
+            assert_eq!(&token, expected, "{name}");
+
+            // decrypt message
+            let (decrypted, decrypted_footer) = decrypt(&key, expected, implicit.as_bytes())
+                .unwrap_or_else(|err| panic!("{name}: {err}"));
+
+            assert_eq!(decrypted, message.as_bytes(), "{name}");
+            assert_eq!(decrypted_footer, footer.as_bytes(), "{name}");
+        }
+    }
+
+    /// Decode token with invalid key
+    #[test]
+    fn decode_key_invalid() {
+        let token = LocalToken::new([1u8; 32], Claims::new("user_id_1234", 24)).unwrap();
+
+        // unwrap error when decoding token
+        let err = LocalToken::decode([2u8; 32], token.encoded).unwrap_err();
+
+        assert!(matches!(err, Error::InvalidSignature));
+    }
+
+    /// Decode expired token
+    #[test]
+    fn decode_expired() {
+        let key = [1u8; 32];
+
+        // create a token that expired an hour ago
+        let token = LocalToken::new(key, Claims::new("test", -1)).expect("generate token");
+
+        // unwrap error when decoding token
+        let err = LocalToken::decode(key, token.encoded).unwrap_err();
+
+        assert!(matches!(err, Error::Expired));
+    }
+}
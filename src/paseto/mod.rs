@@ -0,0 +1,55 @@
+//! Module for creating and decoding [PASETO](https://paseto.io) v4 tokens.
+//!
+//! PASETO doesn't let the token choose its algorithm, so it avoids the algorithm confusion
+//! pitfalls of the json web token. The tokens use the same [Claims](crate::jsonwebtoken::Claims)
+//! as the [jsonwebtoken](crate::jsonwebtoken) module.
+//!
+//! ## Local
+//!
+//! Token encrypted with XChaCha20 and authenticated with BLAKE2b using a shared key:
+//!
+//! ```
+//! use crypto_utils::{jsonwebtoken::Claims, paseto::LocalToken};
+//!
+//! let key = [0u8; 32];
+//! let user_id = "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx";
+//!
+//! // create claims
+//! let claims = Claims::new(user_id, 24);
+//!
+//! // create token
+//! let token = LocalToken::new(&key, claims).unwrap();
+//!
+//! // decode token
+//! let decoded = LocalToken::decode(&key, token.encoded).unwrap();
+//! ```
+//!
+//! ## Public
+//!
+//! Token signed with an Ed25519 secret key and verified with the public key:
+//!
+//! ```
+//! use crypto_utils::{jsonwebtoken::Claims, paseto::PublicToken};
+//!
+//! let secret_key = hex::decode("b4cbfb43df4ce210727d953e4a713307fa19bb7d9f85041438d9e11b942a3774").unwrap();
+//! let public_key = hex::decode("1eb9dbbbbc047c03fd70604e0071f0987e16b28b757225c11f00415d0e20b1a2").unwrap();
+//! let user_id = "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx";
+//!
+//! // create claims
+//! let claims = Claims::new(user_id, 24);
+//!
+//! // create token
+//! let token = PublicToken::new(&secret_key, claims).unwrap();
+//!
+//! // decode token
+//! let decoded = PublicToken::decode(&public_key, token.encoded).unwrap();
+//! ```
+
+mod local;
+mod public;
+mod token;
+
+pub use crate::error::{Error, Result};
+pub use local::*;
+pub use public::*;
+pub use token::TokenData;
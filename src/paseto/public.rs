@@ -0,0 +1,193 @@
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey, SIGNATURE_LENGTH};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    token::{decode_claims, decode_token, encode_claims, encode_token, pae},
+    Error, Result, TokenData,
+};
+use crate::jsonwebtoken::Claims;
+
+/// Header of the `v4.public` tokens
+const HEADER: &str = "v4.public.";
+
+/// PASETO `v4.public` token (Ed25519 signed).
+///
+/// The secret key is either a 32-byte seed or a 64-byte keypair (seed and public key),
+/// the public key is 32 bytes long.
+///
+/// ```
+/// use crypto_utils::{jsonwebtoken::Claims, paseto::PublicToken};
+///
+/// // Ed25519 keys
+/// let secret_key = hex::decode("b4cbfb43df4ce210727d953e4a713307fa19bb7d9f85041438d9e11b942a3774").unwrap();
+/// let public_key = hex::decode("1eb9dbbbbc047c03fd70604e0071f0987e16b28b757225c11f00415d0e20b1a2").unwrap();
+///
+/// // token claims
+/// let claims = Claims::new("user_id_1234", 24);
+///
+/// // create token
+/// let token = PublicToken::new(&secret_key, claims).unwrap();
+///
+/// // decode token
+/// let decoded = PublicToken::decode(&public_key, token.encoded).unwrap();
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PublicToken {
+    /// Token claims
+    pub claims: Claims,
+    /// Encoded token to a String
+    pub encoded: String,
+}
+
+impl PublicToken {
+    /// Create a new token signed with the secret key
+    pub fn new(secret_key: impl AsRef<[u8]>, claims: Claims) -> Result<Self> {
+        let secret_key = secret_key.as_ref();
+
+        let encoded = sign(secret_key, &encode_claims(&claims)?, b"", b"")?;
+
+        Ok(Self { claims, encoded })
+    }
+
+    /// Verify, validate and decode token using the public key
+    pub fn decode(public_key: impl AsRef<[u8]>, token: String) -> Result<TokenData> {
+        let public_key = public_key.as_ref();
+
+        let (payload, _footer) = verify(public_key, &token, b"")?;
+
+        decode_claims(&payload)
+    }
+}
+
+/// Sign a message.
+fn sign(secret_key: &[u8], message: &[u8], footer: &[u8], implicit: &[u8]) -> Result<String> {
+    let signing_key = match secret_key.len() {
+        32 => SigningKey::from_bytes(secret_key.try_into().unwrap()),
+        64 => SigningKey::from_keypair_bytes(secret_key.try_into().unwrap())
+            .map_err(|_| Error::InvalidKey)?,
+        _ => return Err(Error::InvalidKeyLength),
+    };
+
+    let signature = signing_key.sign(&pae(&[HEADER.as_bytes(), message, footer, implicit]));
+
+    let body = [message, &signature.to_bytes()].concat();
+
+    Ok(encode_token(HEADER, &body, footer))
+}
+
+/// Verify a token, returns the message and the footer.
+fn verify(public_key: &[u8], token: &str, implicit: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    let verifying_key = public_key
+        .try_into()
+        .ok()
+        .and_then(|key| VerifyingKey::from_bytes(key).ok())
+        .ok_or(Error::InvalidKey)?;
+
+    let (body, footer) = decode_token(HEADER, token)?;
+
+    if body.len() < SIGNATURE_LENGTH {
+        return Err(Error::InvalidToken);
+    }
+
+    let (message, signature) = body.split_at(body.len() - SIGNATURE_LENGTH);
+    let signature = Signature::from_slice(signature).map_err(|_| Error::InvalidToken)?;
+
+    verifying_key
+        .verify_strict(
+            &pae(&[HEADER.as_bytes(), message, &footer, implicit]),
+            &signature,
+        )
+        .map_err(|_| Error::InvalidSignature)?;
+
+    Ok((message.to_vec(), footer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sign, verify, Claims, Error, PublicToken, SigningKey};
+
+    const SECRET_KEY: &str = "b4cbfb43df4ce210727d953e4a713307fa19bb7d9f85041438d9e11b942a37741eb9dbbbbc047c03fd70604e0071f0987e16b28b757225c11f00415d0e20b1a2";
+    const PUBLIC_KEY: &str = "1eb9dbbbbc047c03fd70604e0071f0987e16b28b757225c11f00415d0e20b1a2";
+
+    const MESSAGE: &str =
+        r#"{"data":"this is a signed message","exp":"2022-01-01T00:00:00+00:00"}"#;
+    const FOOTER: &str = r#"{"kid":"zVhMiPBP9fRf2snEcT7gFTioeA9COcNy9DfgL1W60haN"}"#;
+
+    /// Official PASETO test vectors (`4-S-*`)
+    const VECTORS: &[(&str, &str, &str, &str)] = &[
+        (
+            "4-S-1",
+            "",
+            "",
+            "v4.public.eyJkYXRhIjoidGhpcyBpcyBhIHNpZ25lZCBtZXNzYWdlIiwiZXhwIjoiMjAyMi0wMS0wMVQwMDowMDowMCswMDowMCJ9bg_XBBzds8lTZShVlwwKSgeKpLT3yukTw6JUz3W4h_ExsQV-P0V54zemZDcAxFaSeef1QlXEFtkqxT1ciiQEDA",
+        ),
+        (
+            "4-S-2",
+            FOOTER,
+            "",
+            "v4.public.eyJkYXRhIjoidGhpcyBpcyBhIHNpZ25lZCBtZXNzYWdlIiwiZXhwIjoiMjAyMi0wMS0wMVQwMDowMDowMCswMDowMCJ9v3Jt8mx_TdM2ceTGoqwrh4yDFn0XsHvvV_D0DtwQxVrJEBMl0F2caAdgnpKlt4p7xBnx1HcO-SPo8FPp214HDw.eyJraWQiOiJ6VmhNaVBCUDlmUmYyc25FY1Q3Z0ZUaW9lQTlDT2NOeTlEZmdMMVc2MGhhTiJ9",
+        ),
+        (
+            "4-S-3",
+            FOOTER,
+            r#"{"test-vector":"4-S-3"}"#,
+            "v4.public.eyJkYXRhIjoidGhpcyBpcyBhIHNpZ25lZCBtZXNzYWdlIiwiZXhwIjoiMjAyMi0wMS0wMVQwMDowMDowMCswMDowMCJ9NPWciuD3d0o5eXJXG5pJy-DiVEoyPYWs1YSTwWHNJq6DZD3je5gf-0M4JR9ipdUSJbIovzmBECeaWmaqcaP0DQ.eyJraWQiOiJ6VmhNaVBCUDlmUmYyc25FY1Q3Z0ZUaW9lQTlDT2NOeTlEZmdMMVc2MGhhTiJ9",
+        ),
+    ];
+
+    /// Test the official PASETO `v4.public` test vectors
+    #[test]
+    fn test_vectors() {
+        let secret_key = hex::decode(SECRET_KEY).unwrap();
+        let public_key = hex::decode(PUBLIC_KEY).unwrap();
+
+        for (name, footer, implicit, expected) in VECTORS {
+            // sign message
+            let token = sign(
+                &secret_key,
+                MESSAGE.as_bytes(),
+                footer.as_bytes(),
+                implicit.as_bytes(),
+            )
+            .unwrap();
+
+            assert_eq!(&token, expected, "{name}");
+
+            // verify message
+            let (message, verified_footer) = verify(&public_key, expected, implicit.as_bytes())
+                .unwrap_or_else(|err| panic!("{name}: {err}"));
+
+            assert_eq!(message, MESSAGE.as_bytes(), "{name}");
+            assert_eq!(verified_footer, footer.as_bytes(), "{name}");
+        }
+    }
+
+    /// Decode token with a public key of other keypair
+    #[test]
+    fn decode_key_invalid() {
+        let secret_key = hex::decode(SECRET_KEY).unwrap();
+
+        let token = PublicToken::new(&secret_key, Claims::new("user_id_1234", 24)).unwrap();
+
+        // public key of other keypair
+        let other_key = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+
+        // unwrap error when decoding token
+        let err = PublicToken::decode(other_key.as_bytes(), token.encoded).unwrap_err();
+
+        assert!(matches!(err, Error::InvalidSignature));
+    }
+
+    /// Decode token signed for the `v4.local` purpose
+    #[test]
+    fn decode_purpose_invalid() {
+        let public_key = hex::decode(PUBLIC_KEY).unwrap();
+
+        let token = VECTORS[0].3.replace("v4.public.", "v4.local.");
+
+        // unwrap error when decoding token
+        let err = PublicToken::decode(&public_key, token).unwrap_err();
+
+        assert!(matches!(err, Error::InvalidToken));
+    }
+}
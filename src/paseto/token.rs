@@ -0,0 +1,132 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{Error, Result};
+use crate::jsonwebtoken::Claims;
+
+/// Leeway (in seconds) for the `exp` claim, the same as in [Token::decode](crate::jsonwebtoken::Token::decode)
+const LEEWAY: i64 = 60;
+
+/// The return type of a successful call to [LocalToken::decode](super::LocalToken::decode)
+/// and [PublicToken::decode](super::PublicToken::decode).
+#[derive(Debug, Clone)]
+pub struct TokenData {
+    /// Token claims
+    pub claims: Claims,
+}
+
+/// Claims in the PASETO payload, the registered time claims are ISO 8601 strings.
+#[derive(Serialize, Deserialize)]
+struct Payload {
+    sub: String,
+    exp: String,
+    iat: String,
+}
+
+/// Serialize claims to the PASETO payload.
+pub(super) fn encode_claims(claims: &Claims) -> Result<Vec<u8>> {
+    let payload = Payload {
+        sub: claims.sub.clone(),
+        exp: encode_time(claims.exp)?,
+        iat: encode_time(claims.iat)?,
+    };
+
+    Ok(serde_json::to_vec(&payload)?)
+}
+
+/// Deserialize and validate claims from the PASETO payload.
+pub(super) fn decode_claims(payload: &[u8]) -> Result<TokenData> {
+    let payload: Payload = serde_json::from_slice(payload)?;
+
+    let claims = Claims {
+        sub: payload.sub,
+        exp: decode_time(&payload.exp)?,
+        iat: decode_time(&payload.iat)?,
+    };
+
+    // validate expiration time
+    if claims.exp < Utc::now().timestamp() - LEEWAY {
+        return Err(Error::Expired);
+    }
+
+    Ok(TokenData { claims })
+}
+
+fn encode_time(timestamp: i64) -> Result<String> {
+    let time = Utc
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .ok_or(Error::InvalidToken)?;
+
+    Ok(time.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+fn decode_time(time: &str) -> Result<i64> {
+    let time = DateTime::parse_from_rfc3339(time).map_err(|_| Error::InvalidToken)?;
+
+    Ok(time.timestamp())
+}
+
+/// Pre-Authentication Encoding
+pub(super) fn pae(pieces: &[&[u8]]) -> Vec<u8> {
+    let mut output = le64(pieces.len() as u64).to_vec();
+
+    for piece in pieces {
+        output.extend_from_slice(&le64(piece.len() as u64));
+        output.extend_from_slice(piece);
+    }
+
+    output
+}
+
+/// Little-endian 64-bit unsigned integer with the most significant bit cleared.
+fn le64(n: u64) -> [u8; 8] {
+    (n & (u64::MAX >> 1)).to_le_bytes()
+}
+
+/// Serialize the token: `header || base64url(body) || ("." || base64url(footer))`
+pub(super) fn encode_token(header: &str, body: &[u8], footer: &[u8]) -> String {
+    let mut token = format!("{header}{}", URL_SAFE_NO_PAD.encode(body));
+
+    if !footer.is_empty() {
+        token.push('.');
+        token.push_str(&URL_SAFE_NO_PAD.encode(footer));
+    }
+
+    token
+}
+
+/// Split the token into a decoded body and footer, checking the header.
+pub(super) fn decode_token(header: &str, token: &str) -> Result<(Vec<u8>, Vec<u8>)> {
+    let token = token.strip_prefix(header).ok_or(Error::InvalidToken)?;
+
+    let (body, footer) = match token.split_once('.') {
+        Some((body, footer)) => (body, b64_decode(footer)?),
+        None => (token, Vec::new()),
+    };
+
+    Ok((b64_decode(body)?, footer))
+}
+
+fn b64_decode(input: &str) -> Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(input)
+        .map_err(|_| Error::InvalidToken)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pae;
+
+    /// Test the Pre-Authentication Encoding
+    #[test]
+    fn pre_auth_encoding() {
+        assert_eq!(hex::encode(pae(&[])), "0000000000000000");
+        assert_eq!(hex::encode(pae(&[b""])), "01000000000000000000000000000000");
+        assert_eq!(
+            hex::encode(pae(&[b"test"])),
+            "0100000000000000040000000000000074657374"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use rand::{distributions::Alphanumeric, rngs::OsRng, Rng};
+
+use crate::{
+    sha::{Algorithm, CryptographicHash},
+    Error, Result,
+};
+
+/// Number of random characters in the API key (about 178 bits of entropy)
+const RANDOM_LEN: usize = 30;
+/// Number of checksum characters in the API key
+const CHECKSUM_LEN: usize = 6;
+/// Characters of the base62 alphabet
+const BASE62_ALPHABET: &[u8; 62] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Generate an API key in the `<prefix>_<random><checksum>` format.
+///
+/// The random part is 30 alphanumeric characters and the checksum is 6 characters, so typos
+/// and made-up keys can be rejected with [verify_api_key] without a database lookup.
+///
+/// ```
+/// use crypto_utils::random;
+///
+/// let api_key: String = random::api_key("sk_live");
+///
+/// assert!(api_key.starts_with("sk_live_"));
+/// ```
+pub fn api_key(prefix: &str) -> String {
+    let random: String = OsRng
+        .sample_iter(&Alphanumeric)
+        .take(RANDOM_LEN)
+        .map(char::from)
+        .collect();
+
+    let body = format!("{prefix}_{random}");
+    let checksum = checksum(&body);
+
+    body + &checksum
+}
+
+/// Verify the prefix and checksum of an API key generated with [api_key].
+///
+/// Returns [Error::InvalidKey] if the key is malformed. It doesn't prove that the key was
+/// issued, only that it is well-formed.
+///
+/// ```
+/// use crypto_utils::random;
+///
+/// let api_key = random::api_key("sk");
+///
+/// // valid key
+/// random::verify_api_key("sk", &api_key).unwrap();
+///
+/// // key with a typo
+/// let typo = api_key.replacen("sk_", "sk_x", 1);
+/// assert!(random::verify_api_key("sk", &typo).is_err());
+/// ```
+pub fn verify_api_key(prefix: &str, api_key: &str) -> Result<()> {
+    let random = api_key
+        .strip_prefix(prefix)
+        .and_then(|key| key.strip_prefix('_'))
+        .ok_or(Error::InvalidKey)?;
+
+    if random.len() != RANDOM_LEN + CHECKSUM_LEN
+        || !random.bytes().all(|b| b.is_ascii_alphanumeric())
+    {
+        return Err(Error::InvalidKey);
+    }
+
+    let (body, checksum_str) = api_key.split_at(api_key.len() - CHECKSUM_LEN);
+
+    if checksum(body) != checksum_str {
+        return Err(Error::InvalidKey);
+    }
+
+    Ok(())
+}
+
+/// First 32 bits of the SHA-256 hash encoded as 6 base62 characters.
+fn checksum(body: &str) -> String {
+    let hash = CryptographicHash::hash(Algorithm::SHA256, body.as_bytes());
+
+    let mut value = u32::from_be_bytes(hash[..4].try_into().unwrap());
+    let mut checksum = [b'0'; CHECKSUM_LEN];
+
+    for c in checksum.iter_mut().rev() {
+        *c = BASE62_ALPHABET[(value % 62) as usize];
+        value /= 62;
+    }
+
+    String::from_utf8(checksum.to_vec()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{api_key, verify_api_key, Error};
+
+    /// Generate and verify API keys
+    #[test]
+    fn api_key_verify() {
+        let key = api_key("sk_test");
+
+        assert_eq!(key.len(), "sk_test_".len() + 36);
+
+        verify_api_key("sk_test", &key).unwrap();
+    }
+
+    /// Verify API key with other prefix
+    #[test]
+    fn api_key_prefix_invalid() {
+        let key = api_key("sk_test");
+
+        let err = verify_api_key("sk_live", &key).unwrap_err();
+
+        assert!(matches!(err, Error::InvalidKey));
+    }
+
+    /// Verify API key with a modified character
+    #[test]
+    fn api_key_checksum_invalid() {
+        let key = api_key("pk");
+
+        // change the first random character
+        let mut chars: Vec<char> = key.chars().collect();
+        chars[3] = if chars[3].is_ascii_digit() {
+            ((chars[3] as u8 - b'0' + 1) % 10 + b'0') as char
+        } else if chars[3].is_ascii_uppercase() {
+            chars[3].to_ascii_lowercase()
+        } else {
+            chars[3].to_ascii_uppercase()
+        };
+        let modified: String = chars.into_iter().collect();
+
+        let err = verify_api_key("pk", &modified).unwrap_err();
+
+        assert!(matches!(err, Error::InvalidKey));
+    }
+}
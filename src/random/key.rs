@@ -0,0 +1,122 @@
+use rand::{rngs::OsRng, RngCore};
+
+use crate::{secret::SecretKey, sha::AlgorithmMac};
+
+/// Characters of the URL-safe alphabet (RFC 4648)
+const URL_SAFE_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Generate `len` random bytes.
+///
+/// ```
+/// use crypto_utils::random;
+///
+/// let bytes: Vec<u8> = random::bytes(16);
+///
+/// assert_eq!(bytes.len(), 16);
+/// ```
+pub fn bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+
+    bytes
+}
+
+/// Generate a fixed-size array of random bytes, for the non-secret values like UUIDs.
+pub(super) fn array<const N: usize>() -> [u8; N] {
+    let mut array = [0u8; N];
+    OsRng.fill_bytes(&mut array);
+
+    array
+}
+
+/// Generate a random key of `len` bytes, zeroized on drop.
+///
+/// ```
+/// use crypto_utils::{random, secret::SecretKey};
+///
+/// // 256-bit key
+/// let key: SecretKey = random::key(32);
+///
+/// assert_eq!(key.len(), 32);
+/// ```
+pub fn key(len: usize) -> SecretKey {
+    SecretKey::generate(len)
+}
+
+/// Generate a random key for the HMAC algorithm, as long as the hash it computes.
+///
+/// ```
+/// use crypto_utils::{random, sha::{AlgorithmMac, CryptographicMac}};
+///
+/// let key = random::mac_key(AlgorithmMac::HmacSHA512);
+///
+/// let mut hasher = CryptographicMac::new(AlgorithmMac::HmacSHA512, &key).unwrap();
+///
+/// assert_eq!(key.len(), 64);
+/// ```
+pub fn mac_key(algo: AlgorithmMac) -> SecretKey {
+    key(algo.output_len())
+}
+
+/// Generate a random string of `len` URL-safe characters (`A-Z`, `a-z`, `0-9`, `-` and `_`).
+///
+/// Every character holds 6 bits of entropy.
+///
+/// ```
+/// use crypto_utils::random;
+///
+/// let string: String = random::string(32);
+///
+/// assert_eq!(string.len(), 32);
+/// ```
+pub fn string(len: usize) -> String {
+    bytes(len)
+        .into_iter()
+        // the alphabet has 64 characters, so the lower 6 bits are uniformly distributed
+        .map(|byte| URL_SAFE_ALPHABET[(byte & 0x3f) as usize] as char)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bytes, key, mac_key, string};
+    use crate::sha::AlgorithmMac;
+
+    /// Generate keys of the requested length
+    #[test]
+    fn key_length() {
+        assert_eq!(bytes(0).len(), 0);
+        assert_eq!(bytes(100).len(), 100);
+        assert_eq!(key(16).len(), 16);
+
+        assert_eq!(mac_key(AlgorithmMac::HmacSHA1).len(), 20);
+        assert_eq!(mac_key(AlgorithmMac::HmacSHA256).len(), 32);
+        assert_eq!(mac_key(AlgorithmMac::HmacSHA512).len(), 64);
+    }
+
+    /// Generated keys are different
+    #[test]
+    fn key_unique() {
+        assert_ne!(key(32), key(32));
+    }
+
+    /// Generated keys are never printed
+    #[test]
+    fn key_redacted() {
+        let key = mac_key(AlgorithmMac::HmacSHA256);
+
+        assert_eq!(format!("{key:?}"), "SecretKey([REDACTED])");
+    }
+
+    /// Generate URL-safe strings
+    #[test]
+    fn string_url_safe() {
+        let string = string(1000);
+
+        assert_eq!(string.len(), 1000);
+        assert!(string
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    }
+}
@@ -0,0 +1,50 @@
+//! Module for generating random keys, strings, API keys and UUIDs.
+//!
+//! Everything is generated with the operating system's CSPRNG.
+//!
+//! ## Keys
+//!
+//! ```
+//! use crypto_utils::{
+//!     jsonwebtoken::{Claims, Token},
+//!     random,
+//!     secret::SecretKey,
+//!     sha::{AlgorithmMac, CryptographicMac},
+//! };
+//!
+//! // key sized for the HMAC algorithm, zeroized on drop
+//! let key: SecretKey = random::mac_key(AlgorithmMac::HmacSHA256);
+//! let mac = CryptographicMac::hash(AlgorithmMac::HmacSHA256, &key, b"input").unwrap();
+//!
+//! // secret for the Json Web Token (HS256)
+//! let secret = random::mac_key(AlgorithmMac::HmacSHA256);
+//! let token = Token::new(&secret, Claims::new("user_id_1234", 24)).unwrap();
+//!
+//! // fixed-size key, e.g. for the AEAD ciphers
+//! let key: SecretKey = random::key(32);
+//! ```
+//!
+//! ## Strings and identifiers
+//!
+//! ```
+//! use crypto_utils::random;
+//!
+//! // URL-safe random string
+//! let state: String = random::string(32);
+//!
+//! // API key with a prefix and checksum
+//! let api_key: String = random::api_key("sk");
+//! random::verify_api_key("sk", &api_key).unwrap();
+//!
+//! // UUIDs, e.g. for the `jti` claim
+//! let jti = random::uuid_v4().to_string();
+//! let id = random::uuid_v7();
+//! ```
+
+mod api_key;
+mod key;
+mod uuid;
+
+pub use self::uuid::*;
+pub use api_key::*;
+pub use key::*;
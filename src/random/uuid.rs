@@ -0,0 +1,64 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use uuid::Builder;
+pub use uuid::Uuid;
+
+use super::key::array;
+
+/// Generate a random UUID (version 4).
+///
+/// ```
+/// use crypto_utils::random;
+///
+/// let uuid = random::uuid_v4();
+///
+/// assert_eq!(uuid.get_version_num(), 4);
+/// ```
+pub fn uuid_v4() -> Uuid {
+    Builder::from_random_bytes(array()).into_uuid()
+}
+
+/// Generate a time-ordered UUID (version 7), sortable by the creation time.
+///
+/// ```
+/// use crypto_utils::random;
+///
+/// let uuid = random::uuid_v7();
+///
+/// assert_eq!(uuid.get_version_num(), 7);
+/// ```
+pub fn uuid_v7() -> Uuid {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+
+    Builder::from_unix_timestamp_millis(millis, &array()).into_uuid()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::{uuid_v4, uuid_v7};
+
+    /// Generate UUIDs v4
+    #[test]
+    fn uuid_v4_random() {
+        let uuid = uuid_v4();
+
+        assert_eq!(uuid.get_version_num(), 4);
+        assert_ne!(uuid, uuid_v4());
+    }
+
+    /// UUIDs v7 are ordered by the creation time
+    #[test]
+    fn uuid_v7_ordered() {
+        let first = uuid_v7();
+        thread::sleep(Duration::from_millis(2));
+        let second = uuid_v7();
+
+        assert_eq!(first.get_version_num(), 7);
+        assert!(first < second);
+    }
+}
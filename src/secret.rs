@@ -0,0 +1,334 @@
+//! Module for keeping secret keys in memory.
+//!
+//! [SecretKey] is wiped from memory when dropped, is never printed by [Debug](std::fmt::Debug)
+//! and is compared in constant time. Every keyed function in the crate accepts it.
+//!
+//! ```
+//! use crypto_utils::{
+//!     secret::{KeyEncoding, SecretKey},
+//!     sha::{AlgorithmMac, CryptographicMac},
+//! };
+//!
+//! # std::env::set_var("HMAC_KEY", "c2VjcmV0");
+//! // load key from the environment variable
+//! let key = SecretKey::from_env("HMAC_KEY", KeyEncoding::Base64).unwrap();
+//!
+//! // use key
+//! let hash = CryptographicMac::hash(AlgorithmMac::HmacSHA256, &key, b"input").unwrap();
+//!
+//! // the key is never printed
+//! assert_eq!(format!("{key:?}"), "SecretKey([REDACTED])");
+//! ```
+
+use std::{env, fmt, fs, path::Path};
+
+use base64::{
+    engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD},
+    Engine,
+};
+use rand::{rngs::OsRng, RngCore};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+
+use crate::{Error, Result};
+
+/// Encoding of a key stored as text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyEncoding {
+    /// Bytes of the text are the key
+    Raw,
+    /// Hex encoded key
+    Hex,
+    /// Base64 encoded key (standard or URL-safe alphabet, with or without padding)
+    Base64,
+}
+
+impl KeyEncoding {
+    fn decode(&self, text: &[u8]) -> Result<SecretKey> {
+        match self {
+            Self::Raw => Ok(SecretKey::new(text.to_vec())),
+            Self::Hex => {
+                let text = std::str::from_utf8(text).map_err(|_| Error::InvalidKey)?;
+                SecretKey::from_hex(text)
+            }
+            Self::Base64 => {
+                let text = std::str::from_utf8(text).map_err(|_| Error::InvalidKey)?;
+                SecretKey::from_base64(text)
+            }
+        }
+    }
+}
+
+/// Secret key zeroized on drop.
+///
+/// ```
+/// use crypto_utils::secret::SecretKey;
+///
+/// // generate a new key
+/// let key = SecretKey::generate(32);
+///
+/// // import key
+/// let key = SecretKey::from_hex("736563726574").unwrap();
+///
+/// assert_eq!(key.expose_secret(), b"secret");
+/// ```
+#[derive(Clone)]
+pub struct SecretKey(Vec<u8>);
+
+impl SecretKey {
+    /// Create a secret key from bytes, the bytes are moved and not copied.
+    ///
+    /// ```no_run
+    /// use crypto_utils::secret::SecretKey;
+    ///
+    /// let key = SecretKey::new(b"secret".to_vec());
+    /// ```
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    /// Generate a new random key of `len` bytes.
+    ///
+    /// ```no_run
+    /// use crypto_utils::secret::SecretKey;
+    ///
+    /// let key = SecretKey::generate(32);
+    /// ```
+    pub fn generate(len: usize) -> Self {
+        let mut bytes = vec![0u8; len];
+        OsRng.fill_bytes(&mut bytes);
+
+        Self(bytes)
+    }
+
+    /// Decode a hex encoded key, surrounding whitespace is ignored.
+    ///
+    /// ```no_run
+    /// use crypto_utils::secret::SecretKey;
+    ///
+    /// let key = SecretKey::from_hex("736563726574").unwrap();
+    /// ```
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let hex = hex.trim().as_bytes();
+
+        if !hex.len().is_multiple_of(2) {
+            return Err(Error::InvalidKey);
+        }
+
+        let mut key = Self(Vec::with_capacity(hex.len() / 2));
+
+        for pair in hex.chunks(2) {
+            let high = hex_value(pair[0]).ok_or(Error::InvalidKey)?;
+            let low = hex_value(pair[1]).ok_or(Error::InvalidKey)?;
+
+            key.0.push(high << 4 | low);
+        }
+
+        Ok(key)
+    }
+
+    /// Decode a base64 encoded key, surrounding whitespace is ignored.
+    ///
+    /// ```no_run
+    /// use crypto_utils::secret::SecretKey;
+    ///
+    /// let key = SecretKey::from_base64("c2VjcmV0").unwrap();
+    /// ```
+    pub fn from_base64(base64: &str) -> Result<Self> {
+        let base64 = base64.trim();
+
+        [STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD]
+            .iter()
+            .find_map(|engine| engine.decode(base64).ok())
+            .map(Self)
+            .ok_or(Error::InvalidKey)
+    }
+
+    /// Load a key from the environment variable.
+    ///
+    /// Returns [Error::MissingKey] if the variable is not set.
+    ///
+    /// ```no_run
+    /// use crypto_utils::secret::{KeyEncoding, SecretKey};
+    ///
+    /// let key = SecretKey::from_env("JWT_SECRET", KeyEncoding::Hex).unwrap();
+    /// ```
+    pub fn from_env(name: &str, encoding: KeyEncoding) -> Result<Self> {
+        let mut value = env::var(name).map_err(|_| Error::MissingKey(name.to_string()))?;
+
+        let key = encoding.decode(value.as_bytes());
+        value.zeroize();
+
+        key
+    }
+
+    /// Load a key from the file, e.g. a Docker or Kubernetes secret.
+    ///
+    /// Trailing newline is ignored for the [KeyEncoding::Hex] and [KeyEncoding::Base64] encodings.
+    ///
+    /// ```no_run
+    /// use crypto_utils::secret::{KeyEncoding, SecretKey};
+    ///
+    /// let key = SecretKey::from_file("/run/secrets/jwt_secret", KeyEncoding::Base64).unwrap();
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>, encoding: KeyEncoding) -> Result<Self> {
+        let mut content = fs::read(path)?;
+
+        let key = encoding.decode(&content);
+        content.zeroize();
+
+        key
+    }
+
+    /// Bytes of the key
+    pub fn expose_secret(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Length of the key in bytes
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the key is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+fn hex_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl AsRef<[u8]> for SecretKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for SecretKey {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl From<&[u8]> for SecretKey {
+    fn from(bytes: &[u8]) -> Self {
+        Self::new(bytes.to_vec())
+    }
+}
+
+/// Compare keys in constant time
+impl PartialEq for SecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+}
+
+impl Eq for SecretKey {}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretKey([REDACTED])")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::{Error, KeyEncoding, SecretKey};
+
+    /// Decode hex and base64 encoded keys
+    #[test]
+    fn decode_key() {
+        let key = SecretKey::from_hex("736563726574\n").unwrap();
+        assert_eq!(key.expose_secret(), b"secret");
+
+        let key = SecretKey::from_hex("7365637265744B").unwrap();
+        assert_eq!(key.expose_secret(), b"secretK");
+
+        // standard and URL-safe alphabet, with and without padding
+        for base64 in ["/+/+", "_-_-", "c2VjcmV0Kw==", "c2VjcmV0Kw"] {
+            SecretKey::from_base64(base64).unwrap();
+        }
+
+        let err = SecretKey::from_hex("7g").unwrap_err();
+        assert!(matches!(err, Error::InvalidKey));
+
+        let err = SecretKey::from_hex("736").unwrap_err();
+        assert!(matches!(err, Error::InvalidKey));
+
+        let err = SecretKey::from_base64("c2Vj*").unwrap_err();
+        assert!(matches!(err, Error::InvalidKey));
+    }
+
+    /// Load key from the environment variable
+    #[test]
+    fn from_env() {
+        std::env::set_var("CRYPTO_UTILS_TEST_KEY", "736563726574");
+
+        let key = SecretKey::from_env("CRYPTO_UTILS_TEST_KEY", KeyEncoding::Hex).unwrap();
+        assert_eq!(key.expose_secret(), b"secret");
+
+        let key = SecretKey::from_env("CRYPTO_UTILS_TEST_KEY", KeyEncoding::Raw).unwrap();
+        assert_eq!(key.expose_secret(), b"736563726574");
+
+        let err = SecretKey::from_env("CRYPTO_UTILS_MISSING_KEY", KeyEncoding::Raw).unwrap_err();
+        assert!(matches!(err, Error::MissingKey(name) if name == "CRYPTO_UTILS_MISSING_KEY"));
+    }
+
+    /// Load key from the file
+    #[test]
+    fn from_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "c2VjcmV0").unwrap();
+
+        let key = SecretKey::from_file(file.path(), KeyEncoding::Base64).unwrap();
+        assert_eq!(key.expose_secret(), b"secret");
+
+        let key = SecretKey::from_file(file.path(), KeyEncoding::Raw).unwrap();
+        assert_eq!(key.expose_secret(), b"c2VjcmV0\n");
+
+        let err = SecretKey::from_file("/nonexistent/key", KeyEncoding::Raw).unwrap_err();
+        assert!(matches!(err, Error::Io(_)));
+    }
+
+    /// Key is redacted when printed
+    #[test]
+    fn debug_redacted() {
+        let key = SecretKey::new(b"secret".to_vec());
+
+        assert_eq!(format!("{key:?}"), "SecretKey([REDACTED])");
+    }
+
+    /// Compare keys
+    #[test]
+    fn compare() {
+        let key = SecretKey::from(&b"secret"[..]);
+
+        assert_eq!(key, SecretKey::new(b"secret".to_vec()));
+        assert_ne!(key, SecretKey::new(b"secreT".to_vec()));
+        assert_ne!(key, SecretKey::new(b"secret2".to_vec()));
+    }
+
+    /// Generate random keys
+    #[test]
+    fn generate() {
+        let key = SecretKey::generate(32);
+
+        assert_eq!(key.len(), 32);
+        assert_ne!(key, SecretKey::generate(32));
+    }
+}
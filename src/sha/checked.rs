@@ -0,0 +1,161 @@
+use std::io::{self, Write};
+
+use sha1_checked::{CollisionResult, Digest as _};
+
+use super::{Algorithm, Digest};
+use crate::{Error, Result};
+
+/// SHA-1 hasher which detects the inputs of the known collision attacks (SHAttered and
+/// SHA-1 is a Shambles), with the counter-cryptanalysis of
+/// [sha1collisiondetection](https://github.com/cr-marcstevens/sha1collisiondetection)
+/// also used by git.
+///
+/// The hash of other inputs is the same as the one of [Algorithm::SHA1], so it can replace
+/// it where SHA-1 is still required for compatibility.
+/// [CryptographicHash::new_checked](super::CryptographicHash::new_checked) creates the same
+/// hasher for the code which is generic over the algorithm.
+///
+/// ```
+/// use crypto_utils::sha::{Algorithm, CryptographicHash, Sha1Checked};
+///
+/// let hash: Vec<u8> = Sha1Checked::hash(b"P@ssw0rd").unwrap();
+///
+/// assert_eq!(hash, CryptographicHash::hash(Algorithm::SHA1, b"P@ssw0rd"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Sha1Checked {
+    hasher: sha1_checked::Sha1,
+}
+
+impl Sha1Checked {
+    /// Create a new collision detecting Sha1 hasher
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set value in the hasher
+    pub fn update(&mut self, input: &[u8]) {
+        self.hasher.update(input);
+    }
+
+    /// Compute hash
+    ///
+    /// Returns [Error::CollisionDetected] if the input contains the blocks of a collision
+    /// attack, then its SHA-1 hash can't be trusted.
+    pub fn finalize(self) -> Result<Vec<u8>> {
+        check(self.hasher.try_finalize())
+    }
+
+    /// Compute hash as a [Digest] of the [Algorithm::SHA1]
+    pub fn digest(self) -> Result<Digest> {
+        Ok(Digest::new(Algorithm::SHA1, self.finalize()?))
+    }
+
+    /// Compute hash using a single function
+    pub fn hash(input: &[u8]) -> Result<Vec<u8>> {
+        let mut hasher = Self::new();
+        hasher.update(input);
+        hasher.finalize()
+    }
+}
+
+/// Collision detecting Sha1 hasher which keeps the SHA-1 hash of a detected collision,
+/// instead of replacing it with the mitigated hash
+pub(super) fn unmitigated() -> sha1_checked::Sha1 {
+    sha1_checked::Sha1::builder().safe_hash(false).build()
+}
+
+/// Finalize the hasher created with [unmitigated] and reset it
+pub(super) fn finalize_reset(hasher: &mut sha1_checked::Sha1) -> CollisionResult {
+    std::mem::replace(hasher, unmitigated()).try_finalize()
+}
+
+/// Return the hash, unless a collision attack was detected
+pub(super) fn check(result: CollisionResult) -> Result<Vec<u8>> {
+    match result {
+        CollisionResult::Ok(hash) => Ok(hash.to_vec()),
+        CollisionResult::Mitigated(_) | CollisionResult::Collision(_) => {
+            Err(Error::CollisionDetected)
+        }
+    }
+}
+
+/// Hash data written to the hasher, e.g. with [io::copy]
+impl Write for Sha1Checked {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Sha1Checked};
+    use crate::sha::{Algorithm, CryptographicHash};
+
+    /// First message of the chosen-prefix collision of "SHA-1 is a Shambles"
+    const SHAMBLES: &[u8] = include_bytes!("testdata/sha-mbles-1.bin");
+
+    /// Compute the same hash as Sha1 for the other inputs
+    #[test]
+    fn same_as_sha1() {
+        for len in [0, 1, 55, 56, 64, 1000] {
+            let input = vec![0x5a; len];
+
+            assert_eq!(
+                Sha1Checked::hash(&input).unwrap(),
+                CryptographicHash::hash(Algorithm::SHA1, &input)
+            );
+        }
+    }
+
+    /// Detect the collision
+    #[test]
+    fn collision() {
+        // plain Sha1 doesn't notice anything
+        assert_eq!(
+            hex::encode(CryptographicHash::hash(Algorithm::SHA1, SHAMBLES)),
+            "8ac60ba76f1999a1ab70223f225aefdc78d4ddc0"
+        );
+
+        let err = Sha1Checked::hash(SHAMBLES).unwrap_err();
+        assert!(matches!(err, Error::CollisionDetected));
+
+        // streamed in chunks
+        let mut hasher = Sha1Checked::new();
+        SHAMBLES.chunks(7).for_each(|chunk| hasher.update(chunk));
+        assert!(matches!(hasher.digest(), Err(Error::CollisionDetected)));
+    }
+
+    /// Detect the collision with a checked CryptographicHash
+    #[test]
+    fn cryptographic_hash() {
+        let mut hasher = CryptographicHash::new_checked(Algorithm::SHA1);
+        assert_eq!(hasher.algorithm(), Algorithm::SHA1);
+
+        hasher.update(SHAMBLES);
+        assert!(matches!(hasher.try_digest(), Err(Error::CollisionDetected)));
+
+        // the infallible finalize returns the SHA-1 hash, not the mitigated one
+        hasher.update(SHAMBLES);
+        assert_eq!(
+            hasher.finalize(),
+            CryptographicHash::hash(Algorithm::SHA1, SHAMBLES)
+        );
+
+        // the hasher is reset, other inputs have the same hash as Sha1
+        hasher.update(b"input");
+        assert_eq!(
+            hasher.try_finalize().unwrap(),
+            CryptographicHash::hash(Algorithm::SHA1, b"input")
+        );
+
+        // the other algorithms don't detect collisions
+        let hasher = CryptographicHash::new_checked(Algorithm::SHA256);
+        assert!(matches!(hasher, CryptographicHash::Sha256(_)));
+    }
+}
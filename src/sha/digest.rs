@@ -0,0 +1,62 @@
+use std::fmt;
+
+use super::Algorithm;
+
+/// Hash computed by [CryptographicHash](super::CryptographicHash) together with its algorithm.
+///
+/// ```
+/// use crypto_utils::sha::{Algorithm, CryptographicHash, Digest};
+///
+/// let mut hasher = CryptographicHash::new(Algorithm::SHA1);
+/// hasher.update(b"P@ssw0rd");
+///
+/// // compute hash
+/// let digest: Digest = hasher.digest();
+///
+/// assert_eq!(digest.algorithm(), Algorithm::SHA1);
+/// assert_eq!(digest.to_string(), "21bd12dc183f740ee76f27b78eb39c8ad972a757");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Digest {
+    algorithm: Algorithm,
+    bytes: Vec<u8>,
+}
+
+impl Digest {
+    /// Create a digest from the hash bytes computed with the algorithm.
+    pub fn new(algorithm: Algorithm, bytes: Vec<u8>) -> Self {
+        Self { algorithm, bytes }
+    }
+
+    /// Algorithm used to compute the hash
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Hash bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Convert into the hash bytes
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl AsRef<[u8]> for Digest {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Format the hash as a lowercase hex string
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.bytes {
+            write!(f, "{byte:02x}")?;
+        }
+
+        Ok(())
+    }
+}
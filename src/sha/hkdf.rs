@@ -0,0 +1,138 @@
+use super::{AlgorithmMac, CryptographicMac};
+use crate::{Error, Result};
+
+/// Derive a key from the input keying material using HKDF ([RFC 5869](https://www.rfc-editor.org/rfc/rfc5869)).
+///
+/// ```
+/// use crypto_utils::sha::{hkdf, AlgorithmMac};
+///
+/// // derive a 32-byte key
+/// let key: Vec<u8> = hkdf(AlgorithmMac::HmacSHA256, b"salt", b"input key", b"encryption", 32).unwrap();
+///
+/// assert_eq!(key.len(), 32);
+/// ```
+pub fn hkdf(
+    algo: AlgorithmMac,
+    salt: impl AsRef<[u8]>,
+    ikm: impl AsRef<[u8]>,
+    info: &[u8],
+    len: usize,
+) -> Result<Vec<u8>> {
+    let prk = hkdf_extract(algo, salt, ikm)?;
+
+    hkdf_expand(algo, &prk, info, len)
+}
+
+/// HKDF-Extract, computes a pseudorandom key from the input keying material.
+///
+/// An empty salt is the same as a salt of zeros.
+///
+/// ```no_run
+/// use crypto_utils::sha::{hkdf_extract, AlgorithmMac};
+///
+/// let prk: Vec<u8> = hkdf_extract(AlgorithmMac::HmacSHA256, b"salt", b"input key").unwrap();
+/// ```
+pub fn hkdf_extract(
+    algo: AlgorithmMac,
+    salt: impl AsRef<[u8]>,
+    ikm: impl AsRef<[u8]>,
+) -> Result<Vec<u8>> {
+    CryptographicMac::hash(algo, salt, ikm.as_ref())
+}
+
+/// HKDF-Expand, expands the pseudorandom key to `len` bytes of output keying material.
+///
+/// Returns [Error::InvalidKeyLength] if `len` is greater than 255 hashes.
+///
+/// ```no_run
+/// use crypto_utils::sha::{hkdf_expand, AlgorithmMac};
+///
+/// # let prk = [0u8; 32];
+/// let okm: Vec<u8> = hkdf_expand(AlgorithmMac::HmacSHA256, &prk, b"encryption", 32).unwrap();
+/// ```
+pub fn hkdf_expand(
+    algo: AlgorithmMac,
+    prk: impl AsRef<[u8]>,
+    info: &[u8],
+    len: usize,
+) -> Result<Vec<u8>> {
+    let prk = prk.as_ref();
+
+    if len > 255 * algo.output_len() {
+        return Err(Error::InvalidKeyLength);
+    }
+
+    let mut okm = Vec::with_capacity(len);
+    let mut block = Vec::new();
+
+    for counter in 1..=len.div_ceil(algo.output_len()) as u8 {
+        // T(i) = HMAC(PRK, T(i - 1) || info || i)
+        let mut hasher = CryptographicMac::new(algo, prk)?;
+        hasher.update(&block);
+        hasher.update(info);
+        hasher.update(&[counter]);
+
+        block = hasher.finalize();
+        okm.extend_from_slice(&block);
+    }
+
+    okm.truncate(len);
+
+    Ok(okm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hkdf, hkdf_expand, hkdf_extract, AlgorithmMac, Error};
+
+    /// RFC 5869 test case 1 (HMAC Sha256)
+    #[test]
+    fn hkdf_sha256_rfc5869() {
+        let ikm = [0x0b; 22];
+        let salt = hex::decode("000102030405060708090a0b0c").unwrap();
+        let info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
+
+        // extract
+        let prk = hkdf_extract(AlgorithmMac::HmacSHA256, &salt, ikm).unwrap();
+
+        assert_eq!(
+            hex::encode(&prk),
+            "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"
+        );
+
+        // expand
+        let okm = hkdf_expand(AlgorithmMac::HmacSHA256, &prk, &info, 42).unwrap();
+
+        assert_eq!(
+            hex::encode(&okm),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+
+        // extract and expand
+        assert_eq!(
+            hkdf(AlgorithmMac::HmacSHA256, &salt, ikm, &info, 42).unwrap(),
+            okm
+        );
+    }
+
+    /// RFC 5869 test case 7 (HMAC Sha1, no salt and info)
+    #[test]
+    fn hkdf_sha1_rfc5869() {
+        let ikm = [0x0c; 22];
+
+        let okm = hkdf(AlgorithmMac::HmacSHA1, b"", ikm, b"", 42).unwrap();
+
+        assert_eq!(
+            hex::encode(okm),
+            "2c91117204d745f3500d636a62f64f0ab3bae548aa53d423b0d1f27ebba6f5e5673a081d70cce7acfc48"
+        );
+    }
+
+    /// Expand to more than 255 hashes
+    #[test]
+    fn hkdf_expand_too_long() {
+        let err = hkdf_expand(AlgorithmMac::HmacSHA256, [0u8; 32], b"", 255 * 32 + 1).unwrap_err();
+
+        assert!(matches!(err, Error::InvalidKeyLength));
+    }
+}
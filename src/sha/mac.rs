@@ -1,10 +1,13 @@
+use std::io::{self, Write};
+
 use hmac::{Hmac, Mac};
 use sha1::Sha1;
 use sha2::{Sha256, Sha512};
 
-use super::{Error, Result};
+use crate::{Error, Result};
 
 /// HMAC hashing algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AlgorithmMac {
     /// Read about HMAC in [wikipedia](https://en.wikipedia.org/wiki/HMAC)
     HmacSHA1,
@@ -14,6 +17,17 @@ pub enum AlgorithmMac {
     HmacSHA512,
 }
 
+impl AlgorithmMac {
+    /// Length of the computed hash in bytes
+    pub fn output_len(&self) -> usize {
+        match self {
+            Self::HmacSHA1 => 20,
+            Self::HmacSHA256 => 32,
+            Self::HmacSHA512 => 64,
+        }
+    }
+}
+
 /// Compute cryptographic hash from bytes (HMAC Sha1, HMAC Sha256, HMAC Sha512).
 ///
 /// ## Method 1 (recommend)
@@ -47,6 +61,7 @@ pub enum AlgorithmMac {
 ///
 /// # assert_eq!(hash, "30440f36ddc2809bbd4c8b1f37a6e80d7588c303".to_string())
 /// ```
+#[derive(Clone)]
 pub enum CryptographicMac {
     /// HMAC Sha1 hasher
     HmacSha1(Hmac<Sha1>),
@@ -71,16 +86,18 @@ impl CryptographicMac {
     /// // Hmac Sha512
     /// let mut hasher = CryptographicMac::new(AlgorithmMac::HmacSHA512, b"secret").unwrap();
     /// ```
-    pub fn new(algo: AlgorithmMac, key: &[u8]) -> Result<Self> {
+    pub fn new(algo: AlgorithmMac, key: impl AsRef<[u8]>) -> Result<Self> {
+        let key = key.as_ref();
+
         Ok(match algo {
-            AlgorithmMac::HmacSHA1 => {
-                Self::HmacSha1(Hmac::<Sha1>::new_from_slice(key).map_err(|_| Error::InvalidKey)?)
-            }
+            AlgorithmMac::HmacSHA1 => Self::HmacSha1(
+                Hmac::<Sha1>::new_from_slice(key).map_err(|_| Error::InvalidKeyLength)?,
+            ),
             AlgorithmMac::HmacSHA256 => Self::HmacSha256(
-                Hmac::<Sha256>::new_from_slice(key).map_err(|_| Error::InvalidKey)?,
+                Hmac::<Sha256>::new_from_slice(key).map_err(|_| Error::InvalidKeyLength)?,
             ),
             AlgorithmMac::HmacSHA512 => Self::HmacSha512(
-                Hmac::<Sha512>::new_from_slice(key).map_err(|_| Error::InvalidKey)?,
+                Hmac::<Sha512>::new_from_slice(key).map_err(|_| Error::InvalidKeyLength)?,
             ),
         })
     }
@@ -127,6 +144,31 @@ impl CryptographicMac {
         }
     }
 
+    /// Verify the computed hash against the expected one in constant time
+    ///
+    /// ```
+    /// use crypto_utils::sha::{AlgorithmMac, CryptographicMac};
+    ///
+    /// let expected = hex::decode("30440f36ddc2809bbd4c8b1f37a6e80d7588c303").unwrap();
+    ///
+    /// let mut hasher = CryptographicMac::new(AlgorithmMac::HmacSHA1, b"secret").unwrap();
+    /// hasher.update(b"input");
+    ///
+    /// // verify hash
+    /// hasher.verify(&expected).unwrap();
+    /// ```
+    pub fn verify(self, expected: &[u8]) -> Result<()> {
+        match self {
+            // Sha1
+            Self::HmacSha1(sha1) => sha1.verify_slice(expected),
+            // Sha256
+            Self::HmacSha256(sha256) => sha256.verify_slice(expected),
+            // Sha512
+            Self::HmacSha512(sha512) => sha512.verify_slice(expected),
+        }
+        .map_err(|_| Error::MacMismatch)
+    }
+
     /// Compute hash using a single function
     ///
     /// ```
@@ -139,7 +181,7 @@ impl CryptographicMac {
     ///
     /// # assert_eq!(hash, "20bbb9ec2d4574845911b13695b776097bd46e41".to_string())
     /// ```
-    pub fn hash(algo: AlgorithmMac, secret: &[u8], input: &[u8]) -> Result<Vec<u8>> {
+    pub fn hash(algo: AlgorithmMac, secret: impl AsRef<[u8]>, input: &[u8]) -> Result<Vec<u8>> {
         // create hasher
         let mut hasher = Self::new(algo, secret)?;
 
@@ -151,9 +193,34 @@ impl CryptographicMac {
     }
 }
 
+/// Hash data written to the hasher, e.g. with [io::copy]
+///
+/// ```
+/// use crypto_utils::sha::{AlgorithmMac, CryptographicMac};
+///
+/// let mut hasher = CryptographicMac::new(AlgorithmMac::HmacSHA1, b"secret").unwrap();
+///
+/// // stream data to the hasher
+/// std::io::copy(&mut &b"input"[..], &mut hasher).unwrap();
+///
+/// let hash: String = hex::encode(hasher.finalize());
+///
+/// # assert_eq!(hash, "30440f36ddc2809bbd4c8b1f37a6e80d7588c303".to_string())
+/// ```
+impl Write for CryptographicMac {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{AlgorithmMac, CryptographicMac};
+    use super::{AlgorithmMac, CryptographicMac, Error};
 
     const SECRET: &[u8] = b"secret";
     const INPUT: &[u8] = b"input";
@@ -191,6 +258,37 @@ mod tests {
         assert_eq!(hash, EXPECTED_HMAC_SHA256.to_string())
     }
 
+    /// Verify a HMAC Sha256 hash
+    #[test]
+    fn hmac_sha256_verify() {
+        let expected = hex::decode(EXPECTED_HMAC_SHA256).unwrap();
+
+        // verify valid hash
+        let mut hasher = CryptographicMac::new(AlgorithmMac::HmacSHA256, SECRET).unwrap();
+        hasher.update(INPUT);
+        hasher.verify(&expected).unwrap();
+
+        // verify hash of other input
+        let mut hasher = CryptographicMac::new(AlgorithmMac::HmacSHA256, SECRET).unwrap();
+        hasher.update(b"other input");
+        let err = hasher.verify(&expected).unwrap_err();
+
+        assert!(matches!(err, Error::MacMismatch));
+    }
+
+    /// Compute a HMAC Sha256 hash using the SecretKey
+    #[cfg(feature = "secret")]
+    #[test]
+    fn hmac_sha256_secret_key() {
+        let key = crate::secret::SecretKey::new(SECRET.to_vec());
+
+        // compute hash
+        let hash_bytes = CryptographicMac::hash(AlgorithmMac::HmacSHA256, &key, INPUT).unwrap();
+
+        // validate hash
+        assert_eq!(hex::encode(hash_bytes), EXPECTED_HMAC_SHA256)
+    }
+
     /// Test a HMAC Sha512 hasher
     #[test]
     fn hmac_sha512() {
@@ -1,8 +1,8 @@
-//! Module for creating sha1, sha256 and sha512 hashes.
+//! Module for creating sha1, sha256, sha384 and sha512 hashes.
 //!
 //! ## Sha
 //!
-//! Example of computing a sha1, sha256 and sha512 hashes:
+//! Example of computing a sha1, sha256, sha384 and sha512 hashes:
 //!
 //! ```no_run
 //! use crypto_utils::sha::{Algorithm, CryptographicHash};
@@ -13,10 +13,26 @@
 //! // Sha256
 //! let hash: Vec<u8> = CryptographicHash::hash(Algorithm::SHA256, b"input");
 //!
+//! // Sha384
+//! let hash: Vec<u8> = CryptographicHash::hash(Algorithm::SHA384, b"input");
+//!
 //! // Sha512
 //! let hash: Vec<u8> = CryptographicHash::hash(Algorithm::SHA512, b"input");
 //! ```
 //!
+//! ## Resumable hashing
+//!
+//! `ResumableHash` and `ResumableMac` compute the same hashes, but their state can be
+//! exported in the middle of a stream and imported e.g. after a restart of the process.
+//! They require the `resumable` feature.
+//!
+//! ## Sha1 with collision detection
+//!
+//! `Sha1Checked` and the hashers of `CryptographicHash::new_checked` compute the same hashes
+//! as [Algorithm::SHA1], but `try_finalize` rejects the inputs of the known SHA-1 collision
+//! attacks (e.g. the SHAttered PDFs) with `Error::CollisionDetected`.
+//! They require the `sha1dc` feature.
+//!
 //! ## HMAC-Sha
 //!
 //! Read about HMAC in [wikipedia](https://en.wikipedia.org/wiki/HMAC)
@@ -38,12 +54,33 @@
 //! // HMAC Sha512
 //! let hash: Vec<u8> = CryptographicMac::hash(AlgorithmMac::HmacSHA512, SECRET, b"input").unwrap();
 //! ```
+//!
+//! ## HKDF
+//!
+//! Derive keys from a secret with HKDF ([RFC 5869](https://www.rfc-editor.org/rfc/rfc5869)):
+//!
+//! ```no_run
+//! use crypto_utils::sha::{hkdf, AlgorithmMac};
+//!
+//! let key: Vec<u8> = hkdf(AlgorithmMac::HmacSHA256, b"salt", b"secret", b"info", 32).unwrap();
+//! ```
 
-mod error;
+#[cfg(feature = "sha1dc")]
+mod checked;
+mod digest;
+mod hkdf;
 mod mac;
+#[cfg(feature = "resumable")]
+mod resumable;
 #[allow(clippy::module_inception)]
 mod sha;
 
-pub use error::*;
+pub use crate::error::{Error, Result};
+#[cfg(feature = "sha1dc")]
+pub use checked::*;
+pub use digest::*;
+pub use hkdf::*;
 pub use mac::*;
+#[cfg(feature = "resumable")]
+pub use resumable::*;
 pub use sha::*;
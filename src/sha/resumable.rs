@@ -0,0 +1,696 @@
+use std::{
+    io::{self, Write},
+    slice,
+};
+
+use sha2::digest::generic_array::GenericArray;
+use zeroize::{Zeroize, Zeroizing};
+
+use super::{Algorithm, AlgorithmMac, Digest};
+use crate::{Error, Result};
+
+/// Version of the exported state
+const VERSION: u8 = 1;
+
+/// Kind of the exported state
+const KIND_HASH: u8 = 1;
+const KIND_HMAC: u8 = 2;
+
+const SHA1_IV: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+const SHA256_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const SHA384_IV: [u64; 8] = [
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+    0x9159015a3070dd17,
+    0x152fecd8f70e5939,
+    0x67332667ffc00b31,
+    0x8eb44a8768581511,
+    0xdb0c2e0d64f98fa7,
+    0x47b5481dbefa4fa4,
+];
+
+const SHA512_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// Chaining words of the hash function
+#[derive(Debug, Clone)]
+enum Words {
+    Sha1([u32; 5]),
+    Sha256([u32; 8]),
+    Sha512([u64; 8]),
+}
+
+/// Sha hasher whose state can be exported in the middle of a stream, e.g. to continue
+/// hashing a resumable upload after a restart of the process.
+///
+/// It computes the same hashes as [CryptographicHash](super::CryptographicHash), which
+/// should be used when the state doesn't have to be persisted.
+///
+/// ```
+/// use crypto_utils::sha::{Algorithm, CryptographicHash, ResumableHash};
+///
+/// let mut hasher = ResumableHash::new(Algorithm::SHA256);
+/// hasher.update(b"first chunk, ");
+///
+/// // persist the state, e.g. next to the partially uploaded file
+/// let state: Vec<u8> = hasher.export_state();
+///
+/// // continue hashing after a restart
+/// let mut hasher = ResumableHash::import_state(&state).unwrap();
+/// hasher.update(b"second chunk");
+///
+/// assert_eq!(
+///     hasher.finalize(),
+///     CryptographicHash::hash(Algorithm::SHA256, b"first chunk, second chunk")
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResumableHash {
+    algorithm: Algorithm,
+    words: Words,
+    /// Number of the compressed bytes
+    length: u64,
+    /// Bytes of the incomplete block
+    buffer: Vec<u8>,
+}
+
+impl ResumableHash {
+    /// Create a new resumable Sha hasher
+    pub fn new(algo: Algorithm) -> Self {
+        let words = match algo {
+            Algorithm::SHA1 => Words::Sha1(SHA1_IV),
+            Algorithm::SHA256 => Words::Sha256(SHA256_IV),
+            Algorithm::SHA384 => Words::Sha512(SHA384_IV),
+            Algorithm::SHA512 => Words::Sha512(SHA512_IV),
+        };
+
+        Self {
+            algorithm: algo,
+            words,
+            length: 0,
+            // never reallocated, so no copy of the input is left behind
+            buffer: Vec::with_capacity(block_size(algo)),
+        }
+    }
+
+    /// Create a hasher from a state exported with [ResumableHash::export_state].
+    ///
+    /// Returns [Error::InvalidState] if the state is malformed or from another version.
+    pub fn import_state(state: &[u8]) -> Result<Self> {
+        let mut input = state;
+
+        if take(&mut input, 2)? != [VERSION, KIND_HASH] {
+            return Err(Error::InvalidState);
+        }
+
+        let hasher = Self::decode(&mut input)?;
+
+        match input.is_empty() {
+            true => Ok(hasher),
+            false => Err(Error::InvalidState),
+        }
+    }
+
+    /// Export the state of the hasher, so the hashing of a stream can continue later
+    /// with [ResumableHash::import_state].
+    ///
+    /// The bytes are in an opaque versioned format of this crate, they contain the
+    /// intermediate state of the hash function and the not yet hashed part of the input.
+    pub fn export_state(&self) -> Vec<u8> {
+        let mut output = vec![VERSION, KIND_HASH];
+        self.encode(&mut output);
+
+        output
+    }
+
+    /// Set value in the hasher
+    pub fn update(&mut self, mut input: &[u8]) {
+        let block_size = block_size(self.algorithm);
+
+        // fill the incomplete block first
+        if !self.buffer.is_empty() {
+            let take = (block_size - self.buffer.len()).min(input.len());
+            self.buffer.extend_from_slice(&input[..take]);
+            input = &input[take..];
+
+            if self.buffer.len() < block_size {
+                return;
+            }
+
+            let block = std::mem::take(&mut self.buffer);
+            self.compress(&block);
+            self.buffer = block;
+            self.buffer.zeroize();
+        }
+
+        let mut blocks = input.chunks_exact(block_size);
+        for block in &mut blocks {
+            self.compress(block);
+        }
+
+        self.buffer.extend_from_slice(blocks.remainder());
+    }
+
+    /// Compute hash, the hasher is reset
+    pub fn finalize(&mut self) -> Vec<u8> {
+        let output = self.finish();
+        *self = Self::new(self.algorithm);
+
+        output
+    }
+
+    /// Compute hash as a [Digest] which also keeps the algorithm
+    pub fn digest(&mut self) -> Digest {
+        Digest::new(self.algorithm, self.finalize())
+    }
+
+    /// Algorithm of the hasher
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Compute hash using a single function
+    pub fn hash(algo: Algorithm, input: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new(algo);
+        hasher.update(input);
+        hasher.finalize()
+    }
+
+    /// Pad the message with 0x80, zeros and the length in bits, and return the hash
+    fn finish(&mut self) -> Vec<u8> {
+        let block_size = block_size(self.algorithm);
+        let length_size = block_size / 8;
+        let bits = (self.length as u128 + self.buffer.len() as u128) * 8;
+
+        let mut tail = Zeroizing::new(Vec::with_capacity(2 * block_size));
+        tail.extend_from_slice(&self.buffer);
+        tail.push(0x80);
+        let padded_len = (tail.len() + length_size).next_multiple_of(block_size) - length_size;
+        tail.resize(padded_len, 0);
+
+        match length_size {
+            8 => tail.extend_from_slice(&(bits as u64).to_be_bytes()),
+            _ => tail.extend_from_slice(&bits.to_be_bytes()),
+        }
+
+        for block in tail.chunks_exact(block_size) {
+            self.compress(block);
+        }
+
+        let mut output = match &self.words {
+            Words::Sha1(words) => words
+                .iter()
+                .flat_map(|w| w.to_be_bytes())
+                .collect::<Vec<_>>(),
+            Words::Sha256(words) => words.iter().flat_map(|w| w.to_be_bytes()).collect(),
+            Words::Sha512(words) => words.iter().flat_map(|w| w.to_be_bytes()).collect(),
+        };
+        output.truncate(self.algorithm.output_len());
+
+        output
+    }
+
+    fn compress(&mut self, block: &[u8]) {
+        match &mut self.words {
+            Words::Sha1(words) => {
+                sha1::compress(words, slice::from_ref(GenericArray::from_slice(block)))
+            }
+            Words::Sha256(words) => {
+                sha2::compress256(words, slice::from_ref(GenericArray::from_slice(block)))
+            }
+            Words::Sha512(words) => {
+                sha2::compress512(words, slice::from_ref(GenericArray::from_slice(block)))
+            }
+        }
+
+        self.length = self.length.wrapping_add(block.len() as u64);
+    }
+
+    /// Encode as: algorithm, chaining words, compressed length and the incomplete block
+    fn encode(&self, output: &mut Vec<u8>) {
+        output.push(algorithm_id(self.algorithm));
+
+        match &self.words {
+            Words::Sha1(words) => words.iter().for_each(|w| output.extend(w.to_be_bytes())),
+            Words::Sha256(words) => words.iter().for_each(|w| output.extend(w.to_be_bytes())),
+            Words::Sha512(words) => words.iter().for_each(|w| output.extend(w.to_be_bytes())),
+        }
+
+        output.extend_from_slice(&self.length.to_be_bytes());
+        output.push(self.buffer.len() as u8);
+        output.extend_from_slice(&self.buffer);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self> {
+        let algorithm = match take(input, 1)?[0] {
+            1 => Algorithm::SHA1,
+            2 => Algorithm::SHA256,
+            3 => Algorithm::SHA384,
+            4 => Algorithm::SHA512,
+            _ => return Err(Error::InvalidState),
+        };
+
+        let mut hasher = Self::new(algorithm);
+
+        match &mut hasher.words {
+            Words::Sha1(words) => {
+                for word in words.iter_mut() {
+                    *word = u32::from_be_bytes(take(input, 4)?.try_into().unwrap());
+                }
+            }
+            Words::Sha256(words) => {
+                for word in words.iter_mut() {
+                    *word = u32::from_be_bytes(take(input, 4)?.try_into().unwrap());
+                }
+            }
+            Words::Sha512(words) => {
+                for word in words.iter_mut() {
+                    *word = u64::from_be_bytes(take(input, 8)?.try_into().unwrap());
+                }
+            }
+        }
+
+        hasher.length = u64::from_be_bytes(take(input, 8)?.try_into().unwrap());
+
+        // only whole blocks are compressed and the incomplete block is never full
+        let block_size = block_size(algorithm);
+        let buffer_len = take(input, 1)?[0] as usize;
+        if !hasher.length.is_multiple_of(block_size as u64) || buffer_len >= block_size {
+            return Err(Error::InvalidState);
+        }
+
+        hasher.buffer.extend_from_slice(take(input, buffer_len)?);
+
+        Ok(hasher)
+    }
+}
+
+/// Wipe the state from memory, the states of a [ResumableMac] are derived from the key.
+impl Drop for ResumableHash {
+    fn drop(&mut self) {
+        match &mut self.words {
+            Words::Sha1(words) => words.zeroize(),
+            Words::Sha256(words) => words.zeroize(),
+            Words::Sha512(words) => words.zeroize(),
+        }
+        self.buffer.zeroize();
+    }
+}
+
+/// Hash data written to the hasher, e.g. with [io::copy]
+impl Write for ResumableHash {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// HMAC Sha hasher whose state can be exported in the middle of a stream, see
+/// [ResumableHash].
+///
+/// It computes the same hashes as [CryptographicMac](super::CryptographicMac). The padded
+/// key and the states derived from it are zeroized when dropped.
+///
+/// ```
+/// use crypto_utils::sha::{AlgorithmMac, CryptographicMac, ResumableMac};
+///
+/// let mut hasher = ResumableMac::new(AlgorithmMac::HmacSHA256, b"secret").unwrap();
+/// hasher.update(b"first chunk, ");
+///
+/// let state: Vec<u8> = hasher.export_state();
+///
+/// // continue hashing after a restart
+/// let mut hasher = ResumableMac::import_state(&state).unwrap();
+/// hasher.update(b"second chunk");
+///
+/// assert_eq!(
+///     hasher.finalize(),
+///     CryptographicMac::hash(AlgorithmMac::HmacSHA256, b"secret", b"first chunk, second chunk").unwrap()
+/// );
+/// ```
+#[derive(Clone)]
+pub struct ResumableMac {
+    algorithm: AlgorithmMac,
+    inner: ResumableHash,
+    outer: ResumableHash,
+}
+
+impl ResumableMac {
+    /// Create a new resumable HMAC Sha hasher.
+    pub fn new(algo: AlgorithmMac, key: impl AsRef<[u8]>) -> Result<Self> {
+        let key = key.as_ref();
+        let hash_algo = hash_algorithm(algo);
+        let block_size = block_size(hash_algo);
+
+        // keys longer than the block are hashed first
+        let hashed_key;
+        let key = match key.len() > block_size {
+            true => {
+                hashed_key = Zeroizing::new(ResumableHash::hash(hash_algo, key));
+                hashed_key.as_slice()
+            }
+            false => key,
+        };
+
+        let mut pad = Zeroizing::new(vec![0u8; block_size]);
+        pad[..key.len()].copy_from_slice(key);
+
+        pad.iter_mut().for_each(|b| *b ^= 0x36);
+        let mut inner = ResumableHash::new(hash_algo);
+        inner.update(&pad);
+
+        pad.iter_mut().for_each(|b| *b ^= 0x36 ^ 0x5c);
+        let mut outer = ResumableHash::new(hash_algo);
+        outer.update(&pad);
+
+        Ok(Self {
+            algorithm: algo,
+            inner,
+            outer,
+        })
+    }
+
+    /// Create a hasher from a state exported with [ResumableMac::export_state].
+    ///
+    /// Returns [Error::InvalidState] if the state is malformed or from another version.
+    pub fn import_state(state: &[u8]) -> Result<Self> {
+        let mut input = state;
+
+        if take(&mut input, 2)? != [VERSION, KIND_HMAC] {
+            return Err(Error::InvalidState);
+        }
+
+        let inner = ResumableHash::decode(&mut input)?;
+        let outer = ResumableHash::decode(&mut input)?;
+
+        // the outer state only contains the padded key
+        let block_size = block_size(inner.algorithm) as u64;
+        if !input.is_empty()
+            || inner.algorithm != outer.algorithm
+            || inner.length < block_size
+            || outer.length != block_size
+            || !outer.buffer.is_empty()
+        {
+            return Err(Error::InvalidState);
+        }
+
+        let algorithm = match inner.algorithm {
+            Algorithm::SHA1 => AlgorithmMac::HmacSHA1,
+            Algorithm::SHA256 => AlgorithmMac::HmacSHA256,
+            Algorithm::SHA512 => AlgorithmMac::HmacSHA512,
+            Algorithm::SHA384 => return Err(Error::InvalidState),
+        };
+
+        Ok(Self {
+            algorithm,
+            inner,
+            outer,
+        })
+    }
+
+    /// Export the state of the hasher, so the hashing of a stream can continue later
+    /// with [ResumableMac::import_state].
+    ///
+    /// The state is derived from the key and allows computing hashes of any input,
+    /// so it must be stored as securely as the key itself.
+    pub fn export_state(&self) -> Vec<u8> {
+        let mut output = vec![VERSION, KIND_HMAC];
+        self.inner.encode(&mut output);
+        self.outer.encode(&mut output);
+
+        output
+    }
+
+    /// Set value in the hasher
+    pub fn update(&mut self, input: &[u8]) {
+        self.inner.update(input)
+    }
+
+    /// Compute hash
+    pub fn finalize(mut self) -> Vec<u8> {
+        let inner = self.inner.finish();
+        self.outer.update(&inner);
+
+        self.outer.finish()
+    }
+
+    /// Verify the computed hash against the expected one in constant time
+    pub fn verify(self, expected: &[u8]) -> Result<()> {
+        let hash = self.finalize();
+
+        // compare all bytes, so the time doesn't depend on the first difference
+        let diff = hash
+            .iter()
+            .zip(expected)
+            .fold(0, |diff, (a, b)| diff | (a ^ b));
+
+        match hash.len() == expected.len() && diff == 0 {
+            true => Ok(()),
+            false => Err(Error::MacMismatch),
+        }
+    }
+
+    /// Algorithm of the hasher
+    pub fn algorithm(&self) -> AlgorithmMac {
+        self.algorithm
+    }
+}
+
+/// Hash data written to the hasher, e.g. with [io::copy]
+impl Write for ResumableMac {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Split `len` bytes from the input
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if input.len() < len {
+        return Err(Error::InvalidState);
+    }
+
+    let (head, tail) = input.split_at(len);
+    *input = tail;
+
+    Ok(head)
+}
+
+fn hash_algorithm(algo: AlgorithmMac) -> Algorithm {
+    match algo {
+        AlgorithmMac::HmacSHA1 => Algorithm::SHA1,
+        AlgorithmMac::HmacSHA256 => Algorithm::SHA256,
+        AlgorithmMac::HmacSHA512 => Algorithm::SHA512,
+    }
+}
+
+fn algorithm_id(algo: Algorithm) -> u8 {
+    match algo {
+        Algorithm::SHA1 => 1,
+        Algorithm::SHA256 => 2,
+        Algorithm::SHA384 => 3,
+        Algorithm::SHA512 => 4,
+    }
+}
+
+fn block_size(algo: Algorithm) -> usize {
+    match algo {
+        Algorithm::SHA1 | Algorithm::SHA256 => 64,
+        Algorithm::SHA384 | Algorithm::SHA512 => 128,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Algorithm, AlgorithmMac, Error, ResumableHash, ResumableMac};
+    use crate::sha::{CryptographicHash, CryptographicMac};
+
+    const ALGORITHMS: [Algorithm; 4] = [
+        Algorithm::SHA1,
+        Algorithm::SHA256,
+        Algorithm::SHA384,
+        Algorithm::SHA512,
+    ];
+
+    const ALGORITHMS_MAC: [AlgorithmMac; 3] = [
+        AlgorithmMac::HmacSHA1,
+        AlgorithmMac::HmacSHA256,
+        AlgorithmMac::HmacSHA512,
+    ];
+
+    /// Compare the hashes of all lengths around the block and padding boundaries with the
+    /// RustCrypto hashers
+    #[test]
+    fn lengths() {
+        let input = (0..300u32).map(|i| i as u8).collect::<Vec<_>>();
+
+        for len in 0..input.len() {
+            let input = &input[..len];
+
+            for algo in ALGORITHMS {
+                assert_eq!(
+                    ResumableHash::hash(algo, input),
+                    CryptographicHash::hash(algo, input),
+                    "{algo:?} of {len} bytes"
+                );
+            }
+        }
+    }
+
+    /// Feed the input in chunks of different sizes
+    #[test]
+    fn chunks() {
+        let input = vec![7u8; 1000];
+
+        for algo in ALGORITHMS {
+            let expected = CryptographicHash::hash(algo, &input);
+
+            for chunk_size in [1, 3, 63, 64, 65, 127, 128, 129, 500] {
+                let mut hasher = ResumableHash::new(algo);
+                input
+                    .chunks(chunk_size)
+                    .for_each(|chunk| hasher.update(chunk));
+
+                assert_eq!(
+                    hasher.finalize(),
+                    expected,
+                    "{algo:?} in chunks of {chunk_size}"
+                );
+            }
+        }
+    }
+
+    /// Export the state in the middle of a stream and continue after importing it
+    #[test]
+    fn export_import_state() {
+        let input = (0..1000u32).map(|i| i as u8).collect::<Vec<_>>();
+
+        for algo in ALGORITHMS {
+            for split in [0, 1, 64, 100, 128, 999] {
+                let mut hasher = ResumableHash::new(algo);
+                hasher.update(&input[..split]);
+                let state = hasher.export_state();
+
+                let mut hasher = ResumableHash::import_state(&state).unwrap();
+                assert_eq!(hasher.algorithm(), algo);
+                hasher.update(&input[split..]);
+
+                assert_eq!(hasher.finalize(), CryptographicHash::hash(algo, &input));
+            }
+        }
+    }
+
+    /// Reject a truncated or modified state
+    #[test]
+    fn import_invalid_state() {
+        let mut hasher = ResumableHash::new(Algorithm::SHA256);
+        hasher.update(b"input");
+        let state = hasher.export_state();
+
+        // truncated state
+        let err = ResumableHash::import_state(&state[..state.len() - 1]).unwrap_err();
+        assert!(matches!(err, Error::InvalidState));
+
+        // trailing bytes
+        let mut trailing = state.clone();
+        trailing.push(0);
+        let err = ResumableHash::import_state(&trailing).unwrap_err();
+        assert!(matches!(err, Error::InvalidState));
+
+        // unknown algorithm
+        let mut unknown = state.clone();
+        unknown[2] = 0xff;
+        let err = ResumableHash::import_state(&unknown).unwrap_err();
+        assert!(matches!(err, Error::InvalidState));
+
+        // other version
+        let mut other_version = state.clone();
+        other_version[0] = 2;
+        let err = ResumableHash::import_state(&other_version).unwrap_err();
+        assert!(matches!(err, Error::InvalidState));
+    }
+
+    /// Compare HMAC with the hmac crate, with short keys and keys longer than the block
+    #[test]
+    fn mac_same_as_hmac() {
+        for algo in ALGORITHMS_MAC {
+            for key_len in [0, 20, 64, 65, 128, 131] {
+                let key = vec![0xaa; key_len];
+
+                let mut hasher = ResumableMac::new(algo, &key).unwrap();
+                hasher.update(b"Test Using Larger Than Block-Size Key - Hash Key First");
+
+                assert_eq!(
+                    hasher.finalize(),
+                    CryptographicMac::hash(
+                        algo,
+                        &key,
+                        b"Test Using Larger Than Block-Size Key - Hash Key First"
+                    )
+                    .unwrap(),
+                    "{algo:?} with a key of {key_len} bytes"
+                );
+            }
+        }
+    }
+
+    /// Export the MAC state in the middle of a stream and continue after importing it
+    #[test]
+    fn mac_export_import_state() {
+        for algo in ALGORITHMS_MAC {
+            let mut hasher = ResumableMac::new(algo, b"secret").unwrap();
+            hasher.update(b"in");
+            let state = hasher.export_state();
+
+            let mut hasher = ResumableMac::import_state(&state).unwrap();
+            assert_eq!(hasher.algorithm(), algo);
+            hasher.update(b"put");
+
+            let expected = CryptographicMac::hash(algo, b"secret", b"input").unwrap();
+            hasher.verify(&expected).unwrap();
+        }
+    }
+
+    /// Reject a truncated MAC state or a state of a plain hasher
+    #[test]
+    fn mac_import_invalid_state() {
+        let state = ResumableMac::new(AlgorithmMac::HmacSHA256, b"secret")
+            .unwrap()
+            .export_state();
+
+        let result = ResumableMac::import_state(&state[..state.len() - 1]);
+        assert!(matches!(result, Err(Error::InvalidState)));
+
+        let state = ResumableHash::new(Algorithm::SHA256).export_state();
+        let result = ResumableMac::import_state(&state);
+        assert!(matches!(result, Err(Error::InvalidState)));
+
+        let err = ResumableHash::import_state(
+            &ResumableMac::new(AlgorithmMac::HmacSHA256, b"secret")
+                .unwrap()
+                .export_state(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::InvalidState));
+    }
+}
@@ -1,18 +1,43 @@
-use sha1::{Digest, Sha1};
-use sha2::{Sha256, Sha512};
+use std::io::{self, Write};
+
+use sha1::{Digest as _, Sha1};
+use sha2::{Sha256, Sha384, Sha512};
+
+use super::Digest;
+use crate::Result;
 
 /// Hashing algorithms
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Algorithm {
     /// Read about SHA-1 in [wikipedia](https://en.wikipedia.org/wiki/SHA-1)
     SHA1,
     /// Read about SHA-2 in [wikipedia](https://en.wikipedia.org/wiki/SHA-2)
     SHA256,
     /// Read about SHA-2 in [wikipedia](https://en.wikipedia.org/wiki/SHA-2)
+    SHA384,
+    /// Read about SHA-2 in [wikipedia](https://en.wikipedia.org/wiki/SHA-2)
     SHA512,
 }
 
-/// Compute cryptographic hash from bytes (sha1, sha256, sha512).
+impl Algorithm {
+    /// Length of the computed hash in bytes
+    ///
+    /// ```
+    /// use crypto_utils::sha::Algorithm;
+    ///
+    /// assert_eq!(Algorithm::SHA256.output_len(), 32);
+    /// ```
+    pub fn output_len(&self) -> usize {
+        match self {
+            Self::SHA1 => 20,
+            Self::SHA256 => 32,
+            Self::SHA384 => 48,
+            Self::SHA512 => 64,
+        }
+    }
+}
+
+/// Compute cryptographic hash from bytes (sha1, sha256, sha384, sha512).
 ///
 /// Method 1 (recommend)
 /// ```
@@ -46,13 +71,19 @@ pub enum Algorithm {
 /// # assert_eq!(hash, "21bd12dc183f740ee76f27b78eb39c8ad972a757".to_string())
 /// ```
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum CryptographicHash {
     /// Sha1 hasher
     Sha1(Sha1),
     /// Sha256 hasher
     Sha256(Sha256),
+    /// Sha384 hasher
+    Sha384(Sha384),
     /// Sha512 hasher
     Sha512(Sha512),
+    /// Sha1 hasher with collision detection
+    #[cfg(feature = "sha1dc")]
+    Sha1Checked(Box<sha1_checked::Sha1>),
 }
 
 impl CryptographicHash {
@@ -67,6 +98,9 @@ impl CryptographicHash {
     /// // sha256 hasher
     /// let mut hasher = CryptographicHash::new(Algorithm::SHA256);
     ///
+    /// // sha384 hasher
+    /// let mut hasher = CryptographicHash::new(Algorithm::SHA384);
+    ///
     /// // sha512 hasher
     /// let mut hasher = CryptographicHash::new(Algorithm::SHA512);
     /// ```
@@ -76,11 +110,39 @@ impl CryptographicHash {
             Algorithm::SHA1 => Self::Sha1(Sha1::new()),
             // new Sha256 hasher
             Algorithm::SHA256 => Self::Sha256(Sha256::new()),
+            // new Sha384 hasher
+            Algorithm::SHA384 => Self::Sha384(Sha384::new()),
             // new Sha512 hasher
             Algorithm::SHA512 => Self::Sha512(Sha512::new()),
         }
     }
 
+    /// Create a new Sha hasher which detects the inputs of the known collision attacks
+    ///
+    /// Only [Algorithm::SHA1] has known collisions, the hasher computes the same hashes but
+    /// [CryptographicHash::try_finalize] rejects the attacks. The other algorithms return the
+    /// same hasher as [CryptographicHash::new].
+    ///
+    /// ```
+    /// use crypto_utils::sha::{Algorithm, CryptographicHash};
+    ///
+    /// let mut hasher = CryptographicHash::new_checked(Algorithm::SHA1);
+    /// hasher.update(b"P@ssw0rd");
+    ///
+    /// let hash: Vec<u8> = hasher.try_finalize().unwrap();
+    ///
+    /// # assert_eq!(hex::encode(hash), "21bd12dc183f740ee76f27b78eb39c8ad972a757")
+    /// ```
+    #[cfg(feature = "sha1dc")]
+    pub fn new_checked(algo: Algorithm) -> Self {
+        match algo {
+            // new Sha1 hasher with collision detection
+            Algorithm::SHA1 => Self::Sha1Checked(Box::new(super::checked::unmitigated())),
+            // no known collisions
+            _ => Self::new(algo),
+        }
+    }
+
     /// Set value in the hasher
     ///
     /// ```no_run
@@ -96,13 +158,21 @@ impl CryptographicHash {
             Self::Sha1(sha1) => sha1.update(input),
             // Sha256
             Self::Sha256(sha256) => sha256.update(input),
+            // Sha384
+            Self::Sha384(sha384) => sha384.update(input),
             // Sha512
             Self::Sha512(sha512) => sha512.update(input),
+            // Sha1 with collision detection
+            #[cfg(feature = "sha1dc")]
+            Self::Sha1Checked(sha1) => sha1.update(input),
         }
     }
 
     /// Compute hash
     ///
+    /// A hasher with collision detection returns the SHA-1 hash even if it detected a
+    /// collision attack, use [CryptographicHash::try_finalize] to reject it.
+    ///
     /// ```no_run
     /// # use crypto_utils::sha::{Algorithm, CryptographicHash};
     /// #
@@ -118,8 +188,61 @@ impl CryptographicHash {
             Self::Sha1(sha1) => sha1.finalize_reset().to_vec(),
             // Sha256
             Self::Sha256(sha256) => sha256.finalize_reset().to_vec(),
+            // Sha384
+            Self::Sha384(sha384) => sha384.finalize_reset().to_vec(),
             // Sha512
             Self::Sha512(sha512) => sha512.finalize_reset().to_vec(),
+            // Sha1 with collision detection
+            #[cfg(feature = "sha1dc")]
+            Self::Sha1Checked(sha1) => super::checked::finalize_reset(sha1).hash().to_vec(),
+        }
+    }
+
+    /// Compute hash, rejecting the known collision attacks
+    ///
+    /// Returns [Error::CollisionDetected](crate::Error::CollisionDetected) if the hasher was
+    /// created with [CryptographicHash::new_checked] and the input contains the blocks of a
+    /// collision attack. The other hashers always succeed.
+    pub fn try_finalize(&mut self) -> Result<Vec<u8>> {
+        match self {
+            // Sha1 with collision detection
+            #[cfg(feature = "sha1dc")]
+            Self::Sha1Checked(sha1) => super::checked::check(super::checked::finalize_reset(sha1)),
+            _ => Ok(self.finalize()),
+        }
+    }
+
+    /// Compute hash as a [Digest] which also keeps the algorithm
+    ///
+    /// ```no_run
+    /// # use crypto_utils::sha::{Algorithm, CryptographicHash, Digest};
+    /// #
+    /// # let mut hasher = CryptographicHash::new(Algorithm::SHA1);
+    /// #
+    /// # hasher.update(b"value");
+    /// let digest: Digest = hasher.digest();
+    /// let hash_str: String = digest.to_string();
+    /// ```
+    pub fn digest(&mut self) -> Digest {
+        Digest::new(self.algorithm(), self.finalize())
+    }
+
+    /// Compute hash as a [Digest], rejecting the known collision attacks
+    ///
+    /// See [CryptographicHash::try_finalize].
+    pub fn try_digest(&mut self) -> Result<Digest> {
+        Ok(Digest::new(self.algorithm(), self.try_finalize()?))
+    }
+
+    /// Algorithm of the hasher
+    pub fn algorithm(&self) -> Algorithm {
+        match self {
+            Self::Sha1(_) => Algorithm::SHA1,
+            Self::Sha256(_) => Algorithm::SHA256,
+            Self::Sha384(_) => Algorithm::SHA384,
+            Self::Sha512(_) => Algorithm::SHA512,
+            #[cfg(feature = "sha1dc")]
+            Self::Sha1Checked(_) => Algorithm::SHA1,
         }
     }
 
@@ -148,6 +271,31 @@ impl CryptographicHash {
     }
 }
 
+/// Hash data written to the hasher, e.g. with [io::copy]
+///
+/// ```
+/// use crypto_utils::sha::{Algorithm, CryptographicHash};
+///
+/// let mut hasher = CryptographicHash::new(Algorithm::SHA1);
+///
+/// // stream data to the hasher
+/// std::io::copy(&mut &b"P@ssw0rd"[..], &mut hasher).unwrap();
+///
+/// let hash: String = hex::encode(hasher.finalize());
+///
+/// # assert_eq!(hash, "21bd12dc183f740ee76f27b78eb39c8ad972a757".to_string())
+/// ```
+impl Write for CryptographicHash {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Algorithm, CryptographicHash};
@@ -158,6 +306,8 @@ mod tests {
     const EXPECTED_SHA1: &str = "140f86aae51ab9e1cda9b4254fe98a74eb54c1a1";
     const EXPECTED_SHA256: &str =
         "c96c6d5be8d08a12e7b5cdc1b207fa6b2430974c86803d8891675e76fd992c20";
+    const EXPECTED_SHA384: &str =
+        "4fbd200eb6266698f0846c66607c98797e2b9b3af5bf82aa1aa330a0e2b12aba97755e3bc955c9765e9edcc70278ca2c";
     const EXPECTED_SHA512: &str =
         "dc6d6c30f2be9c976d6318c9a534d85e9a1c3f3608321a04b4678ef408124d45d7164f3e562e68c6c0b6c077340a785824017032fddfa924f4cf400e6cbb6adc";
 
@@ -187,6 +337,19 @@ mod tests {
         assert_eq!(hash, EXPECTED_SHA256.to_string())
     }
 
+    /// Test a Sha384 hasher
+    #[test]
+    fn sha384() {
+        // compute hash
+        let hash_bytes = CryptographicHash::hash(Algorithm::SHA384, INPUT);
+
+        // decode hash to a String
+        let hash = hex::encode(hash_bytes);
+
+        // validate hash
+        assert_eq!(hash, EXPECTED_SHA384.to_string())
+    }
+
     /// Test a Sha512 hasher
     #[test]
     fn sha512() {
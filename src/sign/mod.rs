@@ -0,0 +1,94 @@
+//! Module for detached digital signatures (Ed25519, ECDSA P-256 and ECDSA P-384).
+//!
+//! Signatures are returned as raw bytes: 64 bytes for Ed25519 and the fixed-size `r || s`
+//! encoding for ECDSA (64 bytes for P-256 and 96 bytes for P-384).
+//!
+//! Keys can be imported and exported as raw bytes, DER and PEM. Secret keys use the PKCS#8
+//! format and public keys the SubjectPublicKeyInfo format, the same as `openssl` produces.
+//!
+//! ## Sign and verify
+//!
+//! ```
+//! use crypto_utils::sign::{AlgorithmSign, SigningKey};
+//!
+//! // generate a new keypair
+//! let signing_key = SigningKey::generate(AlgorithmSign::Ed25519);
+//! let verifying_key = signing_key.verifying_key();
+//!
+//! // sign message
+//! let signature: Vec<u8> = signing_key.sign(b"release manifest");
+//!
+//! // verify signature
+//! verifying_key.verify(b"release manifest", &signature).unwrap();
+//! ```
+//!
+//! ## Sign a digest
+//!
+//! ```
+//! use crypto_utils::{
+//!     sha::{Algorithm, CryptographicHash},
+//!     sign::{AlgorithmSign, SigningKey},
+//! };
+//!
+//! let signing_key = SigningKey::generate(AlgorithmSign::EcdsaP256);
+//!
+//! // hash the release manifest
+//! let mut hasher = CryptographicHash::new(Algorithm::SHA256);
+//! hasher.update(b"release manifest");
+//! let digest = hasher.digest();
+//!
+//! // sign digest
+//! let signature = signing_key.sign_digest(&digest).unwrap();
+//!
+//! // verify signature
+//! let verifying_key = signing_key.verifying_key();
+//! verifying_key.verify_digest(&digest, &signature).unwrap();
+//!
+//! // a SHA-256 digest signed with ECDSA P-256 is the same as a signed message
+//! verifying_key.verify(b"release manifest", &signature).unwrap();
+//! ```
+//!
+//! ## Import and export keys
+//!
+//! ```
+//! use crypto_utils::sign::{AlgorithmSign, SigningKey, VerifyingKey};
+//!
+//! let signing_key = SigningKey::generate(AlgorithmSign::EcdsaP384);
+//!
+//! // export keys
+//! let secret_pem: String = signing_key.to_pkcs8_pem().unwrap();
+//! let public_pem: String = signing_key.verifying_key().to_public_key_pem().unwrap();
+//!
+//! // import keys (the algorithm is read from the key)
+//! let signing_key = SigningKey::from_pkcs8_pem(&secret_pem).unwrap();
+//! let verifying_key = VerifyingKey::from_public_key_pem(&public_pem).unwrap();
+//!
+//! assert_eq!(verifying_key.algorithm(), AlgorithmSign::EcdsaP384);
+//! ```
+
+mod signing;
+mod verifying;
+
+pub use signing::*;
+pub use verifying::*;
+
+/// Digital signature algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlgorithmSign {
+    /// Read about Ed25519 in [wikipedia](https://en.wikipedia.org/wiki/EdDSA#Ed25519)
+    Ed25519,
+    /// ECDSA using the NIST P-256 curve and SHA-256
+    EcdsaP256,
+    /// ECDSA using the NIST P-384 curve and SHA-384
+    EcdsaP384,
+}
+
+impl AlgorithmSign {
+    /// Length of the signature in bytes
+    pub fn signature_len(&self) -> usize {
+        match self {
+            Self::Ed25519 | Self::EcdsaP256 => 64,
+            Self::EcdsaP384 => 96,
+        }
+    }
+}
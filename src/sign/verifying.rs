@@ -0,0 +1,306 @@
+use p256::{
+    ecdsa::signature::{hazmat::PrehashVerifier, Verifier},
+    pkcs8::{DecodePublicKey, EncodePublicKey, LineEnding},
+};
+
+use super::AlgorithmSign;
+use crate::{sha::Digest, Error, Result};
+
+/// Public key used to verify signatures made with [SigningKey](super::SigningKey).
+///
+/// ```
+/// use crypto_utils::sign::{AlgorithmSign, SigningKey, VerifyingKey};
+///
+/// let signing_key = SigningKey::generate(AlgorithmSign::Ed25519);
+/// let signature = signing_key.sign(b"input");
+///
+/// // public key shared with the other side
+/// let public_key: Vec<u8> = signing_key.verifying_key().to_bytes();
+///
+/// // import public key
+/// let verifying_key = VerifyingKey::from_bytes(AlgorithmSign::Ed25519, &public_key).unwrap();
+///
+/// // verify signature
+/// verifying_key.verify(b"input", &signature).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyingKey {
+    /// Ed25519 public key
+    Ed25519(ed25519_dalek::VerifyingKey),
+    /// ECDSA P-256 public key
+    EcdsaP256(p256::ecdsa::VerifyingKey),
+    /// ECDSA P-384 public key
+    EcdsaP384(p384::ecdsa::VerifyingKey),
+}
+
+impl VerifyingKey {
+    /// Import a raw public key: 32 bytes for Ed25519 or a SEC1 encoded point
+    /// (compressed or uncompressed) for ECDSA.
+    ///
+    /// ```no_run
+    /// use crypto_utils::sign::{AlgorithmSign, VerifyingKey};
+    ///
+    /// let public_key = hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a").unwrap();
+    ///
+    /// let verifying_key = VerifyingKey::from_bytes(AlgorithmSign::Ed25519, &public_key).unwrap();
+    /// ```
+    pub fn from_bytes(algo: AlgorithmSign, bytes: impl AsRef<[u8]>) -> Result<Self> {
+        let bytes = bytes.as_ref();
+
+        Ok(match algo {
+            AlgorithmSign::Ed25519 => Self::Ed25519(
+                ed25519_dalek::VerifyingKey::from_bytes(
+                    bytes.try_into().map_err(|_| Error::InvalidKeyLength)?,
+                )
+                .map_err(|_| Error::InvalidKey)?,
+            ),
+            AlgorithmSign::EcdsaP256 => Self::EcdsaP256(
+                p256::ecdsa::VerifyingKey::from_sec1_bytes(bytes).map_err(|_| Error::InvalidKey)?,
+            ),
+            AlgorithmSign::EcdsaP384 => Self::EcdsaP384(
+                p384::ecdsa::VerifyingKey::from_sec1_bytes(bytes).map_err(|_| Error::InvalidKey)?,
+            ),
+        })
+    }
+
+    /// Export the raw public key, ECDSA keys are exported as uncompressed SEC1 points.
+    ///
+    /// ```no_run
+    /// # use crypto_utils::sign::{AlgorithmSign, SigningKey};
+    /// #
+    /// # let verifying_key = SigningKey::generate(AlgorithmSign::Ed25519).verifying_key();
+    /// #
+    /// let bytes: Vec<u8> = verifying_key.to_bytes();
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Ed25519(key) => key.to_bytes().to_vec(),
+            Self::EcdsaP256(key) => key.to_encoded_point(false).as_bytes().to_vec(),
+            Self::EcdsaP384(key) => key.to_encoded_point(false).as_bytes().to_vec(),
+        }
+    }
+
+    /// Import a DER encoded SubjectPublicKeyInfo, the algorithm is read from the key.
+    ///
+    /// ```no_run
+    /// # use crypto_utils::sign::{AlgorithmSign, SigningKey, VerifyingKey};
+    /// #
+    /// # let der = SigningKey::generate(AlgorithmSign::Ed25519).verifying_key().to_public_key_der().unwrap();
+    /// #
+    /// let verifying_key = VerifyingKey::from_public_key_der(&der).unwrap();
+    /// ```
+    pub fn from_public_key_der(der: &[u8]) -> Result<Self> {
+        if let Ok(key) = ed25519_dalek::VerifyingKey::from_public_key_der(der) {
+            return Ok(Self::Ed25519(key));
+        }
+
+        if let Ok(key) = p256::ecdsa::VerifyingKey::from_public_key_der(der) {
+            return Ok(Self::EcdsaP256(key));
+        }
+
+        p384::ecdsa::VerifyingKey::from_public_key_der(der)
+            .map(Self::EcdsaP384)
+            .map_err(|_| Error::InvalidKey)
+    }
+
+    /// Export the public key as a DER encoded SubjectPublicKeyInfo.
+    ///
+    /// ```no_run
+    /// # use crypto_utils::sign::{AlgorithmSign, SigningKey};
+    /// #
+    /// # let verifying_key = SigningKey::generate(AlgorithmSign::Ed25519).verifying_key();
+    /// #
+    /// let der: Vec<u8> = verifying_key.to_public_key_der().unwrap();
+    /// ```
+    pub fn to_public_key_der(&self) -> Result<Vec<u8>> {
+        let document = match self {
+            Self::Ed25519(key) => key.to_public_key_der(),
+            Self::EcdsaP256(key) => key.to_public_key_der(),
+            Self::EcdsaP384(key) => key.to_public_key_der(),
+        }
+        .map_err(|_| Error::InvalidKey)?;
+
+        Ok(document.into_vec())
+    }
+
+    /// Import a PEM encoded public key (`-----BEGIN PUBLIC KEY-----`),
+    /// the algorithm is read from the key.
+    ///
+    /// ```no_run
+    /// # use crypto_utils::sign::{AlgorithmSign, SigningKey, VerifyingKey};
+    /// #
+    /// # let pem = SigningKey::generate(AlgorithmSign::Ed25519).verifying_key().to_public_key_pem().unwrap();
+    /// #
+    /// let verifying_key = VerifyingKey::from_public_key_pem(&pem).unwrap();
+    /// ```
+    pub fn from_public_key_pem(pem: &str) -> Result<Self> {
+        if let Ok(key) = ed25519_dalek::VerifyingKey::from_public_key_pem(pem) {
+            return Ok(Self::Ed25519(key));
+        }
+
+        if let Ok(key) = p256::ecdsa::VerifyingKey::from_public_key_pem(pem) {
+            return Ok(Self::EcdsaP256(key));
+        }
+
+        p384::ecdsa::VerifyingKey::from_public_key_pem(pem)
+            .map(Self::EcdsaP384)
+            .map_err(|_| Error::InvalidKey)
+    }
+
+    /// Export the public key as PEM.
+    ///
+    /// ```no_run
+    /// # use crypto_utils::sign::{AlgorithmSign, SigningKey};
+    /// #
+    /// # let verifying_key = SigningKey::generate(AlgorithmSign::Ed25519).verifying_key();
+    /// #
+    /// let pem: String = verifying_key.to_public_key_pem().unwrap();
+    /// ```
+    pub fn to_public_key_pem(&self) -> Result<String> {
+        match self {
+            Self::Ed25519(key) => key.to_public_key_pem(LineEnding::LF),
+            Self::EcdsaP256(key) => key.to_public_key_pem(LineEnding::LF),
+            Self::EcdsaP384(key) => key.to_public_key_pem(LineEnding::LF),
+        }
+        .map_err(|_| Error::InvalidKey)
+    }
+
+    /// Algorithm of the key
+    pub fn algorithm(&self) -> AlgorithmSign {
+        match self {
+            Self::Ed25519(_) => AlgorithmSign::Ed25519,
+            Self::EcdsaP256(_) => AlgorithmSign::EcdsaP256,
+            Self::EcdsaP384(_) => AlgorithmSign::EcdsaP384,
+        }
+    }
+
+    /// Verify the signature of a message, returns [Error::InvalidSignature] if it doesn't match.
+    ///
+    /// ```no_run
+    /// # use crypto_utils::sign::{AlgorithmSign, SigningKey};
+    /// #
+    /// # let signing_key = SigningKey::generate(AlgorithmSign::Ed25519);
+    /// # let signature = signing_key.sign(b"input");
+    /// # let verifying_key = signing_key.verifying_key();
+    /// #
+    /// verifying_key.verify(b"input", &signature).unwrap();
+    /// ```
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<()> {
+        match self {
+            Self::Ed25519(key) => {
+                let signature = ed25519_dalek::Signature::from_slice(signature)
+                    .map_err(|_| Error::InvalidSignature)?;
+                key.verify_strict(message, &signature)
+            }
+            Self::EcdsaP256(key) => {
+                let signature = p256::ecdsa::Signature::from_slice(signature)
+                    .map_err(|_| Error::InvalidSignature)?;
+                key.verify(message, &signature)
+            }
+            Self::EcdsaP384(key) => {
+                let signature = p384::ecdsa::Signature::from_slice(signature)
+                    .map_err(|_| Error::InvalidSignature)?;
+                key.verify(message, &signature)
+            }
+        }
+        .map_err(|_| Error::InvalidSignature)
+    }
+
+    /// Verify the signature of a digest made with [SigningKey::sign_digest](super::SigningKey::sign_digest).
+    ///
+    /// ```no_run
+    /// # use crypto_utils::{sha::{Algorithm, CryptographicHash}, sign::{AlgorithmSign, SigningKey}};
+    /// #
+    /// # let signing_key = SigningKey::generate(AlgorithmSign::EcdsaP256);
+    /// # let verifying_key = signing_key.verifying_key();
+    /// #
+    /// let mut hasher = CryptographicHash::new(Algorithm::SHA256);
+    /// hasher.update(b"input");
+    /// let digest = hasher.digest();
+    /// # let signature = signing_key.sign_digest(&digest).unwrap();
+    ///
+    /// verifying_key.verify_digest(&digest, &signature).unwrap();
+    /// ```
+    pub fn verify_digest(&self, digest: &Digest, signature: &[u8]) -> Result<()> {
+        let prehash = digest.as_bytes();
+
+        match self {
+            Self::Ed25519(_) => self.verify(prehash, signature),
+            Self::EcdsaP256(key) => {
+                let signature = p256::ecdsa::Signature::from_slice(signature)
+                    .map_err(|_| Error::InvalidSignature)?;
+                key.verify_prehash(prehash, &signature)
+                    .map_err(|_| Error::InvalidSignature)
+            }
+            Self::EcdsaP384(key) => {
+                let signature = p384::ecdsa::Signature::from_slice(signature)
+                    .map_err(|_| Error::InvalidSignature)?;
+                key.verify_prehash(prehash, &signature)
+                    .map_err(|_| Error::InvalidSignature)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AlgorithmSign, VerifyingKey};
+    use crate::{sign::SigningKey, Error};
+
+    /// Export and import public keys as raw bytes, DER and PEM
+    #[test]
+    fn import_export() {
+        for algo in [
+            AlgorithmSign::Ed25519,
+            AlgorithmSign::EcdsaP256,
+            AlgorithmSign::EcdsaP384,
+        ] {
+            let verifying_key = SigningKey::generate(algo).verifying_key();
+
+            // raw bytes
+            let key = VerifyingKey::from_bytes(algo, verifying_key.to_bytes()).unwrap();
+            assert_eq!(key, verifying_key);
+
+            // DER
+            let der = verifying_key.to_public_key_der().unwrap();
+            assert_eq!(
+                VerifyingKey::from_public_key_der(&der).unwrap(),
+                verifying_key
+            );
+
+            // PEM
+            let pem = verifying_key.to_public_key_pem().unwrap();
+            assert!(pem.starts_with("-----BEGIN PUBLIC KEY-----"));
+            assert_eq!(
+                VerifyingKey::from_public_key_pem(&pem).unwrap(),
+                verifying_key
+            );
+        }
+    }
+
+    /// Verify signature with a public key of other keypair
+    #[test]
+    fn verify_key_invalid() {
+        let signature = SigningKey::generate(AlgorithmSign::EcdsaP256).sign(b"input");
+
+        let other_key = SigningKey::generate(AlgorithmSign::EcdsaP256).verifying_key();
+
+        let err = other_key.verify(b"input", &signature).unwrap_err();
+
+        assert!(matches!(err, Error::InvalidSignature));
+    }
+
+    /// Verify signature of an invalid length
+    #[test]
+    fn verify_signature_length_invalid() {
+        let signing_key = SigningKey::generate(AlgorithmSign::Ed25519);
+        let signature = signing_key.sign(b"input");
+
+        let err = signing_key
+            .verifying_key()
+            .verify(b"input", &signature[..63])
+            .unwrap_err();
+
+        assert!(matches!(err, Error::InvalidSignature));
+    }
+}
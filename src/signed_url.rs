@@ -0,0 +1,505 @@
+//! Module for signed, expiring URLs (e.g. time-limited download links).
+//!
+//! The `expires`, `key_id` and `signature` query parameters are appended to the URL.
+//! The signature is a HMAC-SHA256 of the path and the sorted query, so the URL can't be
+//! changed and is valid on every host serving the path. The key id selects the key when
+//! verifying, so a new key can be introduced while the URLs signed with the old one are
+//! still valid.
+//!
+//! ```
+//! use std::time::{Duration, SystemTime};
+//!
+//! use crypto_utils::signed_url::{SignedUrl, UrlSigningKey};
+//!
+//! let key = UrlSigningKey::new("2024-01", b"secret").unwrap();
+//!
+//! // link valid for one hour
+//! let expires_at = SystemTime::now() + Duration::from_secs(3600);
+//! let signed = SignedUrl::sign("https://files.example.com/download/report.pdf?inline=1", &key, expires_at).unwrap();
+//!
+//! // verify the link in the file service
+//! let old_key = UrlSigningKey::new("2023-12", b"old secret").unwrap();
+//! let verified = SignedUrl::verify(&signed.url, &[key, old_key]).unwrap();
+//!
+//! assert_eq!(verified.key_id, "2024-01");
+//! ```
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+use crate::{
+    sha::{AlgorithmMac, CryptographicMac},
+    Error, Result,
+};
+
+/// Query parameter with the expiration time (Unix timestamp)
+const EXPIRES: &str = "expires";
+/// Query parameter with the key id
+const KEY_ID: &str = "key_id";
+/// Query parameter with the signature
+const SIGNATURE: &str = "signature";
+
+/// Key signing the URLs, identified by its id.
+#[derive(Clone)]
+pub struct UrlSigningKey {
+    id: String,
+    mac: CryptographicMac,
+}
+
+impl UrlSigningKey {
+    /// Create a key with the id (sent in the URL) and the secret.
+    ///
+    /// ```no_run
+    /// use crypto_utils::signed_url::UrlSigningKey;
+    ///
+    /// let key = UrlSigningKey::new("2024-01", b"secret").unwrap();
+    /// ```
+    pub fn new(id: &str, secret: impl AsRef<[u8]>) -> Result<Self> {
+        Ok(Self {
+            id: id.to_string(),
+            mac: CryptographicMac::new(AlgorithmMac::HmacSHA256, secret)?,
+        })
+    }
+
+    /// Id of the key
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Signed URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedUrl {
+    /// URL with the signature
+    pub url: String,
+    /// Expiration time of the URL
+    pub expires_at: SystemTime,
+    /// Id of the key which signed the URL
+    pub key_id: String,
+}
+
+impl SignedUrl {
+    /// Sign the URL (absolute or only the path), valid until the expiration time.
+    ///
+    /// Signature parameters already present in the URL are replaced.
+    ///
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// use crypto_utils::signed_url::{SignedUrl, UrlSigningKey};
+    ///
+    /// let key = UrlSigningKey::new("2024-01", b"secret").unwrap();
+    /// let expires_at = SystemTime::now() + Duration::from_secs(3600);
+    ///
+    /// let signed = SignedUrl::sign("/download/report.pdf", &key, expires_at).unwrap();
+    ///
+    /// assert!(signed.url.starts_with("/download/report.pdf?expires="));
+    /// ```
+    pub fn sign(url: &str, key: &UrlSigningKey, expires_at: SystemTime) -> Result<Self> {
+        let expires = expires_at
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| Error::InvalidTimestamp)?
+            .as_secs();
+
+        let parts = UrlParts::parse(url);
+
+        // parameters of a previous signature are replaced
+        let mut params = parts.params();
+        params.retain(|(name, _)| !is_signature_param(name));
+        params.push((EXPIRES.into(), expires.to_string().into_bytes()));
+        params.push((KEY_ID.into(), key.id.clone().into_bytes()));
+
+        let signature = signature(key, parts.path, &params);
+
+        let mut query = parts.query_without_signature();
+        for (name, value) in [
+            (EXPIRES, expires.to_string()),
+            (KEY_ID, key.id.clone()),
+            (SIGNATURE, URL_SAFE_NO_PAD.encode(signature)),
+        ] {
+            if !query.is_empty() {
+                query.push('&');
+            }
+            query.push_str(&format!("{name}={}", encode(value.as_bytes())));
+        }
+
+        Ok(Self {
+            url: format!("{}?{query}{}", parts.base, parts.fragment),
+            expires_at: UNIX_EPOCH + Duration::from_secs(expires),
+            key_id: key.id.clone(),
+        })
+    }
+
+    /// Verify the signed URL with one of the keys, selected by the key id in the URL.
+    ///
+    /// Returns [Error::InvalidKey] if none of the keys has the id, [Error::MacMismatch] if the
+    /// URL was changed and [Error::Expired] if it has expired.
+    ///
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// use crypto_utils::signed_url::{SignedUrl, UrlSigningKey};
+    ///
+    /// let key = UrlSigningKey::new("2024-01", b"secret").unwrap();
+    /// let expires_at = SystemTime::now() + Duration::from_secs(3600);
+    ///
+    /// let signed = SignedUrl::sign("/download/report.pdf", &key, expires_at).unwrap();
+    ///
+    /// SignedUrl::verify(&signed.url, &[key]).unwrap();
+    /// ```
+    pub fn verify(url: &str, keys: &[UrlSigningKey]) -> Result<Self> {
+        Self::verify_at(url, keys, SystemTime::now())
+    }
+
+    fn verify_at(url: &str, keys: &[UrlSigningKey], now: SystemTime) -> Result<Self> {
+        let parts = UrlParts::parse(url);
+
+        let mut params = parts.params();
+
+        let position = params
+            .iter()
+            .position(|(name, _)| name == SIGNATURE.as_bytes())
+            .ok_or(Error::InvalidSignature)?;
+        let (_, signature) = params.remove(position);
+
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| Error::InvalidSignature)?;
+
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(n, _)| n == name.as_bytes())
+                .and_then(|(_, value)| String::from_utf8(value.clone()).ok())
+                .ok_or(Error::InvalidSignature)
+        };
+
+        let key_id = param(KEY_ID)?;
+        let expires = param(EXPIRES)?
+            .parse::<u64>()
+            .map_err(|_| Error::InvalidSignature)?;
+
+        let key = keys
+            .iter()
+            .find(|key| key.id == key_id)
+            .ok_or(Error::InvalidKey)?;
+
+        let mut mac = key.mac.clone();
+        mac.update(&signature_input(parts.path, &params));
+        mac.verify(&signature)?;
+
+        let expires_at = UNIX_EPOCH + Duration::from_secs(expires);
+
+        if now >= expires_at {
+            return Err(Error::Expired);
+        }
+
+        Ok(Self {
+            url: url.to_string(),
+            expires_at,
+            key_id,
+        })
+    }
+}
+
+fn signature(key: &UrlSigningKey, path: &str, params: &[Param]) -> Vec<u8> {
+    let mut mac = key.mac.clone();
+    mac.update(&signature_input(path, params));
+    mac.finalize()
+}
+
+/// Canonical `<path>\n<sorted query>` of the URL, the encoding is normalized.
+fn signature_input(path: &str, params: &[Param]) -> Vec<u8> {
+    let path = if path.is_empty() { "/" } else { path };
+
+    let path = path
+        .split('/')
+        .map(|segment| encode(&decode(segment, false)))
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let mut params = params
+        .iter()
+        .map(|(name, value)| format!("{}={}", encode(name), encode(value)))
+        .collect::<Vec<_>>();
+    params.sort();
+
+    format!("{path}\n{}", params.join("&")).into_bytes()
+}
+
+/// Decoded name and value of a query parameter
+type Param = (Vec<u8>, Vec<u8>);
+
+/// URL split into the parts
+struct UrlParts<'a> {
+    /// Scheme, authority and path
+    base: &'a str,
+    /// Path
+    path: &'a str,
+    /// Query without the `?`
+    query: &'a str,
+    /// Fragment with the `#`
+    fragment: &'a str,
+}
+
+impl<'a> UrlParts<'a> {
+    fn parse(url: &'a str) -> Self {
+        let (url, fragment) = match url.find('#') {
+            Some(index) => url.split_at(index),
+            None => (url, ""),
+        };
+
+        let (base, query) = url.split_once('?').unwrap_or((url, ""));
+
+        // skip the scheme and the authority of an absolute URL
+        let path = match base.split_once("://") {
+            Some((_, rest)) => rest.find('/').map(|index| &rest[index..]).unwrap_or(""),
+            None => base,
+        };
+
+        Self {
+            base,
+            path,
+            query,
+            fragment,
+        }
+    }
+
+    /// Decoded query parameters
+    fn params(&self) -> Vec<Param> {
+        self.query
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(|param| {
+                let (name, value) = param.split_once('=').unwrap_or((param, ""));
+
+                (decode(name, true), decode(value, true))
+            })
+            .collect()
+    }
+
+    /// Query as it is, without the signature parameters
+    fn query_without_signature(&self) -> String {
+        self.query
+            .split('&')
+            .filter(|param| {
+                let name = param.split_once('=').map_or(*param, |(name, _)| name);
+                !param.is_empty() && !is_signature_param(&decode(name, true))
+            })
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+}
+
+/// Whether the decoded parameter name is one of the signature parameters
+fn is_signature_param(name: &[u8]) -> bool {
+    [EXPIRES, KEY_ID, SIGNATURE]
+        .map(str::as_bytes)
+        .contains(&name)
+}
+
+/// Percent-encode the bytes, only the unreserved characters are kept.
+fn encode(input: &[u8]) -> String {
+    let mut out = String::with_capacity(input.len());
+
+    for &byte in input {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    out
+}
+
+/// Decode the percent-encoded string, `+` is a space in the query.
+fn decode(input: &str, plus_as_space: bool) -> Vec<u8> {
+    let input = input.as_bytes();
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < input.len() {
+        let escaped = input
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (input[i], escaped) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', _) if plus_as_space => out.push(b' '),
+            (byte, _) => out.push(byte),
+        }
+
+        i += 1;
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use super::{Error, SignedUrl, UrlSigningKey};
+
+    fn key() -> UrlSigningKey {
+        UrlSigningKey::new("key-1", b"secret").unwrap()
+    }
+
+    fn expires_at() -> SystemTime {
+        SystemTime::now() + Duration::from_secs(3600)
+    }
+
+    /// Sign and verify URLs
+    #[test]
+    fn sign_verify() {
+        for url in [
+            "https://files.example.com/download/report.pdf",
+            "https://files.example.com/download/report.pdf?inline=1&name=Q1%20report#page=2",
+            "https://files.example.com",
+            "/download/report.pdf?",
+        ] {
+            let signed = SignedUrl::sign(url, &key(), expires_at()).unwrap();
+
+            let verified = SignedUrl::verify(&signed.url, &[key()]).unwrap();
+
+            assert_eq!(verified, signed, "{url}");
+            assert_eq!(verified.key_id, "key-1");
+        }
+    }
+
+    /// Known signed URL
+    #[test]
+    fn sign_known() {
+        let expires_at = UNIX_EPOCH + Duration::from_secs(1700000000);
+
+        let signed = SignedUrl::sign("/download/a.txt?b=2&a=1#top", &key(), expires_at).unwrap();
+
+        // HMAC-SHA256 of `/download/a.txt\na=1&b=2&expires=1700000000&key_id=key-1`,
+        // computed with Python's hmac module
+        assert_eq!(
+            signed.url,
+            "/download/a.txt?b=2&a=1&expires=1700000000&key_id=key-1\
+             &signature=7rjil11jWtdXP7IQ8aidnRAm5aUKkC50F3pxQ_MajTU#top"
+        );
+
+        // verify before the expiration
+        let now = expires_at - Duration::from_secs(1);
+        SignedUrl::verify_at(&signed.url, &[key()], now).unwrap();
+
+        let err = SignedUrl::verify_at(&signed.url, &[key()], expires_at).unwrap_err();
+        assert!(matches!(err, Error::Expired));
+    }
+
+    /// The encoding of the URL doesn't change the signature
+    #[test]
+    fn verify_equivalent_encoding() {
+        let signed = SignedUrl::sign("/files/a%20b?q=x+y&r=%7e", &key(), expires_at()).unwrap();
+
+        let url = signed
+            .url
+            .replace("/a%20b", "/a%20%62")
+            .replace("q=x+y", "q=x%20y")
+            .replace("r=%7e", "r=~");
+
+        SignedUrl::verify(&url, &[key()]).unwrap();
+    }
+
+    /// Verify URLs with rotated keys
+    #[test]
+    fn key_rotation() {
+        let old_key = UrlSigningKey::new("key-0", b"old secret").unwrap();
+
+        let signed = SignedUrl::sign("/download/report.pdf", &old_key, expires_at()).unwrap();
+
+        // the old key is still accepted
+        let verified = SignedUrl::verify(&signed.url, &[key(), old_key.clone()]).unwrap();
+        assert_eq!(verified.key_id, "key-0");
+
+        // the old key is removed
+        let err = SignedUrl::verify(&signed.url, &[key()]).unwrap_err();
+        assert!(matches!(err, Error::InvalidKey));
+
+        // key with the same id but other secret
+        let err = SignedUrl::verify(
+            &signed.url,
+            &[UrlSigningKey::new("key-0", b"secret").unwrap()],
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::MacMismatch));
+    }
+
+    /// Re-sign a signed URL
+    #[test]
+    fn resign() {
+        let signed = SignedUrl::sign("/download/report.pdf?a=1", &key(), expires_at()).unwrap();
+
+        let new_key = UrlSigningKey::new("key-2", b"new secret").unwrap();
+        let resigned = SignedUrl::sign(&signed.url, &new_key, expires_at()).unwrap();
+
+        assert_eq!(resigned.url.matches("signature=").count(), 1);
+        assert!(resigned
+            .url
+            .starts_with("/download/report.pdf?a=1&expires="));
+
+        SignedUrl::verify(&resigned.url, &[new_key]).unwrap();
+    }
+
+    /// Replace the percent-encoded signature parameters
+    #[test]
+    fn resign_encoded_params() {
+        let url = "/download/report.pdf?a=1&%65xpires=1&key%5Fid=other";
+        let signed = SignedUrl::sign(url, &key(), expires_at()).unwrap();
+
+        assert!(signed.url.starts_with("/download/report.pdf?a=1&expires="));
+        assert!(!signed.url.contains("%65xpires") && !signed.url.contains("key%5Fid"));
+
+        SignedUrl::verify(&signed.url, &[key()]).unwrap();
+    }
+
+    /// Verify modified URLs
+    #[test]
+    fn verify_modified() {
+        let signed = SignedUrl::sign(
+            "https://files.example.com/download/a.pdf?user=1",
+            &key(),
+            expires_at(),
+        )
+        .unwrap();
+
+        for url in [
+            signed.url.replace("/a.pdf", "/b.pdf"),
+            signed.url.replace("user=1", "user=2"),
+            signed.url.replace("user=1", "user=1&admin=1"),
+            signed.url.replace("expires=", "expires=1"),
+        ] {
+            let err = SignedUrl::verify(&url, &[key()]).unwrap_err();
+
+            assert!(matches!(err, Error::MacMismatch), "{url}");
+        }
+
+        // the host is not signed
+        let url = signed.url.replace("files.example.com", "cdn.example.com");
+        SignedUrl::verify(&url, &[key()]).unwrap();
+
+        // missing signature parameters
+        for url in [
+            "https://files.example.com/download/a.pdf",
+            "/a.pdf?expires=1&key_id=key-1",
+            "/a.pdf?key_id=key-1&signature=AAAA",
+            "/a.pdf?expires=x&key_id=key-1&signature=AAAA",
+            "/a.pdf?expires=1&key_id=key-1&signature=***",
+        ] {
+            let err = SignedUrl::verify(url, &[key()]).unwrap_err();
+
+            assert!(matches!(err, Error::InvalidSignature), "{url}");
+        }
+    }
+}
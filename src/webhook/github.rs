@@ -0,0 +1,115 @@
+//! GitHub style webhooks (`X-Hub-Signature-256: sha256=<hex>`), the legacy
+//! `X-Hub-Signature: sha1=<hex>` header is also accepted.
+
+use super::{decode_signature, WebhookVerifier};
+use crate::{
+    sha::{AlgorithmMac, CryptographicMac},
+    Error, Result,
+};
+
+/// Sign the body, returns the value of the `X-Hub-Signature-256` header.
+///
+/// ```
+/// use crypto_utils::webhook::github;
+///
+/// let header: String = github::sign(b"It's a Secret to Everybody", b"Hello, World!").unwrap();
+///
+/// assert_eq!(header, "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17");
+/// ```
+pub fn sign(secret: impl AsRef<[u8]>, body: &[u8]) -> Result<String> {
+    let signature = CryptographicMac::hash(AlgorithmMac::HmacSHA256, secret, body)?;
+
+    Ok(format!("sha256={}", hex::encode(signature)))
+}
+
+/// Create a verifier of the `X-Hub-Signature-256` (or `X-Hub-Signature`) header.
+///
+/// ```
+/// use crypto_utils::webhook::github;
+///
+/// let header = github::sign(b"secret", b"body").unwrap();
+///
+/// let mut verifier = github::verifier(b"secret", &header).unwrap();
+/// verifier.update(b"body");
+/// verifier.verify().unwrap();
+/// ```
+pub fn verifier(secret: impl AsRef<[u8]>, header: &str) -> Result<WebhookVerifier> {
+    let (algo, signature) = match header.trim().split_once('=') {
+        Some(("sha256", signature)) => (AlgorithmMac::HmacSHA256, signature),
+        Some(("sha1", signature)) => (AlgorithmMac::HmacSHA1, signature),
+        _ => return Err(Error::InvalidSignature),
+    };
+
+    let mac = CryptographicMac::new(algo, secret)?;
+
+    Ok(WebhookVerifier::new(
+        mac,
+        vec![decode_signature(signature)?],
+    ))
+}
+
+/// Verify the `X-Hub-Signature-256` (or `X-Hub-Signature`) header of the body.
+///
+/// ```
+/// use crypto_utils::webhook::github;
+///
+/// let header = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+///
+/// github::verify(b"It's a Secret to Everybody", header, b"Hello, World!").unwrap();
+/// ```
+pub fn verify(secret: impl AsRef<[u8]>, header: &str, body: &[u8]) -> Result<()> {
+    let mut verifier = verifier(secret, header)?;
+    verifier.update(body);
+    verifier.verify()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sign, verify, Error};
+
+    const SECRET: &[u8] = b"It's a Secret to Everybody";
+    const BODY: &[u8] = b"Hello, World!";
+
+    /// Example from the GitHub documentation
+    #[test]
+    fn github_docs() {
+        let header = sign(SECRET, BODY).unwrap();
+
+        assert_eq!(
+            header,
+            "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"
+        );
+
+        verify(SECRET, &header, BODY).unwrap();
+    }
+
+    /// Verify the legacy HMAC Sha1 header
+    #[test]
+    fn verify_sha1() {
+        let signature =
+            crate::sha::CryptographicMac::hash(crate::sha::AlgorithmMac::HmacSHA1, SECRET, BODY)
+                .unwrap();
+
+        verify(SECRET, &format!("sha1={}", hex::encode(signature)), BODY).unwrap();
+    }
+
+    /// Verify a modified body
+    #[test]
+    fn verify_body_invalid() {
+        let header = sign(SECRET, BODY).unwrap();
+
+        let err = verify(SECRET, &header, b"Hello, World?").unwrap_err();
+
+        assert!(matches!(err, Error::MacMismatch));
+    }
+
+    /// Verify a malformed header
+    #[test]
+    fn verify_header_invalid() {
+        for header in ["", "757107ea", "md5=757107ea", "sha256=xyz"] {
+            let err = verify(SECRET, header, BODY).unwrap_err();
+
+            assert!(matches!(err, Error::InvalidSignature), "{header}");
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Module for signing and verifying webhook requests.
+//!
+//! Supported formats:
+//!
+//! | Module     | Header                                               | Signed content          |
+//! |:-----------|:-----------------------------------------------------|:------------------------|
+//! | [github]   | `X-Hub-Signature-256: sha256=<hex>`                  | `<body>`                |
+//! | [stripe]   | `Stripe-Signature: t=<timestamp>,v1=<hex>`           | `<timestamp>.<body>`    |
+//! | [slack]    | `X-Slack-Signature: v0=<hex>` and `X-Slack-Request-Timestamp` | `v0:<timestamp>:<body>` |
+//!
+//! Signatures are compared in constant time and the timestamps of Stripe and Slack
+//! are checked against a tolerance to prevent replay attacks.
+//!
+//! ## Verify a webhook
+//!
+//! ```
+//! use crypto_utils::webhook::github;
+//!
+//! let secret = b"It's a Secret to Everybody";
+//! let header = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+//!
+//! github::verify(secret, header, b"Hello, World!").unwrap();
+//! ```
+//!
+//! ## Verify a streamed body
+//!
+//! The body doesn't have to be buffered, it can be written to the [WebhookVerifier]:
+//!
+//! ```
+//! use std::{io, time::Duration};
+//!
+//! use crypto_utils::webhook::{stripe, unix_timestamp};
+//!
+//! let secret = b"whsec_secret";
+//!
+//! // header sent by the other side
+//! let header = stripe::sign(secret, unix_timestamp(), b"{\"id\":\"evt_1\"}").unwrap();
+//!
+//! // stream the body to the verifier
+//! let mut verifier = stripe::verifier(secret, &header, Duration::from_secs(300)).unwrap();
+//! io::copy(&mut &b"{\"id\":\"evt_1\"}"[..], &mut verifier).unwrap();
+//!
+//! verifier.verify().unwrap();
+//! ```
+
+pub mod github;
+pub mod slack;
+pub mod stripe;
+
+use std::{
+    io::{self, Write},
+    time::Duration,
+};
+
+use crate::{sha::CryptographicMac, Error, Result};
+
+/// Used for the timestamps of the signed webhooks.
+///
+/// ```
+/// use crypto_utils::webhook::unix_timestamp;
+///
+/// let timestamp: u64 = unix_timestamp();
+/// ```
+pub use crate::time::unix_timestamp;
+
+/// Verifier of a webhook signature, the body is written to it with [update](Self::update)
+/// or [Write].
+pub struct WebhookVerifier {
+    mac: CryptographicMac,
+    /// Any of the signatures is accepted (e.g. during secret rotation)
+    signatures: Vec<Vec<u8>>,
+}
+
+impl WebhookVerifier {
+    fn new(mac: CryptographicMac, signatures: Vec<Vec<u8>>) -> Self {
+        Self { mac, signatures }
+    }
+
+    /// Add a chunk of the body
+    pub fn update(&mut self, input: &[u8]) {
+        self.mac.update(input);
+    }
+
+    /// Verify the signature of the body, returns [Error::MacMismatch] if it doesn't match.
+    pub fn verify(self) -> Result<()> {
+        let matches = self
+            .signatures
+            .iter()
+            .filter(|signature| self.mac.clone().verify(signature).is_ok())
+            .count();
+
+        if matches == 0 {
+            return Err(Error::MacMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+impl Write for WebhookVerifier {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Check that the timestamp is within the tolerance of the current time.
+fn check_timestamp(timestamp: u64, now: u64, tolerance: Duration) -> Result<()> {
+    if now.abs_diff(timestamp) > tolerance.as_secs() {
+        return Err(Error::InvalidTimestamp);
+    }
+
+    Ok(())
+}
+
+/// Decode a hex encoded signature.
+fn decode_signature(signature: &str) -> Result<Vec<u8>> {
+    hex::decode(signature.trim()).map_err(|_| Error::InvalidSignature)
+}
@@ -0,0 +1,206 @@
+//! Slack style webhooks (`X-Slack-Signature: v0=<hex>` and `X-Slack-Request-Timestamp`).
+//!
+//! The signed content is the `v0:<timestamp>:<body>` base string.
+
+use std::time::Duration;
+
+use super::{check_timestamp, decode_signature, unix_timestamp, WebhookVerifier};
+use crate::{
+    sha::{AlgorithmMac, CryptographicMac},
+    Error, Result,
+};
+
+/// Version of the base string
+const VERSION: &str = "v0";
+
+/// Sign the body, returns the value of the `X-Slack-Signature` header.
+///
+/// The timestamp is sent in the `X-Slack-Request-Timestamp` header.
+///
+/// ```
+/// use crypto_utils::webhook::{slack, unix_timestamp};
+///
+/// let timestamp = unix_timestamp();
+///
+/// let signature: String = slack::sign(b"signing secret", timestamp, b"body").unwrap();
+/// ```
+pub fn sign(secret: impl AsRef<[u8]>, timestamp: u64, body: &[u8]) -> Result<String> {
+    let mut mac = base_string_mac(secret, timestamp)?;
+    mac.update(body);
+
+    Ok(format!("{VERSION}={}", hex::encode(mac.finalize())))
+}
+
+/// Create a verifier of the `X-Slack-Request-Timestamp` and `X-Slack-Signature` headers.
+///
+/// Returns [Error::InvalidTimestamp] if the timestamp differs from the current time
+/// by more than the tolerance (Slack recommends 5 minutes).
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use crypto_utils::webhook::{slack, unix_timestamp};
+///
+/// let timestamp = unix_timestamp().to_string();
+/// let signature = slack::sign(b"secret", unix_timestamp(), b"body").unwrap();
+///
+/// let mut verifier = slack::verifier(b"secret", &timestamp, &signature, Duration::from_secs(300)).unwrap();
+/// verifier.update(b"body");
+/// verifier.verify().unwrap();
+/// ```
+pub fn verifier(
+    secret: impl AsRef<[u8]>,
+    timestamp: &str,
+    signature: &str,
+    tolerance: Duration,
+) -> Result<WebhookVerifier> {
+    verifier_at(secret, timestamp, signature, tolerance, unix_timestamp())
+}
+
+fn verifier_at(
+    secret: impl AsRef<[u8]>,
+    timestamp: &str,
+    signature: &str,
+    tolerance: Duration,
+    now: u64,
+) -> Result<WebhookVerifier> {
+    let timestamp = timestamp
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| Error::InvalidTimestamp)?;
+
+    let signature = match signature.trim().split_once('=') {
+        Some((VERSION, signature)) => decode_signature(signature)?,
+        _ => return Err(Error::InvalidSignature),
+    };
+
+    check_timestamp(timestamp, now, tolerance)?;
+
+    Ok(WebhookVerifier::new(
+        base_string_mac(secret, timestamp)?,
+        vec![signature],
+    ))
+}
+
+/// Verify the `X-Slack-Request-Timestamp` and `X-Slack-Signature` headers of the body.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use crypto_utils::webhook::{slack, unix_timestamp};
+///
+/// let timestamp = unix_timestamp();
+/// let signature = slack::sign(b"secret", timestamp, b"body").unwrap();
+///
+/// slack::verify(b"secret", &timestamp.to_string(), &signature, b"body", Duration::from_secs(300)).unwrap();
+/// ```
+pub fn verify(
+    secret: impl AsRef<[u8]>,
+    timestamp: &str,
+    signature: &str,
+    body: &[u8],
+    tolerance: Duration,
+) -> Result<()> {
+    let mut verifier = verifier(secret, timestamp, signature, tolerance)?;
+    verifier.update(body);
+    verifier.verify()
+}
+
+/// MAC with the `v0:<timestamp>:` prefix of the base string
+fn base_string_mac(secret: impl AsRef<[u8]>, timestamp: u64) -> Result<CryptographicMac> {
+    let mut mac = CryptographicMac::new(AlgorithmMac::HmacSHA256, secret)?;
+    mac.update(format!("{VERSION}:{timestamp}:").as_bytes());
+
+    Ok(mac)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{sign, verifier_at, verify, Error};
+    use crate::webhook::unix_timestamp;
+
+    const TOLERANCE: Duration = Duration::from_secs(300);
+
+    /// Example from the Slack documentation
+    #[test]
+    fn slack_docs() {
+        let secret = b"8f742231b10e8888abcd99yyyzzz85a5";
+        let timestamp = 1531420618;
+        let body = b"token=xyzz0WbapA4vBCDEFasx0q6G&team_id=T1DC2JH3J&team_domain=testteamnow&channel_id=G8PSS9T3V&channel_name=foobar&user_id=U2CERLKJA&user_name=roadrunner&command=%2Fwebhook-collect&text=&response_url=https%3A%2F%2Fhooks.slack.com%2Fcommands%2FT1DC2JH3J%2F397700885554%2F96rGlfmibIGlgcZRskXaIFfN&trigger_id=398738663015.47445629121.803a0bc887a14d10d2c447fce8b6703c";
+        let expected = "v0=a2114d57b48eac39b9ad189dd8316235a7b4a8d21a10bd27519666489c69b503";
+
+        assert_eq!(sign(secret, timestamp, body).unwrap(), expected);
+
+        // verify at the time of signing
+        let mut verifier =
+            verifier_at(secret, "1531420618", expected, TOLERANCE, timestamp).unwrap();
+        verifier.update(body);
+        verifier.verify().unwrap();
+    }
+
+    /// Sign and verify a webhook
+    #[test]
+    fn sign_verify() {
+        let timestamp = unix_timestamp();
+        let signature = sign(b"secret", timestamp, b"body").unwrap();
+
+        verify(
+            b"secret",
+            &timestamp.to_string(),
+            &signature,
+            b"body",
+            TOLERANCE,
+        )
+        .unwrap();
+    }
+
+    /// Verify a webhook with a replayed timestamp
+    #[test]
+    fn verify_timestamp_invalid() {
+        let timestamp = unix_timestamp() - 600;
+        let signature = sign(b"secret", timestamp, b"body").unwrap();
+
+        let err = verify(
+            b"secret",
+            &timestamp.to_string(),
+            &signature,
+            b"body",
+            TOLERANCE,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, Error::InvalidTimestamp));
+    }
+
+    /// Verify a signature computed for other timestamp
+    #[test]
+    fn verify_signature_invalid() {
+        let timestamp = unix_timestamp();
+        let signature = sign(b"secret", timestamp - 1, b"body").unwrap();
+
+        let err = verify(
+            b"secret",
+            &timestamp.to_string(),
+            &signature,
+            b"body",
+            TOLERANCE,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, Error::MacMismatch));
+
+        // unknown version
+        let err = verify(
+            b"secret",
+            &timestamp.to_string(),
+            "v1=00",
+            b"body",
+            TOLERANCE,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, Error::InvalidSignature));
+    }
+}
@@ -0,0 +1,199 @@
+//! Stripe style webhooks (`Stripe-Signature: t=<timestamp>,v1=<hex>`).
+//!
+//! The signed content is `<timestamp>.<body>`, the header can contain several `v1` signatures
+//! (e.g. during secret rotation) and any of them is accepted.
+
+use std::time::Duration;
+
+use super::{check_timestamp, decode_signature, unix_timestamp, WebhookVerifier};
+use crate::{
+    sha::{AlgorithmMac, CryptographicMac},
+    Error, Result,
+};
+
+/// Sign the body, returns the value of the `Stripe-Signature` header.
+///
+/// ```
+/// use crypto_utils::webhook::{stripe, unix_timestamp};
+///
+/// let header: String = stripe::sign(b"whsec_secret", unix_timestamp(), b"body").unwrap();
+/// ```
+pub fn sign(secret: impl AsRef<[u8]>, timestamp: u64, body: &[u8]) -> Result<String> {
+    let mut mac = CryptographicMac::new(AlgorithmMac::HmacSHA256, secret)?;
+    mac.update(format!("{timestamp}.").as_bytes());
+    mac.update(body);
+
+    Ok(format!("t={timestamp},v1={}", hex::encode(mac.finalize())))
+}
+
+/// Create a verifier of the `Stripe-Signature` header.
+///
+/// Returns [Error::InvalidTimestamp] if the timestamp differs from the current time
+/// by more than the tolerance (Stripe uses 5 minutes).
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use crypto_utils::webhook::{stripe, unix_timestamp};
+///
+/// let header = stripe::sign(b"secret", unix_timestamp(), b"body").unwrap();
+///
+/// let mut verifier = stripe::verifier(b"secret", &header, Duration::from_secs(300)).unwrap();
+/// verifier.update(b"body");
+/// verifier.verify().unwrap();
+/// ```
+pub fn verifier(
+    secret: impl AsRef<[u8]>,
+    header: &str,
+    tolerance: Duration,
+) -> Result<WebhookVerifier> {
+    verifier_at(secret, header, tolerance, unix_timestamp())
+}
+
+fn verifier_at(
+    secret: impl AsRef<[u8]>,
+    header: &str,
+    tolerance: Duration,
+    now: u64,
+) -> Result<WebhookVerifier> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+
+    for item in header.split(',') {
+        match item.trim().split_once('=') {
+            Some(("t", value)) => {
+                timestamp = Some(value.parse::<u64>().map_err(|_| Error::InvalidSignature)?)
+            }
+            Some(("v1", value)) => signatures.push(decode_signature(value)?),
+            // other schemes (e.g. `v0` test signatures) are ignored
+            Some(_) => {}
+            None => return Err(Error::InvalidSignature),
+        }
+    }
+
+    let timestamp = timestamp.ok_or(Error::InvalidSignature)?;
+
+    if signatures.is_empty() {
+        return Err(Error::InvalidSignature);
+    }
+
+    check_timestamp(timestamp, now, tolerance)?;
+
+    let mut mac = CryptographicMac::new(AlgorithmMac::HmacSHA256, secret)?;
+    mac.update(format!("{timestamp}.").as_bytes());
+
+    Ok(WebhookVerifier::new(mac, signatures))
+}
+
+/// Verify the `Stripe-Signature` header of the body.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use crypto_utils::webhook::{stripe, unix_timestamp};
+///
+/// let header = stripe::sign(b"secret", unix_timestamp(), b"body").unwrap();
+///
+/// stripe::verify(b"secret", &header, b"body", Duration::from_secs(300)).unwrap();
+/// ```
+pub fn verify(
+    secret: impl AsRef<[u8]>,
+    header: &str,
+    body: &[u8],
+    tolerance: Duration,
+) -> Result<()> {
+    let mut verifier = verifier(secret, header, tolerance)?;
+    verifier.update(body);
+    verifier.verify()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{sign, verifier_at, verify, Error};
+    use crate::webhook::unix_timestamp;
+
+    const SECRET: &[u8] = b"whsec_test_secret";
+    const BODY: &[u8] = br#"{"id":"evt_test_webhook","object":"event"}"#;
+    const TOLERANCE: Duration = Duration::from_secs(300);
+
+    /// Sign and verify a webhook
+    #[test]
+    fn sign_verify() {
+        let header = sign(SECRET, unix_timestamp(), BODY).unwrap();
+
+        verify(SECRET, &header, BODY, TOLERANCE).unwrap();
+    }
+
+    /// Known signature of `1492774577.<body>`
+    #[test]
+    fn sign_known() {
+        let header = sign(b"secret", 1492774577, b"body").unwrap();
+
+        // computed with Python's hmac module
+        assert_eq!(
+            header,
+            "t=1492774577,v1=c84e544ce1620f722bc4e0190809549333e33c05e24dc11fa788efaacb6e6c4b"
+        );
+
+        // verify at the time of signing
+        let mut verifier = verifier_at(b"secret", &header, TOLERANCE, 1492774577 + 10).unwrap();
+        verifier.update(b"body");
+        verifier.verify().unwrap();
+    }
+
+    /// Verify header with several signatures, one of them valid
+    #[test]
+    fn verify_rotated_secret() {
+        let timestamp = unix_timestamp();
+        let header = sign(SECRET, timestamp, BODY).unwrap();
+        let old_header = sign(b"old secret", timestamp, BODY).unwrap();
+
+        // combine signatures of both secrets
+        let old_signature = old_header.split_once(",v1=").unwrap().1;
+        let header = format!("{header},v1={old_signature},v0=0000");
+
+        verify(SECRET, &header, BODY, TOLERANCE).unwrap();
+        verify(b"old secret", &header, BODY, TOLERANCE).unwrap();
+
+        let err = verify(b"other secret", &header, BODY, TOLERANCE).unwrap_err();
+        assert!(matches!(err, Error::MacMismatch));
+    }
+
+    /// Verify a webhook signed outside of the tolerance
+    #[test]
+    fn verify_timestamp_invalid() {
+        let header = sign(SECRET, unix_timestamp() - 600, BODY).unwrap();
+
+        let err = verify(SECRET, &header, BODY, TOLERANCE).unwrap_err();
+
+        assert!(matches!(err, Error::InvalidTimestamp));
+    }
+
+    /// Verify a modified body
+    #[test]
+    fn verify_body_invalid() {
+        let header = sign(SECRET, unix_timestamp(), BODY).unwrap();
+
+        let err = verify(SECRET, &header, b"{}", TOLERANCE).unwrap_err();
+
+        assert!(matches!(err, Error::MacMismatch));
+    }
+
+    /// Verify a malformed header
+    #[test]
+    fn verify_header_invalid() {
+        for header in [
+            "",
+            "t=abc,v1=00",
+            "t=1492774577",
+            "v1=00",
+            "t=1492774577,v1=xyz",
+        ] {
+            let err = verify(SECRET, header, BODY, TOLERANCE).unwrap_err();
+
+            assert!(matches!(err, Error::InvalidSignature), "{header}");
+        }
+    }
+}